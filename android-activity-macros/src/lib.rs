@@ -0,0 +1,167 @@
+//! Procedural macros for `android-activity`
+//!
+//! These are re-exported by `android-activity` when its `macros` feature is enabled
+//! and shouldn't need to be used directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, AttributeArgs, ItemFn, Lit, Meta, NestedMeta};
+
+struct MainArgs {
+    logger: bool,
+    log_filter: Option<String>,
+    panic_hook: bool,
+    stdio: bool,
+    stdio_tag: Option<String>,
+    stderr_level: Option<proc_macro2::TokenStream>,
+}
+
+/// Maps a `log::Level` name to the corresponding variant
+fn parse_level(value: &syn::LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let level = match value.value().to_ascii_lowercase().as_str() {
+        "error" => quote!(Error),
+        "warn" => quote!(Warn),
+        "info" => quote!(Info),
+        "debug" => quote!(Debug),
+        "trace" => quote!(Trace),
+        _ => return Err(syn::Error::new(value.span(),
+            "Expected one of \"error\", \"warn\", \"info\", \"debug\" or \"trace\"")),
+    };
+    Ok(quote!(::android_activity::__macro_support::Level::#level))
+}
+
+impl MainArgs {
+    fn parse(args: AttributeArgs) -> syn::Result<Self> {
+        let mut parsed = MainArgs {
+            logger: true,
+            log_filter: None,
+            panic_hook: true,
+            stdio: true,
+            stdio_tag: None,
+            stderr_level: None,
+        };
+        for arg in args {
+            let nv = match arg {
+                NestedMeta::Meta(Meta::NameValue(nv)) => nv,
+                other => return Err(syn::Error::new(other.span(), "Expected `name = value`")),
+            };
+            let name = nv.path.get_ident().map(|ident| ident.to_string()).unwrap_or_default();
+            match (name.as_str(), &nv.lit) {
+                ("logger", Lit::Bool(value)) => parsed.logger = value.value,
+                ("panic_hook", Lit::Bool(value)) => parsed.panic_hook = value.value,
+                ("stdio", Lit::Bool(value)) => parsed.stdio = value.value,
+                ("log_filter", Lit::Str(value)) => parsed.log_filter = Some(value.value()),
+                ("stdio_tag", Lit::Str(value)) => parsed.stdio_tag = Some(value.value()),
+                ("stderr_level", Lit::Str(value)) => parsed.stderr_level = Some(parse_level(value)?),
+                ("logger" | "panic_hook" | "stdio", lit) => return Err(syn::Error::new(lit.span(), "Expected `true` or `false`")),
+                ("log_filter" | "stdio_tag" | "stderr_level", lit) => return Err(syn::Error::new(lit.span(), "Expected a string")),
+                _ => return Err(syn::Error::new(nv.path.span(),
+                    "Unknown argument, expected one of `logger`, `log_filter`, `panic_hook`, `stdio`, \
+                     `stdio_tag` or `stderr_level`")),
+            }
+        }
+        if parsed.log_filter.is_some() && !parsed.logger {
+            return Err(syn::Error::new(Span::call_site(), "`log_filter` can't be used with `logger = false`"));
+        }
+        if (parsed.stdio_tag.is_some() || parsed.stderr_level.is_some()) && !parsed.stdio {
+            return Err(syn::Error::new(Span::call_site(),
+                "`stdio_tag` and `stderr_level` can't be used with `stdio = false`"));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Marks the function that the application's `Activity` runs, on a dedicated thread,
+/// once it has been created
+///
+/// The function takes an `AndroidApp` and returns either `()` or a `Result<(), E>`
+/// where `E: Debug`. If it returns an error then the error is logged and the
+/// `Activity` is finished, with any remaining commands from the Java main thread
+/// being handled until the `Activity` has been destroyed.
+///
+/// Before the function is called, stdout and stderr are redirected to logcat via an
+/// `android_activity::StdioRedirect`, the `android_activity::logger` is installed
+/// (logging messages up to `Info` level unless a `log_filter` is given) and so is an
+/// `android_activity::PanicHook`. Each can be disabled via arguments:
+///
+/// ```ignore
+/// #[android_activity::main(log_filter = "info,my_app=trace", panic_hook = false)]
+/// fn android_main(app: AndroidApp) -> Result<(), Box<dyn std::error::Error>> {
+///     ...
+/// }
+/// ```
+///
+/// The redirection can be configured with a `stdio_tag`, for the logcat tag, and a
+/// `stderr_level` (one of `"error"`, `"warn"`, `"info"`, `"debug"` or `"trace"`) for
+/// the priority of lines written to stderr, or disabled with `stdio = false`.
+///
+/// The function is exported via `android_activity::export_main!`, and is otherwise
+/// left as it is, so it can still be called directly, e.g. from tests.
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let main_fn = parse_macro_input!(item as ItemFn);
+    match expand_main(args, main_fn) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_main(args: AttributeArgs, main_fn: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let args = MainArgs::parse(args)?;
+
+    let sig = &main_fn.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new(asyncness.span(), "The `android_main` function can't be async"));
+    }
+    if !sig.generics.params.is_empty() || sig.generics.where_clause.is_some() {
+        return Err(syn::Error::new(sig.generics.span(), "The `android_main` function can't be generic"));
+    }
+    if sig.inputs.len() != 1 || sig.variadic.is_some() {
+        return Err(syn::Error::new(sig.inputs.span(), "The `android_main` function must take a single `AndroidApp` argument"));
+    }
+
+    let ident = &sig.ident;
+    // The entry point only exists when the function does (e.g. for `#[cfg(target_os = "android")]`)
+    let cfgs = main_fn.attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+    let logger = args.logger;
+    let panic_hook = args.panic_hook;
+    let log_filter = match &args.log_filter {
+        Some(filter) => quote!(::core::option::Option::Some(#filter)),
+        None => quote!(::core::option::Option::None),
+    };
+    let stdio = args.stdio;
+    let stdio_tag = match &args.stdio_tag {
+        Some(tag) => quote!(::core::option::Option::Some(#tag)),
+        None => quote!(::core::option::Option::None),
+    };
+    let stderr_level = match &args.stderr_level {
+        Some(level) => quote!(::core::option::Option::Some(#level)),
+        None => quote!(::core::option::Option::None),
+    };
+    let run_main = quote_spanned! {sig.span()=>
+        ::android_activity::__macro_support::run_main(app, options, #ident)
+    };
+
+    Ok(quote! {
+        #main_fn
+
+        #(#cfgs)*
+        ::android_activity::export_main!({
+            fn android_activity_main(app: ::android_activity::AndroidApp) {
+                let options = ::android_activity::__macro_support::MainOptions {
+                    logger: #logger,
+                    log_filter: #log_filter,
+                    panic_hook: #panic_hook,
+                    stdio: #stdio,
+                    stdio_tag: #stdio_tag,
+                    stderr_level: #stderr_level,
+                };
+                #run_main
+            }
+            android_activity_main
+        });
+    })
+}
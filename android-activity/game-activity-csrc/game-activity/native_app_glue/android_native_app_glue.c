@@ -281,6 +281,7 @@ static struct android_app* android_app_create(GameActivity* activity,
 
     android_app->keyEventFilter = default_key_filter;
     android_app->motionEventFilter = default_motion_filter;
+    android_app->editorAction = -1;
 
     LOGV("Launching android_app_entry in a thread");
     pthread_attr_t attr;
@@ -343,15 +344,27 @@ static void android_app_free(struct android_app* android_app) {
     close(android_app->msgwrite);
     pthread_cond_destroy(&android_app->cond);
     pthread_mutex_destroy(&android_app->mutex);
+    for (int i = 0; i < NATIVE_APP_GLUE_MAX_INPUT_BUFFERS; i++) {
+        struct android_input_buffer* inputBuffer = &android_app->inputBuffers[i];
+        free(inputBuffer->motionEvents);
+        free(inputBuffer->historicalAxisSamples);
+        free(inputBuffer->keyEvents);
+    }
     free(android_app);
 }
 
+// The android_app that onEditorActionNative() reports editor actions to
+static struct android_app* editorActionApp = NULL;
+
 static inline struct android_app* ToApp(GameActivity* activity) {
     return (struct android_app*)activity->instance;
 }
 
 static void onDestroy(GameActivity* activity) {
     LOGV("Destroy: %p", activity);
+    if (editorActionApp == ToApp(activity)) {
+        editorActionApp = NULL;
+    }
     android_app_free(ToApp(activity));
 }
 
@@ -408,7 +421,11 @@ static void onConfigurationChanged(GameActivity* activity) {
 
 static void onTrimMemory(GameActivity* activity, int level) {
     LOGV("TrimMemory: %p %d", activity, level);
-    android_app_write_cmd(ToApp(activity), APP_CMD_LOW_MEMORY);
+    struct android_app* android_app = ToApp(activity);
+    pthread_mutex_lock(&android_app->mutex);
+    android_app->trimMemoryLevel = level;
+    pthread_mutex_unlock(&android_app->mutex);
+    android_app_write_cmd(android_app, APP_CMD_LOW_MEMORY);
 }
 
 static void onWindowFocusChanged(GameActivity* activity, bool focused) {
@@ -449,6 +466,106 @@ void android_app_set_motion_event_filter(struct android_app* app,
     pthread_mutex_unlock(&app->mutex);
 }
 
+#define INITIAL_INPUT_BUFFER_CAPACITY 16
+
+// Makes space for `count` more elements after the first `used` elements of
+// `*array`, doubling its capacity as needed, up to `maxCapacity` elements.
+// Returns false if the array can't be grown enough.
+static bool reserve_input_buffer_space(void** array, uint64_t* capacity,
+                                       uint64_t used, uint64_t count,
+                                       uint64_t maxCapacity,
+                                       size_t elementSize) {
+    uint64_t needed = used + count;
+    if (needed <= *capacity) {
+        return true;
+    }
+    if (needed > maxCapacity) {
+        return false;
+    }
+
+    uint64_t newCapacity =
+        *capacity > 0 ? *capacity : INITIAL_INPUT_BUFFER_CAPACITY;
+    while (newCapacity < needed) {
+        newCapacity *= 2;
+    }
+    if (newCapacity > maxCapacity) {
+        newCapacity = maxCapacity;
+    }
+
+    void* newArray = realloc(*array, newCapacity * elementSize);
+    if (newArray == NULL) {
+        LOGE("Failed to grow input buffer to %llu elements",
+             (unsigned long long)newCapacity);
+        return false;
+    }
+    *array = newArray;
+    *capacity = newCapacity;
+    return true;
+}
+
+// Called with the mutex held, after buffering the key or motion event with the
+// given number, to wait for the application to finish handling it. This is
+// skipped if the application isn't running or hasn't finished handling the
+// events that were buffered before this one, so that the Java main thread isn't
+// held up by an application that isn't keeping up with its input.
+static bool wait_for_input_event(struct android_app* android_app,
+                                 uint64_t serial, const uint64_t* finished,
+                                 const bool* handled) {
+    if (!android_app->running || android_app->destroyRequested ||
+        *finished + 1 != serial) {
+        return true;
+    }
+    if (android_app->looper != NULL) {
+        ALooper_wake(android_app->looper);
+    }
+
+    struct timespec deadline;
+    clock_gettime(CLOCK_REALTIME, &deadline);
+    deadline.tv_sec += NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS / 1000;
+    deadline.tv_nsec += (NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS % 1000) * 1000000L;
+    if (deadline.tv_nsec >= 1000000000L) {
+        deadline.tv_sec += 1;
+        deadline.tv_nsec -= 1000000000L;
+    }
+    while (*finished < serial) {
+        if (pthread_cond_timedwait(&android_app->cond, &android_app->mutex,
+                                   &deadline) == ETIMEDOUT) {
+            LOGW_ONCE("Input event wasn't handled within"
+                 " NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS (%d), so it will be"
+                 " reported as handled", NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS);
+            return true;
+        }
+    }
+    // No more events can be buffered while this thread is waiting, so the
+    // last finished event is the one that was waited for
+    return *handled;
+}
+
+static void finish_input_event(struct android_app* android_app,
+                               uint64_t* finished, const uint64_t* dispatched,
+                               bool* lastHandled, bool handled) {
+    pthread_mutex_lock(&android_app->mutex);
+    if (*finished < *dispatched) {
+        ++*finished;
+        *lastHandled = handled;
+        pthread_cond_broadcast(&android_app->cond);
+    }
+    pthread_mutex_unlock(&android_app->mutex);
+}
+
+// Called with the mutex held, when the input buffers are swapped
+static void dispatch_input_events(struct android_app* android_app,
+                                  uint64_t* finished, uint64_t* dispatched,
+                                  uint64_t queued, bool* lastHandled) {
+    // Events from the previous buffer that weren't finished are handled
+    if (*finished < *dispatched) {
+        *finished = *dispatched;
+        *lastHandled = true;
+        pthread_cond_broadcast(&android_app->cond);
+    }
+    *dispatched = queued;
+}
+
 static bool onTouchEvent(GameActivity* activity,
                          const GameActivityMotionEvent* event,
                          const GameActivityHistoricalPointerAxes* historical,
@@ -466,27 +583,52 @@ static bool onTouchEvent(GameActivity* activity,
         &android_app->inputBuffers[android_app->currentInputBuffer];
 
     // Add to the list of active motion events
-    if (inputBuffer->motionEventsCount <
-        NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS) {
+    if (reserve_input_buffer_space(
+            (void**)&inputBuffer->motionEvents,
+            &inputBuffer->motionEventsCapacity, inputBuffer->motionEventsCount,
+            1, NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS,
+            sizeof(GameActivityMotionEvent))) {
         int new_ix = inputBuffer->motionEventsCount;
         memcpy(&inputBuffer->motionEvents[new_ix], event,
                sizeof(GameActivityMotionEvent));
         ++inputBuffer->motionEventsCount;
+        uint64_t serial = ++android_app->motionEventsQueued;
 
-        if (inputBuffer->historicalSamplesCount + historicalLen <=
-            NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES) {
+        if (historicalLen > 0 &&
+            reserve_input_buffer_space(
+                (void**)&inputBuffer->historicalAxisSamples,
+                &inputBuffer->historicalSamplesCapacity,
+                inputBuffer->historicalSamplesCount, historicalLen,
+                NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES,
+                sizeof(GameActivityHistoricalPointerAxes))) {
 
             int start_ix = inputBuffer->historicalSamplesCount;
             memcpy(&inputBuffer->historicalAxisSamples[start_ix], historical,
                     sizeof(historical[0]) * historicalLen);
-            inputBuffer->historicalSamplesCount += event->historicalCount;
+            inputBuffer->historicalSamplesCount += historicalLen;
 
             inputBuffer->motionEvents[new_ix].historicalStart = start_ix;
             inputBuffer->motionEvents[new_ix].historicalCount = historicalLen;
         } else {
+            // The event is still delivered, with its current sample, but any
+            // historical samples that were batched into it are lost
+            if (historicalLen > 0) {
+                LOGW_ONCE("Historical motion samples will be dropped because the number of"
+                     " unconsumed samples exceeded NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES"
+                     " (%d). Consider setting NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES_OVERRIDE"
+                     " to a larger value",
+                     NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES);
+            }
             inputBuffer->motionEvents[new_ix].historicalCount = 0;
         }
+
+        bool handled = wait_for_input_event(
+            android_app, serial, &android_app->motionEventsFinished,
+            &android_app->motionEventHandled);
+        pthread_mutex_unlock(&android_app->mutex);
+        return handled;
     } else {
+        ++android_app->droppedInputEvents;
         LOGW_ONCE("Motion event will be dropped because the number of unconsumed motion"
              " events exceeded NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS (%d). Consider setting"
              " NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS_OVERRIDE to a larger value",
@@ -503,6 +645,15 @@ struct android_input_buffer* android_app_swap_input_buffers(
     struct android_input_buffer* inputBuffer =
         &android_app->inputBuffers[android_app->currentInputBuffer];
 
+    dispatch_input_events(android_app, &android_app->keyEventsFinished,
+                          &android_app->keyEventsDispatched,
+                          android_app->keyEventsQueued,
+                          &android_app->keyEventHandled);
+    dispatch_input_events(android_app, &android_app->motionEventsFinished,
+                          &android_app->motionEventsDispatched,
+                          android_app->motionEventsQueued,
+                          &android_app->motionEventHandled);
+
     if (inputBuffer->motionEventsCount == 0 &&
         inputBuffer->keyEventsCount == 0) {
         inputBuffer = NULL;
@@ -519,6 +670,7 @@ struct android_input_buffer* android_app_swap_input_buffers(
 
 void android_app_clear_motion_events(struct android_input_buffer* inputBuffer) {
     inputBuffer->motionEventsCount = 0;
+    inputBuffer->historicalSamplesCount = 0;
 }
 
 void android_app_set_key_event_filter(struct android_app* app,
@@ -542,12 +694,23 @@ static bool onKey(GameActivity* activity, const GameActivityKeyEvent* event) {
         &android_app->inputBuffers[android_app->currentInputBuffer];
 
     // Add to the list of active key down events
-    if (inputBuffer->keyEventsCount < NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS) {
+    if (reserve_input_buffer_space(
+            (void**)&inputBuffer->keyEvents, &inputBuffer->keyEventsCapacity,
+            inputBuffer->keyEventsCount, 1, NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS,
+            sizeof(GameActivityKeyEvent))) {
         int new_ix = inputBuffer->keyEventsCount;
         memcpy(&inputBuffer->keyEvents[new_ix], event,
                sizeof(GameActivityKeyEvent));
         ++inputBuffer->keyEventsCount;
+        uint64_t serial = ++android_app->keyEventsQueued;
+
+        bool handled = wait_for_input_event(android_app, serial,
+                                            &android_app->keyEventsFinished,
+                                            &android_app->keyEventHandled);
+        pthread_mutex_unlock(&android_app->mutex);
+        return handled;
     } else {
+        ++android_app->droppedInputEvents;
         LOGW_ONCE("Key event will be dropped because the number of unconsumed key events exceeded"
              " NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS (%d). Consider setting"
              " NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS_OVERRIDE to a larger value",
@@ -562,15 +725,96 @@ void android_app_clear_key_events(struct android_input_buffer* inputBuffer) {
     inputBuffer->keyEventsCount = 0;
 }
 
+void android_app_finish_key_event(struct android_app* android_app,
+                                  bool handled) {
+    finish_input_event(android_app, &android_app->keyEventsFinished,
+                       &android_app->keyEventsDispatched,
+                       &android_app->keyEventHandled, handled);
+}
+
+void android_app_finish_motion_event(struct android_app* android_app,
+                                     bool handled) {
+    finish_input_event(android_app, &android_app->motionEventsFinished,
+                       &android_app->motionEventsDispatched,
+                       &android_app->motionEventHandled, handled);
+}
+
+uint64_t android_app_get_dropped_input_events(struct android_app* android_app) {
+    pthread_mutex_lock(&android_app->mutex);
+    uint64_t dropped = android_app->droppedInputEvents;
+    pthread_mutex_unlock(&android_app->mutex);
+    return dropped;
+}
+
 static void onTextInputEvent(GameActivity* activity,
                              const GameTextInputState* state) {
     struct android_app* android_app = ToApp(activity);
     pthread_mutex_lock(&android_app->mutex);
 
     android_app->textInputState = 1;
+    // Unlike for key and motion events, the application may not otherwise be
+    // woken up to check for input after the IME edits the text
+    if (android_app->looper != NULL) {
+        ALooper_wake(android_app->looper);
+    }
     pthread_mutex_unlock(&android_app->mutex);
 }
 
+// GameActivity doesn't forward IME editor actions, so an Activity subclass that
+// wants them has to declare `native boolean onEditorActionNative(int action)` and
+// call it from its InputConnection's `onEditorAction()`. The native method is
+// registered here, rather than by GameActivity.
+static jboolean onEditorAction_native(JNIEnv* env, jobject javaGameActivity,
+                                      jint action) {
+    struct android_app* android_app = editorActionApp;
+    if (android_app == NULL) {
+        return JNI_FALSE;
+    }
+    pthread_mutex_lock(&android_app->mutex);
+
+    android_app->editorAction = action;
+    if (android_app->looper != NULL) {
+        ALooper_wake(android_app->looper);
+    }
+    pthread_mutex_unlock(&android_app->mutex);
+    return JNI_TRUE;
+}
+
+// Registers onEditorActionNative() with the class that declares it, if any.
+// Note: GetMethodID() also finds inherited methods, so the declaring class is
+// the last class in the hierarchy that has the method.
+static void register_editor_action_native(GameActivity* activity) {
+    static const JNINativeMethod method = {
+        "onEditorActionNative", "(I)Z", (void*)onEditorAction_native};
+    JNIEnv* env = activity->env;
+
+    jclass clazz = (*env)->GetObjectClass(env, activity->javaGameActivity);
+    if ((*env)->GetMethodID(env, clazz, method.name, method.signature) == NULL) {
+        (*env)->ExceptionClear(env);
+        (*env)->DeleteLocalRef(env, clazz);
+        LOGV("onEditorActionNative isn't declared, so editor actions won't be reported");
+        return;
+    }
+    for (;;) {
+        jclass super = (*env)->GetSuperclass(env, clazz);
+        if (super == NULL ||
+            (*env)->GetMethodID(env, super, method.name, method.signature) == NULL) {
+            (*env)->ExceptionClear(env);
+            if (super != NULL) {
+                (*env)->DeleteLocalRef(env, super);
+            }
+            break;
+        }
+        (*env)->DeleteLocalRef(env, clazz);
+        clazz = super;
+    }
+    if ((*env)->RegisterNatives(env, clazz, &method, 1) != 0) {
+        (*env)->ExceptionClear(env);
+        LOGE("Failed to register onEditorActionNative");
+    }
+    (*env)->DeleteLocalRef(env, clazz);
+}
+
 static void onWindowInsetsChanged(GameActivity* activity) {
     LOGV("WindowInsetsChanged: %p", activity);
     android_app_write_cmd(ToApp(activity), APP_CMD_WINDOW_INSETS_CHANGED);
@@ -603,4 +847,7 @@ void GameActivity_onCreate_C(GameActivity* activity, void* savedState,
 
     activity->instance =
         android_app_create(activity, savedState, savedStateSize);
+
+    editorActionApp = ToApp(activity);
+    register_editor_action_native(activity);
 }
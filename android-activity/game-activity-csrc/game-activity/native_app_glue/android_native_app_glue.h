@@ -30,25 +30,45 @@
 
 #include "game-activity/GameActivity.h"
 
+/*
+ * The input buffers grow as events arrive, up to these limits on the number of
+ * events (and historical samples) that can be waiting to be handled. Any
+ * further events are dropped, and counted by android_app_get_dropped_input_events().
+ */
+
 #if (defined NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS_OVERRIDE)
 #define NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS \
     NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS_OVERRIDE
 #else
-#define NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS 16
+#define NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS 1024
 #endif
 
 #if (defined NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES_OVERRIDE)
 #define NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES \
     NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES_OVERRIDE
 #else
-#define NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES 64
+#define NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES 8192
 #endif
 
 #if (defined NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS_OVERRIDE)
 #define NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS \
     NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS_OVERRIDE
 #else
-#define NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS 4
+#define NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS 1024
+#endif
+
+/*
+ * How long GameActivity's onKeyDown(), onKeyUp() and onTouchEvent() wait for
+ * the application to handle a buffered event, so that it can be passed back to
+ * the system if it's not handled. Events that aren't handled in time are
+ * reported as handled.
+ */
+
+#if (defined NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS_OVERRIDE)
+#define NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS \
+    NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS_OVERRIDE
+#else
+#define NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS 100
 #endif
 
 #ifdef __cplusplus
@@ -126,10 +146,10 @@ struct android_poll_source {
 
 struct android_input_buffer {
     /**
-     * Pointer to a read-only array of pointers to GameActivityMotionEvent.
+     * Pointer to a read-only array of GameActivityMotionEvent.
      * Only the first motionEventsCount events are valid.
      */
-    GameActivityMotionEvent motionEvents[NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS];
+    GameActivityMotionEvent* motionEvents;
 
     /**
      * The number of valid motion events in `motionEvents`.
@@ -137,7 +157,12 @@ struct android_input_buffer {
     uint64_t motionEventsCount;
 
     /**
-     * Pointer to a read-only array of pointers to GameActivityHistoricalPointerAxes.
+     * The number of motion events that `motionEvents` has space for.
+     */
+    uint64_t motionEventsCapacity;
+
+    /**
+     * Pointer to a read-only array of GameActivityHistoricalPointerAxes.
      *
      * Only the first historicalSamplesCount samples are valid.
      * Refer to event->historicalStart, event->pointerCount and event->historicalCount
@@ -150,7 +175,7 @@ struct android_input_buffer {
      *
      *   historicalAxisSamples[event->historicalStart + (event->historicalCount * 2) + 3];
      */
-    GameActivityHistoricalPointerAxes historicalAxisSamples[NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES];
+    GameActivityHistoricalPointerAxes* historicalAxisSamples;
 
     /**
      * The number of valid historical samples in `historicalAxisSamples`.
@@ -158,15 +183,25 @@ struct android_input_buffer {
     uint64_t historicalSamplesCount;
 
     /**
-     * Pointer to a read-only array of pointers to GameActivityKeyEvent.
+     * The number of historical samples that `historicalAxisSamples` has space for.
+     */
+    uint64_t historicalSamplesCapacity;
+
+    /**
+     * Pointer to a read-only array of GameActivityKeyEvent.
      * Only the first keyEventsCount events are valid.
      */
-    GameActivityKeyEvent keyEvents[NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS];
+    GameActivityKeyEvent* keyEvents;
 
     /**
      * The number of valid "Key" events in `keyEvents`.
      */
     uint64_t keyEventsCount;
+
+    /**
+     * The number of key events that `keyEvents` has space for.
+     */
+    uint64_t keyEventsCapacity;
 };
 
 /**
@@ -295,6 +330,29 @@ struct android_app {
     android_key_event_filter keyEventFilter;
     android_motion_event_filter motionEventFilter;
 
+    // The id of the last IME editor action that hasn't been handled yet, or -1
+    int editorAction;
+
+    // The level of the last APP_CMD_LOW_MEMORY, from onTrimMemory
+    int trimMemoryLevel;
+
+    // The number of key and motion events that couldn't be buffered
+    uint64_t droppedInputEvents;
+
+    // Buffered key and motion events are numbered, so that the Java main thread
+    // can wait for the application to finish handling them, in order (see
+    // android_app_finish_key_event() and android_app_finish_motion_event())
+    uint64_t keyEventsQueued;
+    uint64_t keyEventsDispatched;
+    uint64_t keyEventsFinished;
+    uint64_t motionEventsQueued;
+    uint64_t motionEventsDispatched;
+    uint64_t motionEventsFinished;
+
+    // Whether the last finished key or motion event was handled
+    bool keyEventHandled;
+    bool motionEventHandled;
+
     /** @endcond */
 };
 
@@ -479,6 +537,36 @@ void android_app_clear_motion_events(struct android_input_buffer* inputBuffer);
  */
 void android_app_clear_key_events(struct android_input_buffer* inputBuffer);
 
+/**
+ * Report whether the application handled the next key event from the buffer
+ * returned by android_app_swap_input_buffers(), in order. If it's not handled,
+ * GameActivity's onKeyDown() or onKeyUp() returns false so that the system can
+ * handle the event instead (e.g. for volume or Back keys).
+ *
+ * Any events in the buffer that aren't reported by the next call to
+ * android_app_swap_input_buffers() are treated as handled.
+ */
+void android_app_finish_key_event(struct android_app* android_app, bool handled);
+
+/**
+ * Report whether the application handled the next motion event from the buffer
+ * returned by android_app_swap_input_buffers(), in order. If it's not handled,
+ * GameActivity's onTouchEvent() returns false so that the system can handle the
+ * event instead.
+ *
+ * Any events in the buffer that aren't reported by the next call to
+ * android_app_swap_input_buffers() are treated as handled.
+ */
+void android_app_finish_motion_event(struct android_app* android_app,
+                                     bool handled);
+
+/**
+ * Get the total number of key and motion events that have been dropped because
+ * the input buffers were full (see NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS and
+ * NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS) or couldn't be grown.
+ */
+uint64_t android_app_get_dropped_input_events(struct android_app* android_app);
+
 /**
  * This is a springboard into the Rust glue layer that wraps calling the
  * main entry for the app itself.
@@ -18,6 +18,7 @@
 #include <jni.h>
 
 #include <errno.h>
+#include <stdarg.h>
 #include <stdlib.h>
 #include <string.h>
 #include <unistd.h>
@@ -140,6 +141,13 @@ void android_app_pre_exec_cmd(struct android_app* android_app, int8_t cmd) {
             LOGV("APP_CMD_DESTROY\n");
             android_app->destroyRequested = 1;
             break;
+
+        case APP_CMD_WINDOW_INSETS_CHANGED:
+            LOGV("APP_CMD_WINDOW_INSETS_CHANGED\n");
+            pthread_mutex_lock(&android_app->mutex);
+            memcpy(android_app->insets, android_app->pendingInsets, sizeof(android_app->insets));
+            pthread_mutex_unlock(&android_app->mutex);
+            break;
     }
 }
 
@@ -335,8 +343,17 @@ static void android_app_free(struct android_app* android_app) {
     free(android_app);
 }
 
+// The android_app that onTrimMemoryNative() and onWindowInsetsChangedNative()
+// report to
+static struct android_app* nativeMethodsApp = NULL;
+
+static void update_window_insets(struct android_app* android_app);
+
 static void onDestroy(ANativeActivity* activity) {
     LOGV("Destroy: %p\n", activity);
+    if (nativeMethodsApp == (struct android_app*)activity->instance) {
+        nativeMethodsApp = NULL;
+    }
     android_app_free((struct android_app*)activity->instance);
 }
 
@@ -348,6 +365,7 @@ static void onStart(ANativeActivity* activity) {
 static void onResume(ANativeActivity* activity) {
     LOGV("Resume: %p\n", activity);
     android_app_set_activity_state((struct android_app*)activity->instance, APP_CMD_RESUME);
+    update_window_insets((struct android_app*)activity->instance);
 }
 
 static void* onSaveInstanceState(ANativeActivity* activity, size_t* outLen) {
@@ -388,23 +406,256 @@ static void onConfigurationChanged(ANativeActivity* activity) {
     struct android_app* android_app = (struct android_app*)activity->instance;
     LOGV("ConfigurationChanged: %p\n", activity);
     android_app_write_cmd(android_app, APP_CMD_CONFIG_CHANGED);
+    update_window_insets(android_app);
 }
 
 static void onLowMemory(ANativeActivity* activity) {
     struct android_app* android_app = (struct android_app*)activity->instance;
     LOGV("LowMemory: %p\n", activity);
+    pthread_mutex_lock(&android_app->mutex);
+    android_app->trimMemoryLevel = 0;
+    pthread_mutex_unlock(&android_app->mutex);
     android_app_write_cmd(android_app, APP_CMD_LOW_MEMORY);
 }
 
+// NativeActivity doesn't forward onTrimMemory(), so an Activity subclass that
+// wants every trim level has to declare `native void onTrimMemoryNative(int level)`
+// and call it from its `onTrimMemory()`. The native method is registered here.
+static void onTrimMemory_native(JNIEnv* env, jobject activity, jint level) {
+    struct android_app* android_app = nativeMethodsApp;
+    if (android_app == NULL) {
+        return;
+    }
+    LOGV("TrimMemory: %p %d\n", android_app->activity, level);
+    pthread_mutex_lock(&android_app->mutex);
+    android_app->trimMemoryLevel = level;
+    pthread_mutex_unlock(&android_app->mutex);
+    android_app_write_cmd(android_app, APP_CMD_LOW_MEMORY);
+}
+
+// Registers a native method of the Activity with the class that declares it, if any.
+// Note: GetMethodID() also finds inherited methods, so the declaring class is
+// the last class in the hierarchy that has the method.
+static void register_activity_native(ANativeActivity* activity, const JNINativeMethod* method) {
+    JNIEnv* env = activity->env;
+
+    jclass clazz = (*env)->GetObjectClass(env, activity->clazz);
+    if ((*env)->GetMethodID(env, clazz, method->name, method->signature) == NULL) {
+        (*env)->ExceptionClear(env);
+        (*env)->DeleteLocalRef(env, clazz);
+        LOGV("%s isn't declared by the activity\n", method->name);
+        return;
+    }
+    for (;;) {
+        jclass super = (*env)->GetSuperclass(env, clazz);
+        if (super == NULL ||
+            (*env)->GetMethodID(env, super, method->name, method->signature) == NULL) {
+            (*env)->ExceptionClear(env);
+            if (super != NULL) {
+                (*env)->DeleteLocalRef(env, super);
+            }
+            break;
+        }
+        (*env)->DeleteLocalRef(env, clazz);
+        clazz = super;
+    }
+    if ((*env)->RegisterNatives(env, clazz, method, 1) != 0) {
+        (*env)->ExceptionClear(env);
+        LOGE("Failed to register %s\n", method->name);
+    }
+    (*env)->DeleteLocalRef(env, clazz);
+}
+
+// Calls an instance method that returns an object, returning NULL if the object
+// is NULL or the method throws
+static jobject call_object_method(JNIEnv* env, jobject obj, const char* name,
+        const char* sig, ...) {
+    if (obj == NULL) {
+        return NULL;
+    }
+    jclass clazz = (*env)->GetObjectClass(env, obj);
+    jmethodID method = (*env)->GetMethodID(env, clazz, name, sig);
+    if (method == NULL) {
+        (*env)->ExceptionClear(env);
+        return NULL;
+    }
+    va_list args;
+    va_start(args, sig);
+    jobject result = (*env)->CallObjectMethodV(env, obj, method, args);
+    va_end(args);
+    if ((*env)->ExceptionCheck(env)) {
+        (*env)->ExceptionClear(env);
+        return NULL;
+    }
+    return result;
+}
+
+// Calls an instance method that takes no arguments and returns an int, returning
+// 0 if the object is NULL or the method throws
+static jint call_int_method(JNIEnv* env, jobject obj, const char* name) {
+    if (obj == NULL) {
+        return 0;
+    }
+    jclass clazz = (*env)->GetObjectClass(env, obj);
+    jmethodID method = (*env)->GetMethodID(env, clazz, name, "()I");
+    if (method == NULL) {
+        (*env)->ExceptionClear(env);
+        return 0;
+    }
+    jint result = (*env)->CallIntMethod(env, obj, method);
+    if ((*env)->ExceptionCheck(env)) {
+        (*env)->ExceptionClear(env);
+        return 0;
+    }
+    return result;
+}
+
+// Reads an android.graphics.Insets object, leaving rect unchanged if it's NULL
+static void read_insets(JNIEnv* env, jobject insets, ARect* rect) {
+    if (insets == NULL) {
+        return;
+    }
+    jclass clazz = (*env)->GetObjectClass(env, insets);
+    jfieldID left = (*env)->GetFieldID(env, clazz, "left", "I");
+    jfieldID top = (*env)->GetFieldID(env, clazz, "top", "I");
+    jfieldID right = (*env)->GetFieldID(env, clazz, "right", "I");
+    jfieldID bottom = (*env)->GetFieldID(env, clazz, "bottom", "I");
+    if (left == NULL || top == NULL || right == NULL || bottom == NULL) {
+        (*env)->ExceptionClear(env);
+        return;
+    }
+    rect->left = (*env)->GetIntField(env, insets, left);
+    rect->top = (*env)->GetIntField(env, insets, top);
+    rect->right = (*env)->GetIntField(env, insets, right);
+    rect->bottom = (*env)->GetIntField(env, insets, bottom);
+}
+
+// Queries the insets of each INSETS_TYPE_* from the root WindowInsets of the
+// activity's decor view. This must be called on the activity's main thread, which
+// owns the view hierarchy. Before Android 11 only the system bars, display cutout
+// (Android 9+) and gesture and tappable element (Android 10+) insets are available,
+// and the other types are zero.
+static void query_window_insets(ANativeActivity* activity, ARect insets[INSETS_TYPE_COUNT]) {
+    // The WindowInsets.Type method that returns the mask for each type
+    static const char* const typeMethods[INSETS_TYPE_COUNT] = {
+        "captionBar", "displayCutout", "ime", "mandatorySystemGestures",
+        "navigationBars", "statusBars", "systemBars", "systemGestures",
+        "tappableElement", NULL};
+    JNIEnv* env = activity->env;
+
+    memset(insets, 0, sizeof(ARect) * INSETS_TYPE_COUNT);
+    // View.getRootWindowInsets() was added in API level 23
+    if (activity->sdkVersion < 23 || (*env)->PushLocalFrame(env, 32) != JNI_OK) {
+        (*env)->ExceptionClear(env);
+        return;
+    }
+
+    jobject window = call_object_method(env, activity->clazz, "getWindow", "()Landroid/view/Window;");
+    jobject decorView = call_object_method(env, window, "getDecorView", "()Landroid/view/View;");
+    // There are no insets until the view has been attached to a window
+    jobject windowInsets = call_object_method(env, decorView, "getRootWindowInsets",
+            "()Landroid/view/WindowInsets;");
+    jobject cutout = activity->sdkVersion >= 28 ? call_object_method(env, windowInsets,
+            "getDisplayCutout", "()Landroid/view/DisplayCutout;") : NULL;
+
+    if (windowInsets != NULL && activity->sdkVersion >= 30) {
+        jclass typeClass = (*env)->FindClass(env, "android/view/WindowInsets$Type");
+        for (int i = 0; typeClass != NULL && i < INSETS_TYPE_COUNT; i++) {
+            if (typeMethods[i] == NULL) {
+                continue;
+            }
+            jmethodID typeMethod = (*env)->GetStaticMethodID(env, typeClass, typeMethods[i], "()I");
+            if (typeMethod == NULL) {
+                break;
+            }
+            jint mask = (*env)->CallStaticIntMethod(env, typeClass, typeMethod);
+            if ((*env)->ExceptionCheck(env)) {
+                break;
+            }
+            read_insets(env, call_object_method(env, windowInsets, "getInsets",
+                    "(I)Landroid/graphics/Insets;", mask), &insets[i]);
+        }
+        (*env)->ExceptionClear(env);
+        read_insets(env, call_object_method(env, cutout, "getWaterfallInsets",
+                "()Landroid/graphics/Insets;"), &insets[INSETS_TYPE_WATERFALL]);
+    } else if (windowInsets != NULL) {
+        insets[INSETS_TYPE_SYSTEM_BARS].left = call_int_method(env, windowInsets, "getSystemWindowInsetLeft");
+        insets[INSETS_TYPE_SYSTEM_BARS].top = call_int_method(env, windowInsets, "getSystemWindowInsetTop");
+        insets[INSETS_TYPE_SYSTEM_BARS].right = call_int_method(env, windowInsets, "getSystemWindowInsetRight");
+        insets[INSETS_TYPE_SYSTEM_BARS].bottom = call_int_method(env, windowInsets, "getSystemWindowInsetBottom");
+        insets[INSETS_TYPE_DISPLAY_CUTOUT].left = call_int_method(env, cutout, "getSafeInsetLeft");
+        insets[INSETS_TYPE_DISPLAY_CUTOUT].top = call_int_method(env, cutout, "getSafeInsetTop");
+        insets[INSETS_TYPE_DISPLAY_CUTOUT].right = call_int_method(env, cutout, "getSafeInsetRight");
+        insets[INSETS_TYPE_DISPLAY_CUTOUT].bottom = call_int_method(env, cutout, "getSafeInsetBottom");
+        if (activity->sdkVersion >= 29) {
+            read_insets(env, call_object_method(env, windowInsets, "getSystemGestureInsets",
+                    "()Landroid/graphics/Insets;"), &insets[INSETS_TYPE_SYSTEM_GESTURES]);
+            read_insets(env, call_object_method(env, windowInsets, "getMandatorySystemGestureInsets",
+                    "()Landroid/graphics/Insets;"), &insets[INSETS_TYPE_MANDATORY_SYSTEM_GESTURES]);
+            read_insets(env, call_object_method(env, windowInsets, "getTappableElementInsets",
+                    "()Landroid/graphics/Insets;"), &insets[INSETS_TYPE_TAPPABLE_ELEMENT]);
+        }
+    }
+
+    (*env)->PopLocalFrame(env, NULL);
+}
+
+// NativeActivity isn't notified of changes to its insets, so they're queried on
+// the activity's main thread whenever they may have changed, and the app is sent
+// APP_CMD_WINDOW_INSETS_CHANGED if they have
+static void update_window_insets(struct android_app* android_app) {
+    ARect insets[INSETS_TYPE_COUNT];
+    query_window_insets(android_app->activity, insets);
+
+    pthread_mutex_lock(&android_app->mutex);
+    if (memcmp(insets, android_app->pendingInsets, sizeof(insets)) != 0) {
+        memcpy(android_app->pendingInsets, insets, sizeof(insets));
+        android_app_write_cmd(android_app, APP_CMD_WINDOW_INSETS_CHANGED);
+    }
+    pthread_mutex_unlock(&android_app->mutex);
+}
+
+// An Activity subclass can declare `native void onWindowInsetsChangedNative()` and
+// call it from an OnApplyWindowInsetsListener, for changes to the insets that don't
+// coincide with any of the NativeActivity callbacks that update them below (such
+// as the IME being shown over the window). The native method is registered here.
+static void onWindowInsetsChanged_native(JNIEnv* env, jobject activity) {
+    struct android_app* android_app = nativeMethodsApp;
+    if (android_app != NULL) {
+        update_window_insets(android_app);
+    }
+}
+
+static void register_activity_natives(ANativeActivity* activity) {
+    static const JNINativeMethod methods[] = {
+        {"onTrimMemoryNative", "(I)V", (void*)onTrimMemory_native},
+        {"onWindowInsetsChangedNative", "()V", (void*)onWindowInsetsChanged_native},
+    };
+    for (size_t i = 0; i < sizeof(methods) / sizeof(methods[0]); i++) {
+        register_activity_native(activity, &methods[i]);
+    }
+}
+
 static void onWindowFocusChanged(ANativeActivity* activity, int focused) {
     LOGV("WindowFocusChanged: %p -- %d\n", activity, focused);
     android_app_write_cmd((struct android_app*)activity->instance,
             focused ? APP_CMD_GAINED_FOCUS : APP_CMD_LOST_FOCUS);
+    update_window_insets((struct android_app*)activity->instance);
 }
 
 static void onNativeWindowCreated(ANativeActivity* activity, ANativeWindow* window) {
     LOGV("NativeWindowCreated: %p -- %p\n", activity, window);
     android_app_set_window((struct android_app*)activity->instance, window);
+    update_window_insets((struct android_app*)activity->instance);
+}
+
+// NativeActivity calls this after a layout that changed the bounds of its content
+// view, such as when the system bars or IME are shown with a resizing layout. It's
+// only used to update the insets, and APP_CMD_CONTENT_RECT_CHANGED isn't sent.
+static void onContentRectChanged(ANativeActivity* activity, const ARect* rect) {
+    LOGV("ContentRectChanged: %p -- (%d %d) (%d %d)\n", activity, rect->left, rect->top,
+            rect->right, rect->bottom);
+    update_window_insets((struct android_app*)activity->instance);
 }
 
 static void onNativeWindowDestroyed(ANativeActivity* activity, ANativeWindow* window) {
@@ -439,6 +690,10 @@ void ANativeActivity_onCreate_C(ANativeActivity* activity, void* savedState,
     activity->callbacks->onNativeWindowDestroyed = onNativeWindowDestroyed;
     activity->callbacks->onInputQueueCreated = onInputQueueCreated;
     activity->callbacks->onInputQueueDestroyed = onInputQueueDestroyed;
+    activity->callbacks->onContentRectChanged = onContentRectChanged;
 
     activity->instance = android_app_create(activity, savedState, savedStateSize);
+
+    nativeMethodsApp = (struct android_app*)activity->instance;
+    register_activity_natives(activity);
 }
@@ -100,6 +100,25 @@ struct android_poll_source {
     void (*process)(struct android_app* app, struct android_poll_source* source);
 };
 
+/**
+ * The types of window insets that are cached in android_app, which correspond
+ * to the WindowInsets.Type masks (except for INSETS_TYPE_WATERFALL, which
+ * corresponds to DisplayCutout.getWaterfallInsets()).
+ */
+enum {
+    INSETS_TYPE_CAPTION_BAR = 0,
+    INSETS_TYPE_DISPLAY_CUTOUT,
+    INSETS_TYPE_IME,
+    INSETS_TYPE_MANDATORY_SYSTEM_GESTURES,
+    INSETS_TYPE_NAVIGATION_BARS,
+    INSETS_TYPE_STATUS_BARS,
+    INSETS_TYPE_SYSTEM_BARS,
+    INSETS_TYPE_SYSTEM_GESTURES,
+    INSETS_TYPE_TAPPABLE_ELEMENT,
+    INSETS_TYPE_WATERFALL,
+    INSETS_TYPE_COUNT,
+};
+
 /**
  * This is the interface for the standard glue code of a threaded
  * application.  In this model, the application's code is running
@@ -182,6 +201,17 @@ struct android_app {
     AInputQueue* pendingInputQueue;
     ANativeWindow* pendingWindow;
     ARect pendingContentRect;
+
+    // The level of the last APP_CMD_LOW_MEMORY if it was sent for
+    // onTrimMemoryNative(), or 0 if it was sent for onLowMemory()
+    int trimMemoryLevel;
+
+    // The window insets of each INSETS_TYPE_*, as of the last
+    // APP_CMD_WINDOW_INSETS_CHANGED.
+    ARect insets[INSETS_TYPE_COUNT];
+
+    // The insets that were last queried on the activity's main thread.
+    ARect pendingInsets[INSETS_TYPE_COUNT];
 };
 
 enum {
@@ -309,6 +339,12 @@ enum {
      * and waiting for the app thread to clean up and exit before proceeding.
      */
     APP_CMD_DESTROY,
+
+    /**
+     * Command from main thread: the window insets have changed.  Upon
+     * processing this command, android_app->insets will be updated.
+     */
+    APP_CMD_WINDOW_INSETS_CHANGED,
 };
 
 /**
@@ -0,0 +1,218 @@
+//! The application's entry point, which the backends call from a dedicated
+//! thread once the `Activity` has been created
+
+use std::ffi::CString;
+use std::fmt;
+
+use log::Level;
+
+use crate::util::android_log_str;
+use crate::AndroidApp;
+
+/// The application's `android_main` function, as exported to the backend
+///
+/// An application exports exactly one `MainEntry` via [`export_main!`], which is
+/// normally generated by the `#[android_activity::main]` attribute macro (with the
+/// `macros` feature):
+///
+/// ```ignore
+/// #[android_activity::main]
+/// fn android_main(app: AndroidApp) -> Result<(), Box<dyn std::error::Error>> {
+///     ...
+/// }
+/// ```
+///
+/// Without the attribute macro it can be exported by hand:
+///
+/// ```ignore
+/// android_activity::export_main!(android_main);
+///
+/// fn android_main(app: AndroidApp) {
+///     ...
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct MainEntry {
+    main: fn(AndroidApp),
+}
+
+impl MainEntry {
+    /// Wraps the function that the backend should call as the application's `android_main`
+    pub const fn new(main: fn(AndroidApp)) -> Self {
+        Self { main }
+    }
+
+    pub(crate) fn call(&self, app: AndroidApp) {
+        (self.main)(app)
+    }
+}
+
+impl fmt::Debug for MainEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MainEntry").finish_non_exhaustive()
+    }
+}
+
+/// Exports the application's `android_main` function to the backend
+///
+/// This defines a [`MainEntry`] static named `ANDROID_ACTIVITY_MAIN`, along with the
+/// `_rust_glue_entry` function that the backend's native glue code calls on its
+/// dedicated thread. Since the `MainEntry` is created in the application's own crate
+/// the function's signature is checked by the compiler, and exporting more than one
+/// entry point fails to link.
+///
+/// With the `mock-activity` backend there's no native glue code, so only the static
+/// is defined, which can be run via `MockActivity::run_entry()`.
+#[macro_export]
+macro_rules! export_main {
+    ($main:expr) => {
+        #[doc(hidden)]
+        static ANDROID_ACTIVITY_MAIN: $crate::MainEntry = $crate::MainEntry::new($main);
+
+        $crate::__export_glue_entry!(ANDROID_ACTIVITY_MAIN);
+    };
+}
+
+#[cfg(not(feature = "mock-activity"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __export_glue_entry {
+    ($entry:ident) => {
+        #[no_mangle]
+        #[doc(hidden)]
+        unsafe extern "C" fn _rust_glue_entry(app: *mut ::core::ffi::c_void) {
+            $crate::__macro_support::glue_entry(app, &$entry)
+        }
+    };
+}
+
+#[cfg(feature = "mock-activity")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __export_glue_entry {
+    ($entry:ident) => {};
+}
+
+/// Finishes the `Activity` after `android_main` has failed, and then keeps handling
+/// commands from the Java main thread until the `Activity` has been destroyed
+///
+/// The Java main thread blocks while waiting for some commands (such as `Pause` and
+/// `Stop`) to be handled, so if `android_main` just returned then the application
+/// would stop responding instead of closing.
+pub(crate) fn finish_and_wait_for_destroy(app: &AndroidApp) {
+    use crate::{LifecycleState, MainEvent, PollError, PollEvent};
+
+    app.inner.finish();
+
+    // If `android_main` failed while handling `Destroy` there won't be another one
+    let mut destroyed = app.lifecycle_state() == LifecycleState::Destroying;
+    while !destroyed {
+        let result = app.try_poll_events(None, |event| {
+            if let PollEvent::Main(MainEvent::Destroy) = event {
+                destroyed = true;
+            }
+        });
+        match result {
+            Ok(()) => {}
+            // Errors for individual events can be skipped, but nothing more can be
+            // polled if the looper itself is broken
+            Err(err @ (PollError::Looper | PollError::Io(_) | PollError::ReadCommand)) => {
+                let tag = CString::new("android-activity").unwrap();
+                android_log_str(Level::Error, &tag, &format!("Failed to wait for the Activity to be destroyed: {err}"));
+                break;
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+/// Support for the code that's generated by `#[android_activity::main]`, which isn't
+/// part of the public API
+pub mod macro_support {
+    use super::*;
+
+    pub use log::Level;
+
+    /// The options that are given as arguments to `#[android_activity::main]`
+    #[derive(Debug)]
+    pub struct MainOptions {
+        pub logger: bool,
+        pub log_filter: Option<&'static str>,
+        pub panic_hook: bool,
+        pub stdio: bool,
+        pub stdio_tag: Option<&'static str>,
+        pub stderr_level: Option<Level>,
+    }
+
+    /// A return type that's supported for an `android_main` function
+    pub trait MainOutput {
+        /// Returns a description of the error, if `android_main` failed
+        fn into_error(self) -> Option<String>;
+    }
+
+    impl MainOutput for () {
+        fn into_error(self) -> Option<String> {
+            None
+        }
+    }
+
+    impl<E: fmt::Debug> MainOutput for Result<(), E> {
+        fn into_error(self) -> Option<String> {
+            self.err().map(|err| format!("{err:?}"))
+        }
+    }
+
+    /// Sets up the stdio redirection, logging and the panic hook and then calls `main`,
+    /// finishing the `Activity` (and waiting for it to be destroyed) if it returns an error
+    pub fn run_main<R: MainOutput>(app: AndroidApp, options: MainOptions, main: fn(AndroidApp) -> R) {
+        // This comes first, so that nothing that's written to stdout or stderr is lost
+        let stdio_err = if options.stdio {
+            let mut redirect = crate::StdioRedirect::new();
+            if let Some(tag) = options.stdio_tag {
+                redirect = redirect.with_tag(tag);
+            }
+            if let Some(level) = options.stderr_level {
+                redirect = redirect.with_stderr_level(Some(level));
+            }
+            // If stdio was already redirected then that redirection is kept
+            redirect.install().err().filter(|err| err.kind() != std::io::ErrorKind::AlreadyExists)
+        } else {
+            None
+        };
+        if options.logger {
+            let mut config = crate::logger::Config::default();
+            let filter = options.log_filter.map(|filter| filter.parse::<crate::logger::Filter>());
+            if let Some(Ok(filter)) = &filter {
+                config = config.with_filter(filter.clone());
+            }
+            // If a different logger was already installed then that one is kept
+            let _ = crate::logger::init(config);
+            if let Some(Err(err)) = filter {
+                log::error!("{err}");
+            }
+        }
+        if options.panic_hook {
+            crate::PanicHook::new().install(&app);
+        }
+        if let Some(err) = stdio_err {
+            log::error!("Failed to redirect stdio to logcat: {err}");
+        }
+
+        if let Some(err) = main(app.clone()).into_error() {
+            let tag = CString::new("android-activity").unwrap();
+            android_log_str(Level::Error, &tag, &format!("android_main returned an error: {err}"));
+            finish_and_wait_for_destroy(&app);
+        }
+    }
+
+    /// Runs the application's `android_main` on the thread that was spawned by the
+    /// backend's native glue code, which passes its `android_app` pointer
+    ///
+    /// # Safety
+    /// This must only be called by the `_rust_glue_entry` function that's defined by
+    /// [`export_main!`](crate::export_main), with the pointer that it was passed.
+    #[cfg(not(feature = "mock-activity"))]
+    pub unsafe fn glue_entry(app: *mut std::ffi::c_void, entry: &MainEntry) {
+        crate::activity_impl::glue_entry(app.cast(), entry)
+    }
+}
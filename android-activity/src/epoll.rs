@@ -0,0 +1,343 @@
+#![cfg(any(feature = "epoll", feature = "mock-activity"))]
+
+//! An event loop core that's built directly on `epoll`, as an alternative to
+//! `ALooper_pollAll`
+//!
+//! This multiplexes the native app glue's command pipe, user file descriptors
+//! and timers itself. Compared to `ALooper` it supports timeouts with nanosecond
+//! precision (via a `timerfd`) and edge-triggered file descriptors, and it never
+//! invokes callbacks internally (so there's no equivalent of `ALOOPER_POLL_CALLBACK`).
+//!
+//! The real backends nest the epoll instance in the thread's `ALooper` (see
+//! [`EpollLooper::poll_alooper()`]), which is only polled to block until there's an
+//! event. That way the `ALooper` still delivers the input queue notifications,
+//! `AChoreographer` frame callbacks and `ALooper_wake()` calls that the backends
+//! depend on.
+//!
+//! Since it only depends on Linux APIs it's also used by the `mock-activity` backend,
+//! so it gets exercised by host tests.
+
+use std::collections::VecDeque;
+use std::io;
+use std::os::unix::prelude::RawFd;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::trace;
+
+use ndk::looper::FdEvent;
+
+use crate::looper::timespec;
+
+// Reserved identifiers for the wake and timeout fds, which can't collide with
+// the (non-negative) identifiers that are used for looper sources.
+const WAKE_IDENT: i32 = -1;
+const TIMEOUT_IDENT: i32 = -2;
+
+/// Determines how readiness is reported for a file descriptor that's registered
+/// via [`AndroidApp::register_fd_with_trigger()`](crate::AndroidApp::register_fd_with_trigger)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "epoll"), allow(dead_code))]
+pub enum TriggerMode {
+    /// Events are reported for as long as the file descriptor is ready (like `ALooper`)
+    Level,
+
+    /// Events are only reported when the file descriptor becomes ready, so the
+    /// application must read or write until it would block (`EPOLLET`)
+    Edge,
+}
+
+/// The result of [`EpollLooper::poll()`]
+#[derive(Debug)]
+pub(crate) enum EpollPoll {
+    Wake,
+    Timeout,
+    Fd { ident: i32, fd: RawFd, events: FdEvent },
+}
+
+#[derive(Debug, Default)]
+struct EpollState {
+    woken: bool,
+    // Like ALooper, we report one fd event per poll and queue any others
+    ready: VecDeque<(i32, RawFd, FdEvent)>,
+}
+
+/// An `epoll` instance along with an `eventfd` for wake ups and a `timerfd`
+/// for timeouts
+///
+/// All methods except for [`poll()`](Self::poll) can be called from any thread.
+#[derive(Debug)]
+pub(crate) struct EpollLooper {
+    epfd: RawFd,
+    wake_fd: RawFd,
+    timeout_fd: RawFd,
+    state: Mutex<EpollState>,
+}
+
+fn cvt(ret: libc::c_int) -> io::Result<libc::c_int> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret)
+    }
+}
+
+fn pack(ident: i32, fd: RawFd) -> u64 {
+    ((ident as u32 as u64) << 32) | fd as u32 as u64
+}
+
+fn unpack(data: u64) -> (i32, RawFd) {
+    ((data >> 32) as u32 as i32, data as u32 as i32)
+}
+
+impl EpollLooper {
+    pub(crate) fn new() -> io::Result<Self> {
+        unsafe {
+            let epfd = cvt(libc::epoll_create1(libc::EPOLL_CLOEXEC))?;
+            let wake_fd = match cvt(libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK)) {
+                Ok(fd) => fd,
+                Err(err) => {
+                    libc::close(epfd);
+                    return Err(err);
+                }
+            };
+            let timeout_fd = match cvt(libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_CLOEXEC | libc::TFD_NONBLOCK)) {
+                Ok(fd) => fd,
+                Err(err) => {
+                    libc::close(wake_fd);
+                    libc::close(epfd);
+                    return Err(err);
+                }
+            };
+
+            // From here the fds are closed by our Drop impl
+            let looper = Self {
+                epfd,
+                wake_fd,
+                timeout_fd,
+                state: Mutex::new(EpollState::default()),
+            };
+            looper.add_fd(wake_fd, WAKE_IDENT, FdEvent::INPUT, TriggerMode::Level)?;
+            looper.add_fd(timeout_fd, TIMEOUT_IDENT, FdEvent::INPUT, TriggerMode::Level)?;
+            Ok(looper)
+        }
+    }
+
+    /// Adds `fd` to the epoll set, so that its events are reported with the given `ident`
+    pub(crate) fn add_fd(&self, fd: RawFd, ident: i32, events: FdEvent, mode: TriggerMode) -> io::Result<()> {
+        let mut flags = 0;
+        if events.contains(FdEvent::INPUT) {
+            flags |= libc::EPOLLIN;
+        }
+        if events.contains(FdEvent::OUTPUT) {
+            flags |= libc::EPOLLOUT;
+        }
+        if mode == TriggerMode::Edge {
+            flags |= libc::EPOLLET;
+        }
+        let mut event = libc::epoll_event { events: flags as u32, u64: pack(ident, fd) };
+        unsafe { cvt(libc::epoll_ctl(self.epfd, libc::EPOLL_CTL_ADD, fd, &mut event))?; }
+        Ok(())
+    }
+
+    /// Removes `fd` from the epoll set, including any of its events that are still queued
+    pub(crate) fn remove_fd(&self, fd: RawFd) -> io::Result<()> {
+        self.state.lock().unwrap().ready.retain(|(_, ready_fd, _)| *ready_fd != fd);
+        unsafe { cvt(libc::epoll_ctl(self.epfd, libc::EPOLL_CTL_DEL, fd, std::ptr::null_mut()))?; }
+        Ok(())
+    }
+
+    /// Wakes up [`poll()`](Self::poll), which will return [`EpollPoll::Wake`]
+    pub(crate) fn wake(&self) {
+        let one: u64 = 1;
+        // The eventfd counter can't realistically overflow, and any error would
+        // mean the looper has already been woken
+        unsafe { libc::write(self.wake_fd, (&one as *const u64).cast(), std::mem::size_of::<u64>()); }
+    }
+
+    fn set_timeout(&self, timeout: Duration) -> io::Result<()> {
+        let spec = libc::itimerspec {
+            it_interval: timespec(Duration::ZERO),
+            it_value: timespec(timeout),
+        };
+        unsafe { cvt(libc::timerfd_settime(self.timeout_fd, 0, &spec, std::ptr::null_mut()))?; }
+        Ok(())
+    }
+
+    /// Waits for an event, for up to `timeout` (or indefinitely if `None`)
+    ///
+    /// This must only be called from one thread at a time.
+    #[cfg_attr(not(feature = "mock-activity"), allow(dead_code))]
+    pub(crate) fn poll(&self, timeout: Option<Duration>) -> io::Result<EpollPoll> {
+        self.poll_impl(timeout, None)
+    }
+
+    /// Waits for an event like [`poll()`](Self::poll), except that instead of blocking
+    /// in `epoll_wait()` this calls `block`, which must block until the epoll fd
+    /// is readable
+    ///
+    /// This lets the epoll instance be nested in another looper, which is polled by
+    /// `block`. If `block` returns `true` then the other looper was woken up, which is
+    /// reported as [`EpollPoll::Wake`].
+    ///
+    /// This must only be called from one thread at a time.
+    #[cfg_attr(feature = "mock-activity", allow(dead_code))]
+    pub(crate) fn poll_nested(&self, timeout: Option<Duration>, mut block: impl FnMut() -> io::Result<bool>) -> io::Result<EpollPoll> {
+        self.poll_impl(timeout, Some(&mut block))
+    }
+
+    fn poll_impl(&self, timeout: Option<Duration>, block: Option<&mut dyn FnMut() -> io::Result<bool>>) -> io::Result<EpollPoll> {
+        if let Some(event) = self.take_event() {
+            return Ok(event);
+        }
+
+        // A zero it_value would disarm the timerfd, so a zero timeout is
+        // handled by epoll_wait() instead
+        let wait_ms = match timeout {
+            Some(timeout) if timeout.is_zero() => 0,
+            Some(timeout) => {
+                self.set_timeout(timeout)?;
+                -1
+            }
+            None => -1,
+        };
+
+        let result = match block {
+            Some(block) if wait_ms != 0 => self.wait_nested(block),
+            _ => self.wait(wait_ms),
+        };
+        if matches!(timeout, Some(timeout) if !timeout.is_zero()) {
+            self.set_timeout(Duration::ZERO)?;
+        }
+        let timed_out = result?;
+
+        match self.take_event() {
+            Some(event) => Ok(event),
+            None if timed_out || wait_ms == 0 => Ok(EpollPoll::Timeout),
+            // E.g. a level-triggered event for an fd that was concurrently removed
+            None => Ok(EpollPoll::Wake),
+        }
+    }
+
+    fn take_event(&self) -> Option<EpollPoll> {
+        let mut state = self.state.lock().unwrap();
+        if state.woken {
+            state.woken = false;
+            return Some(EpollPoll::Wake);
+        }
+        state.ready.pop_front().map(|(ident, fd, events)| EpollPoll::Fd { ident, fd, events })
+    }
+
+    // Blocks via the looper that the epoll instance is nested in, until there's an
+    // event or that looper is woken up. Returns whether the timeout expired.
+    fn wait_nested(&self, block: &mut dyn FnMut() -> io::Result<bool>) -> io::Result<bool> {
+        loop {
+            // The epoll fd may have already been ready, or the other looper may have
+            // returned after running a callback that didn't result in any event
+            let timed_out = self.wait(0)?;
+            {
+                let state = self.state.lock().unwrap();
+                if timed_out || state.woken || !state.ready.is_empty() {
+                    return Ok(timed_out);
+                }
+            }
+            if block()? {
+                trace!("epoll looper woken via nesting looper");
+                self.state.lock().unwrap().woken = true;
+                return Ok(false);
+            }
+        }
+    }
+
+    // Returns whether the timeout expired
+    fn wait(&self, wait_ms: libc::c_int) -> io::Result<bool> {
+        let mut events = [libc::epoll_event { events: 0, u64: 0 }; 16];
+        let n = loop {
+            match unsafe { cvt(libc::epoll_wait(self.epfd, events.as_mut_ptr(), events.len() as libc::c_int, wait_ms)) } {
+                Ok(n) => break n as usize,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        };
+
+        let mut timed_out = false;
+        let mut state = self.state.lock().unwrap();
+        for event in &events[..n] {
+            let flags = event.events as libc::c_int;
+            match unpack(event.u64) {
+                (WAKE_IDENT, fd) => {
+                    let mut count: u64 = 0;
+                    unsafe { libc::read(fd, (&mut count as *mut u64).cast(), std::mem::size_of::<u64>()); }
+                    trace!("epoll looper woken");
+                    state.woken = true;
+                }
+                (TIMEOUT_IDENT, fd) => {
+                    let mut expirations: u64 = 0;
+                    unsafe { libc::read(fd, (&mut expirations as *mut u64).cast(), std::mem::size_of::<u64>()); }
+                    timed_out = true;
+                }
+                (ident, fd) => {
+                    let mut fd_events = FdEvent::empty();
+                    if flags & libc::EPOLLIN != 0 {
+                        fd_events |= FdEvent::INPUT;
+                    }
+                    if flags & libc::EPOLLOUT != 0 {
+                        fd_events |= FdEvent::OUTPUT;
+                    }
+                    if flags & libc::EPOLLERR != 0 {
+                        fd_events |= FdEvent::ERROR;
+                    }
+                    if flags & libc::EPOLLHUP != 0 {
+                        fd_events |= FdEvent::HANGUP;
+                    }
+                    trace!("epoll looper fd event: ident = {ident}, fd = {fd}, events = {fd_events:?}");
+                    state.ready.push_back((ident, fd, fd_events));
+                }
+            }
+        }
+        Ok(timed_out)
+    }
+}
+
+#[cfg(not(feature = "mock-activity"))]
+impl EpollLooper {
+    /// Adds the epoll fd to `looper` with the given `ident`, so that the epoll instance
+    /// can be polled via [`poll_alooper()`](Self::poll_alooper)
+    ///
+    /// `looper` must be the `ALooper` of the thread that polls this looper.
+    pub(crate) unsafe fn nest_in_alooper(&self, looper: *mut ndk_sys::ALooper, ident: i32) -> io::Result<()> {
+        let ret = ndk_sys::ALooper_addFd(looper, self.epfd, ident, ndk_sys::ALOOPER_EVENT_INPUT as i32, None, std::ptr::null_mut());
+        if ret < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, "ALooper_addFd failed for the epoll fd"));
+        }
+        Ok(())
+    }
+
+    /// Waits for an event via [`poll_nested()`](Self::poll_nested), blocking in the
+    /// calling thread's `ALooper`
+    ///
+    /// The `ALooper` runs any callbacks that are ready (such as `AChoreographer`
+    /// frame callbacks) and `ALooper_wake()` is reported as [`EpollPoll::Wake`]. Any
+    /// other source of the `ALooper` that's ready (such as the glue's command pipe,
+    /// which is also added to the epoll instance) just means there might be an event.
+    pub(crate) fn poll_alooper(&self, timeout: Option<Duration>) -> io::Result<EpollPoll> {
+        self.poll_nested(timeout, || {
+            let id = unsafe { ndk_sys::ALooper_pollOnce(-1, std::ptr::null_mut(), std::ptr::null_mut(), std::ptr::null_mut()) };
+            match id {
+                ndk_sys::ALOOPER_POLL_WAKE => Ok(true),
+                ndk_sys::ALOOPER_POLL_ERROR => Err(io::Error::new(io::ErrorKind::Other, "ALooper_pollOnce failed")),
+                _ => Ok(false),
+            }
+        })
+    }
+}
+
+impl Drop for EpollLooper {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.timeout_fd);
+            libc::close(self.wake_fd);
+            libc::close(self.epfd);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+use std::io;
+
+/// An error that was encountered while polling for events via
+/// [`AndroidApp::try_poll_events()`](crate::AndroidApp::try_poll_events)
+///
+/// These errors represent unexpected states in the glue layer or looper, and
+/// it's not generally possible to recover the event that was being read. In
+/// most cases an application can simply log the error and continue polling
+/// but it may also choose to shut down gracefully.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PollError {
+    /// The looper reported an error (`ALOOPER_POLL_ERROR`)
+    Looper,
+
+    /// An I/O error while waiting for events
+    Io(io::Error),
+
+    /// A command couldn't be read from the native app glue's command pipe
+    ReadCommand,
+
+    /// The looper reported a command with a NULL `android_poll_source`
+    NullPollSource,
+
+    /// The glue sent a command with an id that's not recognised
+    UnknownCommand(i32),
+
+    /// The looper reported unknown event flags for a file descriptor
+    InvalidFdEvents { ident: i32, fd: i32, events: u32 },
+}
+
+impl fmt::Display for PollError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PollError::Looper => write!(f, "Looper reported a polling error"),
+            PollError::Io(err) => write!(f, "I/O error while polling for events: {err}"),
+            PollError::ReadCommand => write!(f, "Failed to read command from the main thread"),
+            PollError::NullPollSource => write!(f, "Looper returned a command with a NULL android_poll_source"),
+            PollError::UnknownCommand(cmd) => write!(f, "Unknown command {cmd} from the main thread"),
+            PollError::InvalidFdEvents { ident, fd, events } => {
+                write!(f, "Spurious event flags {events:#04x} for looper ident = {ident}, fd = {fd}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PollError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PollError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
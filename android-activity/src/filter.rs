@@ -0,0 +1,57 @@
+//! Application-defined filters that decide which key and motion events are
+//! delivered to the application, rather than being left for the system to handle
+
+use std::sync::RwLock;
+
+use crate::input::{KeyEvent, MotionEvent};
+
+pub(crate) type KeyEventFilter = Box<dyn Fn(&KeyEvent) -> bool + Send + Sync>;
+pub(crate) type MotionEventFilter = Box<dyn Fn(&MotionEvent) -> bool + Send + Sync>;
+
+/// The filters set via [`AndroidApp::set_key_event_filter()`](crate::AndroidApp::set_key_event_filter)
+/// and [`AndroidApp::set_motion_event_filter()`](crate::AndroidApp::set_motion_event_filter)
+///
+/// Without a filter, every event is accepted.
+pub(crate) struct EventFilters {
+    key: RwLock<Option<KeyEventFilter>>,
+    motion: RwLock<Option<MotionEventFilter>>,
+}
+
+impl EventFilters {
+    pub(crate) const fn new() -> Self {
+        Self { key: RwLock::new(None), motion: RwLock::new(None) }
+    }
+
+    pub(crate) fn set_key_filter(&self, filter: KeyEventFilter) {
+        *self.key.write().unwrap() = Some(filter);
+    }
+
+    pub(crate) fn set_motion_filter(&self, filter: MotionEventFilter) {
+        *self.motion.write().unwrap() = Some(filter);
+    }
+
+    /// Returns `true` if the event should be delivered to the application
+    pub(crate) fn accept_key(&self, event: &KeyEvent) -> bool {
+        match &*self.key.read().unwrap() {
+            Some(filter) => filter(event),
+            None => true,
+        }
+    }
+
+    /// Returns `true` if the event should be delivered to the application
+    pub(crate) fn accept_motion(&self, event: &MotionEvent) -> bool {
+        match &*self.motion.read().unwrap() {
+            Some(filter) => filter(event),
+            None => true,
+        }
+    }
+}
+
+impl std::fmt::Debug for EventFilters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventFilters")
+            .field("key", &self.key.read().unwrap().is_some())
+            .field("motion", &self.motion.read().unwrap().is_some())
+            .finish()
+    }
+}
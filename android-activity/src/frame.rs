@@ -0,0 +1,130 @@
+//! Frame callbacks, which are driven by an `AChoreographer` so that rendering can
+//! be paced to the display's vsync
+
+use std::io;
+use std::sync::Mutex;
+
+#[cfg(not(feature = "mock-activity"))]
+use std::{ffi::c_void, sync::Arc};
+
+#[cfg(not(feature = "mock-activity"))]
+use crate::AndroidAppWaker;
+
+#[derive(Debug, Default)]
+struct FrameState {
+    requested: bool,
+    pending: Option<i64>,
+}
+
+/// Tracks whether a frame callback has been requested, and the vsync time of a
+/// frame that hasn't been delivered yet as a [`PollEvent::Frame`](crate::PollEvent::Frame)
+#[derive(Debug, Default)]
+pub(crate) struct FrameCallbacks {
+    state: Mutex<FrameState>,
+}
+
+impl FrameCallbacks {
+    /// Requests a callback for the next frame, via `post`, unless one has already
+    /// been requested
+    ///
+    /// Multiple requests before the next frame only result in a single callback.
+    pub(crate) fn request(&self, post: impl FnOnce() -> io::Result<()>) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if !state.requested {
+            post()?;
+            state.requested = true;
+        }
+        Ok(())
+    }
+
+    /// Records the vsync time for a new frame, returning `false` if no frame callback
+    /// had been requested
+    pub(crate) fn on_frame(&self, vsync_time_ns: i64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.requested {
+            return false;
+        }
+        state.requested = false;
+        state.pending = Some(vsync_time_ns);
+        true
+    }
+
+    /// Returns `true` if there's a frame that hasn't been delivered yet
+    pub(crate) fn is_pending(&self) -> bool {
+        self.state.lock().unwrap().pending.is_some()
+    }
+
+    /// Takes the vsync time of a frame that hasn't been delivered yet
+    pub(crate) fn take(&self) -> Option<i64> {
+        self.state.lock().unwrap().pending.take()
+    }
+}
+
+#[cfg(not(feature = "mock-activity"))]
+type PostFrameCallback64 = unsafe extern "C" fn(
+    choreographer: *mut ndk_sys::AChoreographer,
+    callback: unsafe extern "C" fn(frame_time_nanos: i64, data: *mut c_void),
+    data: *mut c_void,
+);
+
+/// Posts frame callbacks to the `AChoreographer` of the thread running `android_main`
+#[cfg(not(feature = "mock-activity"))]
+#[derive(Debug)]
+pub(crate) struct Choreographer {
+    // `AChoreographer_postFrameCallback64` was added in API level 29 so it's looked
+    // up at runtime, to avoid failing to load the library on older devices. The older
+    // `AChoreographer_postFrameCallback` is avoided since its frame time overflows
+    // on 32-bit devices.
+    post_frame_callback: Option<PostFrameCallback64>,
+}
+
+#[cfg(not(feature = "mock-activity"))]
+struct CallbackData {
+    frames: Arc<FrameCallbacks>,
+    waker: AndroidAppWaker,
+}
+
+#[cfg(not(feature = "mock-activity"))]
+impl Choreographer {
+    pub(crate) fn new() -> Self {
+        let symbol = unsafe { libc::dlsym(libc::RTLD_DEFAULT, b"AChoreographer_postFrameCallback64\0".as_ptr().cast()) };
+        let post_frame_callback = if symbol.is_null() {
+            None
+        } else {
+            Some(unsafe { std::mem::transmute::<*mut c_void, PostFrameCallback64>(symbol) })
+        };
+        Self { post_frame_callback }
+    }
+
+    /// Posts a callback that will record the time of the next frame in `frames` and
+    /// then wake up the looper
+    ///
+    /// This must be called on the thread running `android_main`, since that's the
+    /// thread whose looper will run the callback.
+    pub(crate) fn post(&self, frames: &Arc<FrameCallbacks>, waker: AndroidAppWaker) -> io::Result<()> {
+        let post_frame_callback = self.post_frame_callback.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Unsupported, "Frame callbacks require Android 10 (API level 29)")
+        })?;
+        let choreographer = unsafe { ndk_sys::AChoreographer_getInstance() };
+        if choreographer.is_null() {
+            return Err(io::Error::new(io::ErrorKind::Other, "No AChoreographer for the current thread"));
+        }
+
+        let data = Box::new(CallbackData { frames: frames.clone(), waker });
+        unsafe {
+            post_frame_callback(choreographer, frame_callback, Box::into_raw(data).cast());
+        }
+        Ok(())
+    }
+}
+
+// Called by ALooper_pollAll(), which doesn't return after running callbacks, so
+// the looper is explicitly woken up for the frame to be delivered (with the "epoll"
+// feature this wakes up the epoll instance that's nested in the ALooper)
+#[cfg(not(feature = "mock-activity"))]
+unsafe extern "C" fn frame_callback(frame_time_nanos: i64, data: *mut c_void) {
+    let data = Box::from_raw(data.cast::<CallbackData>());
+    if data.frames.on_frame(frame_time_nanos) {
+        data.waker.wake();
+    }
+}
@@ -681,9 +681,10 @@ pub const PTHREAD_PROCESS_PRIVATE: u32 = 0;
 pub const PTHREAD_PROCESS_SHARED: u32 = 1;
 pub const PTHREAD_SCOPE_SYSTEM: u32 = 0;
 pub const PTHREAD_SCOPE_PROCESS: u32 = 1;
-pub const NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS: u32 = 16;
-pub const NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES: u32 = 64;
-pub const NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS: u32 = 4;
+pub const NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS: u32 = 1024;
+pub const NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES: u32 = 8192;
+pub const NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS: u32 = 1024;
+pub const NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS: u32 = 100;
 pub const NATIVE_APP_GLUE_MAX_INPUT_BUFFERS: u32 = 2;
 extern "C" {
     pub fn android_get_application_target_sdk_version() -> ::std::os::raw::c_int;
@@ -8472,12 +8473,14 @@ fn bindgen_test_layout_android_poll_source() {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct android_input_buffer {
-    #[doc = " Pointer to a read-only array of pointers to GameActivityMotionEvent."]
+    #[doc = " Pointer to a read-only array of GameActivityMotionEvent."]
     #[doc = " Only the first motionEventsCount events are valid."]
-    pub motionEvents: [GameActivityMotionEvent; 16usize],
+    pub motionEvents: *mut GameActivityMotionEvent,
     #[doc = " The number of valid motion events in `motionEvents`."]
     pub motionEventsCount: u64,
-    #[doc = " Pointer to a read-only array of pointers to GameActivityHistoricalPointerAxes."]
+    #[doc = " The number of motion events that `motionEvents` has space for."]
+    pub motionEventsCapacity: u64,
+    #[doc = " Pointer to a read-only array of GameActivityHistoricalPointerAxes."]
     #[doc = ""]
     #[doc = " Only the first historicalSamplesCount samples are valid."]
     #[doc = " Refer to event->historicalStart, event->pointerCount and event->historicalCount"]
@@ -8489,20 +8492,24 @@ pub struct android_input_buffer {
     #[doc = " E.g. you would access historic sample index 3 for pointer 2 of an event with:"]
     #[doc = ""]
     #[doc = "   historicalAxisSamples[event->historicalStart + (event->historicalCount * 2) + 3];"]
-    pub historicalAxisSamples: [GameActivityHistoricalPointerAxes; 64usize],
+    pub historicalAxisSamples: *mut GameActivityHistoricalPointerAxes,
     #[doc = " The number of valid historical samples in `historicalAxisSamples`."]
     pub historicalSamplesCount: u64,
-    #[doc = " Pointer to a read-only array of pointers to GameActivityKeyEvent."]
+    #[doc = " The number of historical samples that `historicalAxisSamples` has space for."]
+    pub historicalSamplesCapacity: u64,
+    #[doc = " Pointer to a read-only array of GameActivityKeyEvent."]
     #[doc = " Only the first keyEventsCount events are valid."]
-    pub keyEvents: [GameActivityKeyEvent; 4usize],
+    pub keyEvents: *mut GameActivityKeyEvent,
     #[doc = " The number of valid \"Key\" events in `keyEvents`."]
     pub keyEventsCount: u64,
+    #[doc = " The number of key events that `keyEvents` has space for."]
+    pub keyEventsCapacity: u64,
 }
 #[test]
 fn bindgen_test_layout_android_input_buffer() {
     assert_eq!(
         ::std::mem::size_of::<android_input_buffer>(),
-        40312usize,
+        72usize,
         concat!("Size of: ", stringify!(android_input_buffer))
     );
     assert_eq!(
@@ -8526,7 +8533,7 @@ fn bindgen_test_layout_android_input_buffer() {
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).motionEventsCount as *const _ as usize
         },
-        27264usize,
+        8usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -8534,12 +8541,25 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(motionEventsCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).motionEventsCapacity as *const _
+                as usize
+        },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(motionEventsCapacity)
+        )
+    );
     assert_eq!(
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).historicalAxisSamples as *const _
                 as usize
         },
-        27272usize,
+        24usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -8552,7 +8572,7 @@ fn bindgen_test_layout_android_input_buffer() {
             &(*(::std::ptr::null::<android_input_buffer>())).historicalSamplesCount as *const _
                 as usize
         },
-        40072usize,
+        32usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -8560,9 +8580,22 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(historicalSamplesCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).historicalSamplesCapacity as *const _
+                as usize
+        },
+        40usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(historicalSamplesCapacity)
+        )
+    );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_input_buffer>())).keyEvents as *const _ as usize },
-        40080usize,
+        48usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -8574,7 +8607,7 @@ fn bindgen_test_layout_android_input_buffer() {
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).keyEventsCount as *const _ as usize
         },
-        40304usize,
+        56usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -8582,6 +8615,18 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(keyEventsCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).keyEventsCapacity as *const _ as usize
+        },
+        64usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(keyEventsCapacity)
+        )
+    );
 }
 #[doc = " Function pointer declaration for the filtering of key events."]
 #[doc = " A function with this signature should be passed to"]
@@ -8699,12 +8744,23 @@ pub struct android_app {
     pub pendingContentRect: ARect,
     pub keyEventFilter: android_key_event_filter,
     pub motionEventFilter: android_motion_event_filter,
+    pub editorAction: ::std::os::raw::c_int,
+    pub trimMemoryLevel: ::std::os::raw::c_int,
+    pub droppedInputEvents: u64,
+    pub keyEventsQueued: u64,
+    pub keyEventsDispatched: u64,
+    pub keyEventsFinished: u64,
+    pub motionEventsQueued: u64,
+    pub motionEventsDispatched: u64,
+    pub motionEventsFinished: u64,
+    pub keyEventHandled: bool,
+    pub motionEventHandled: bool,
 }
 #[test]
 fn bindgen_test_layout_android_app() {
     assert_eq!(
         ::std::mem::size_of::<android_app>(),
-        80768usize,
+        360usize,
         concat!("Size of: ", stringify!(android_app))
     );
     assert_eq!(
@@ -8834,7 +8890,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).currentInputBuffer as *const _ as usize },
-        80680usize,
+        200usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8844,7 +8900,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).textInputState as *const _ as usize },
-        80684usize,
+        204usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8854,7 +8910,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).mutex as *const _ as usize },
-        80688usize,
+        208usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8864,7 +8920,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).cond as *const _ as usize },
-        80692usize,
+        212usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8874,7 +8930,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).msgread as *const _ as usize },
-        80696usize,
+        216usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8884,7 +8940,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).msgwrite as *const _ as usize },
-        80700usize,
+        220usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8894,7 +8950,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).thread as *const _ as usize },
-        80704usize,
+        224usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8904,7 +8960,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).cmdPollSource as *const _ as usize },
-        80708usize,
+        228usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8914,7 +8970,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).running as *const _ as usize },
-        80720usize,
+        240usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8924,7 +8980,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).stateSaved as *const _ as usize },
-        80724usize,
+        244usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8934,7 +8990,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).destroyed as *const _ as usize },
-        80728usize,
+        248usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8944,7 +9000,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).redrawNeeded as *const _ as usize },
-        80732usize,
+        252usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8954,7 +9010,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).pendingWindow as *const _ as usize },
-        80736usize,
+        256usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8964,7 +9020,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).pendingContentRect as *const _ as usize },
-        80740usize,
+        260usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8974,7 +9030,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).keyEventFilter as *const _ as usize },
-        80756usize,
+        276usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8984,7 +9040,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).motionEventFilter as *const _ as usize },
-        80760usize,
+        280usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -8992,6 +9048,116 @@ fn bindgen_test_layout_android_app() {
             stringify!(motionEventFilter)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).editorAction as *const _ as usize },
+        284usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(editorAction)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).trimMemoryLevel as *const _ as usize },
+        288usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(trimMemoryLevel)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).droppedInputEvents as *const _ as usize },
+        296usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(droppedInputEvents)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsQueued as *const _ as usize },
+        304usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsQueued)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsDispatched as *const _ as usize },
+        312usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsDispatched)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsFinished as *const _ as usize },
+        320usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsFinished)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsQueued as *const _ as usize },
+        328usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsQueued)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsDispatched as *const _ as usize },
+        336usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsDispatched)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsFinished as *const _ as usize },
+        344usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsFinished)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventHandled as *const _ as usize },
+        352usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventHandled)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventHandled as *const _ as usize },
+        353usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventHandled)
+        )
+    );
 }
 #[doc = " Looper data ID of commands coming from the app's main thread, which"]
 #[doc = " is returned as an identifier from ALooper_pollOnce().  The data for this"]
@@ -9102,6 +9268,32 @@ extern "C" {
     #[doc = " your game loop. You should handle events at each iteration of your game loop."]
     pub fn android_app_clear_key_events(inputBuffer: *mut android_input_buffer);
 }
+extern "C" {
+    #[doc = " Report whether the application handled the next key event from the buffer"]
+    #[doc = " returned by android_app_swap_input_buffers(), in order. If it's not handled,"]
+    #[doc = " GameActivity's onKeyDown() or onKeyUp() returns false so that the system can"]
+    #[doc = " handle the event instead (e.g. for volume or Back keys)."]
+    #[doc = ""]
+    #[doc = " Any events in the buffer that aren't reported by the next call to"]
+    #[doc = " android_app_swap_input_buffers() are treated as handled."]
+    pub fn android_app_finish_key_event(android_app: *mut android_app, handled: bool);
+}
+extern "C" {
+    #[doc = " Report whether the application handled the next motion event from the buffer"]
+    #[doc = " returned by android_app_swap_input_buffers(), in order. If it's not handled,"]
+    #[doc = " GameActivity's onTouchEvent() returns false so that the system can handle the"]
+    #[doc = " event instead."]
+    #[doc = ""]
+    #[doc = " Any events in the buffer that aren't reported by the next call to"]
+    #[doc = " android_app_swap_input_buffers() are treated as handled."]
+    pub fn android_app_finish_motion_event(android_app: *mut android_app, handled: bool);
+}
+extern "C" {
+    #[doc = " Get the total number of key and motion events that have been dropped because"]
+    #[doc = " the input buffers were full (see NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS and"]
+    #[doc = " NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS) or couldn't be grown."]
+    pub fn android_app_get_dropped_input_events(android_app: *mut android_app) -> u64;
+}
 extern "C" {
     #[doc = " This is a springboard into the Rust glue layer that wraps calling the"]
     #[doc = " main entry for the app itself."]
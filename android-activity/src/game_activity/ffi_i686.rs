@@ -601,9 +601,10 @@ pub const PTHREAD_PROCESS_PRIVATE: u32 = 0;
 pub const PTHREAD_PROCESS_SHARED: u32 = 1;
 pub const PTHREAD_SCOPE_SYSTEM: u32 = 0;
 pub const PTHREAD_SCOPE_PROCESS: u32 = 1;
-pub const NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS: u32 = 16;
-pub const NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES: u32 = 64;
-pub const NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS: u32 = 4;
+pub const NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS: u32 = 1024;
+pub const NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES: u32 = 8192;
+pub const NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS: u32 = 1024;
+pub const NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS: u32 = 100;
 pub const NATIVE_APP_GLUE_MAX_INPUT_BUFFERS: u32 = 2;
 extern "C" {
     pub fn android_get_application_target_sdk_version() -> ::std::os::raw::c_int;
@@ -10217,12 +10218,14 @@ fn bindgen_test_layout_android_poll_source() {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct android_input_buffer {
-    #[doc = " Pointer to a read-only array of pointers to GameActivityMotionEvent."]
+    #[doc = " Pointer to a read-only array of GameActivityMotionEvent."]
     #[doc = " Only the first motionEventsCount events are valid."]
-    pub motionEvents: [GameActivityMotionEvent; 16usize],
+    pub motionEvents: *mut GameActivityMotionEvent,
     #[doc = " The number of valid motion events in `motionEvents`."]
     pub motionEventsCount: u64,
-    #[doc = " Pointer to a read-only array of pointers to GameActivityHistoricalPointerAxes."]
+    #[doc = " The number of motion events that `motionEvents` has space for."]
+    pub motionEventsCapacity: u64,
+    #[doc = " Pointer to a read-only array of GameActivityHistoricalPointerAxes."]
     #[doc = ""]
     #[doc = " Only the first historicalSamplesCount samples are valid."]
     #[doc = " Refer to event->historicalStart, event->pointerCount and event->historicalCount"]
@@ -10234,20 +10237,24 @@ pub struct android_input_buffer {
     #[doc = " E.g. you would access historic sample index 3 for pointer 2 of an event with:"]
     #[doc = ""]
     #[doc = "   historicalAxisSamples[event->historicalStart + (event->historicalCount * 2) + 3];"]
-    pub historicalAxisSamples: [GameActivityHistoricalPointerAxes; 64usize],
+    pub historicalAxisSamples: *mut GameActivityHistoricalPointerAxes,
     #[doc = " The number of valid historical samples in `historicalAxisSamples`."]
     pub historicalSamplesCount: u64,
-    #[doc = " Pointer to a read-only array of pointers to GameActivityKeyEvent."]
+    #[doc = " The number of historical samples that `historicalAxisSamples` has space for."]
+    pub historicalSamplesCapacity: u64,
+    #[doc = " Pointer to a read-only array of GameActivityKeyEvent."]
     #[doc = " Only the first keyEventsCount events are valid."]
-    pub keyEvents: [GameActivityKeyEvent; 4usize],
+    pub keyEvents: *mut GameActivityKeyEvent,
     #[doc = " The number of valid \"Key\" events in `keyEvents`."]
     pub keyEventsCount: u64,
+    #[doc = " The number of key events that `keyEvents` has space for."]
+    pub keyEventsCapacity: u64,
 }
 #[test]
 fn bindgen_test_layout_android_input_buffer() {
     assert_eq!(
         ::std::mem::size_of::<android_input_buffer>(),
-        40232usize,
+        60usize,
         concat!("Size of: ", stringify!(android_input_buffer))
     );
     assert_eq!(
@@ -10271,7 +10278,7 @@ fn bindgen_test_layout_android_input_buffer() {
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).motionEventsCount as *const _ as usize
         },
-        27200usize,
+        4usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10279,12 +10286,25 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(motionEventsCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).motionEventsCapacity as *const _
+                as usize
+        },
+        12usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(motionEventsCapacity)
+        )
+    );
     assert_eq!(
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).historicalAxisSamples as *const _
                 as usize
         },
-        27208usize,
+        20usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10297,7 +10317,7 @@ fn bindgen_test_layout_android_input_buffer() {
             &(*(::std::ptr::null::<android_input_buffer>())).historicalSamplesCount as *const _
                 as usize
         },
-        40008usize,
+        24usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10305,9 +10325,22 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(historicalSamplesCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).historicalSamplesCapacity as *const _
+                as usize
+        },
+        32usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(historicalSamplesCapacity)
+        )
+    );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_input_buffer>())).keyEvents as *const _ as usize },
-        40016usize,
+        40usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10319,7 +10352,7 @@ fn bindgen_test_layout_android_input_buffer() {
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).keyEventsCount as *const _ as usize
         },
-        40224usize,
+        44usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10327,6 +10360,18 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(keyEventsCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).keyEventsCapacity as *const _ as usize
+        },
+        52usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(keyEventsCapacity)
+        )
+    );
 }
 #[doc = " Function pointer declaration for the filtering of key events."]
 #[doc = " A function with this signature should be passed to"]
@@ -10444,12 +10489,23 @@ pub struct android_app {
     pub pendingContentRect: ARect,
     pub keyEventFilter: android_key_event_filter,
     pub motionEventFilter: android_motion_event_filter,
+    pub editorAction: ::std::os::raw::c_int,
+    pub trimMemoryLevel: ::std::os::raw::c_int,
+    pub droppedInputEvents: u64,
+    pub keyEventsQueued: u64,
+    pub keyEventsDispatched: u64,
+    pub keyEventsFinished: u64,
+    pub motionEventsQueued: u64,
+    pub motionEventsDispatched: u64,
+    pub motionEventsFinished: u64,
+    pub keyEventHandled: bool,
+    pub motionEventHandled: bool,
 }
 #[test]
 fn bindgen_test_layout_android_app() {
     assert_eq!(
         ::std::mem::size_of::<android_app>(),
-        80604usize,
+        328usize,
         concat!("Size of: ", stringify!(android_app))
     );
     assert_eq!(
@@ -10579,7 +10635,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).currentInputBuffer as *const _ as usize },
-        80520usize,
+        176usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10589,7 +10645,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).textInputState as *const _ as usize },
-        80524usize,
+        180usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10599,7 +10655,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).mutex as *const _ as usize },
-        80528usize,
+        184usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10609,7 +10665,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).cond as *const _ as usize },
-        80532usize,
+        188usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10619,7 +10675,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).msgread as *const _ as usize },
-        80536usize,
+        192usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10629,7 +10685,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).msgwrite as *const _ as usize },
-        80540usize,
+        196usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10639,7 +10695,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).thread as *const _ as usize },
-        80544usize,
+        200usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10649,7 +10705,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).cmdPollSource as *const _ as usize },
-        80548usize,
+        204usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10659,7 +10715,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).running as *const _ as usize },
-        80560usize,
+        216usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10669,7 +10725,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).stateSaved as *const _ as usize },
-        80564usize,
+        220usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10679,7 +10735,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).destroyed as *const _ as usize },
-        80568usize,
+        224usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10689,7 +10745,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).redrawNeeded as *const _ as usize },
-        80572usize,
+        228usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10699,7 +10755,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).pendingWindow as *const _ as usize },
-        80576usize,
+        232usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10709,7 +10765,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).pendingContentRect as *const _ as usize },
-        80580usize,
+        236usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10719,7 +10775,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).keyEventFilter as *const _ as usize },
-        80596usize,
+        252usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10729,7 +10785,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).motionEventFilter as *const _ as usize },
-        80600usize,
+        256usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10737,6 +10793,116 @@ fn bindgen_test_layout_android_app() {
             stringify!(motionEventFilter)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).editorAction as *const _ as usize },
+        260usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(editorAction)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).trimMemoryLevel as *const _ as usize },
+        264usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(trimMemoryLevel)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).droppedInputEvents as *const _ as usize },
+        268usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(droppedInputEvents)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsQueued as *const _ as usize },
+        276usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsQueued)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsDispatched as *const _ as usize },
+        284usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsDispatched)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsFinished as *const _ as usize },
+        292usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsFinished)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsQueued as *const _ as usize },
+        300usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsQueued)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsDispatched as *const _ as usize },
+        308usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsDispatched)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsFinished as *const _ as usize },
+        316usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsFinished)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventHandled as *const _ as usize },
+        324usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventHandled)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventHandled as *const _ as usize },
+        325usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventHandled)
+        )
+    );
 }
 #[doc = " Looper data ID of commands coming from the app's main thread, which"]
 #[doc = " is returned as an identifier from ALooper_pollOnce().  The data for this"]
@@ -10847,6 +11013,32 @@ extern "C" {
     #[doc = " your game loop. You should handle events at each iteration of your game loop."]
     pub fn android_app_clear_key_events(inputBuffer: *mut android_input_buffer);
 }
+extern "C" {
+    #[doc = " Report whether the application handled the next key event from the buffer"]
+    #[doc = " returned by android_app_swap_input_buffers(), in order. If it's not handled,"]
+    #[doc = " GameActivity's onKeyDown() or onKeyUp() returns false so that the system can"]
+    #[doc = " handle the event instead (e.g. for volume or Back keys)."]
+    #[doc = ""]
+    #[doc = " Any events in the buffer that aren't reported by the next call to"]
+    #[doc = " android_app_swap_input_buffers() are treated as handled."]
+    pub fn android_app_finish_key_event(android_app: *mut android_app, handled: bool);
+}
+extern "C" {
+    #[doc = " Report whether the application handled the next motion event from the buffer"]
+    #[doc = " returned by android_app_swap_input_buffers(), in order. If it's not handled,"]
+    #[doc = " GameActivity's onTouchEvent() returns false so that the system can handle the"]
+    #[doc = " event instead."]
+    #[doc = ""]
+    #[doc = " Any events in the buffer that aren't reported by the next call to"]
+    #[doc = " android_app_swap_input_buffers() are treated as handled."]
+    pub fn android_app_finish_motion_event(android_app: *mut android_app, handled: bool);
+}
+extern "C" {
+    #[doc = " Get the total number of key and motion events that have been dropped because"]
+    #[doc = " the input buffers were full (see NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS and"]
+    #[doc = " NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS) or couldn't be grown."]
+    pub fn android_app_get_dropped_input_events(android_app: *mut android_app) -> u64;
+}
 extern "C" {
     #[doc = " This is a springboard into the Rust glue layer that wraps calling the"]
     #[doc = " main entry for the app itself."]
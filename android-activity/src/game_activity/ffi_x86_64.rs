@@ -629,9 +629,10 @@ pub const PTHREAD_PROCESS_PRIVATE: u32 = 0;
 pub const PTHREAD_PROCESS_SHARED: u32 = 1;
 pub const PTHREAD_SCOPE_SYSTEM: u32 = 0;
 pub const PTHREAD_SCOPE_PROCESS: u32 = 1;
-pub const NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS: u32 = 16;
-pub const NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES: u32 = 64;
-pub const NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS: u32 = 4;
+pub const NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS: u32 = 1024;
+pub const NATIVE_APP_GLUE_MAX_HISTORICAL_POINTER_SAMPLES: u32 = 8192;
+pub const NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS: u32 = 1024;
+pub const NATIVE_APP_GLUE_INPUT_EVENT_TIMEOUT_MS: u32 = 100;
 pub const NATIVE_APP_GLUE_MAX_INPUT_BUFFERS: u32 = 2;
 extern "C" {
     pub fn android_get_application_target_sdk_version() -> ::std::os::raw::c_int;
@@ -10246,12 +10247,14 @@ fn bindgen_test_layout_android_poll_source() {
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct android_input_buffer {
-    #[doc = " Pointer to a read-only array of pointers to GameActivityMotionEvent."]
+    #[doc = " Pointer to a read-only array of GameActivityMotionEvent."]
     #[doc = " Only the first motionEventsCount events are valid."]
-    pub motionEvents: [GameActivityMotionEvent; 16usize],
+    pub motionEvents: *mut GameActivityMotionEvent,
     #[doc = " The number of valid motion events in `motionEvents`."]
     pub motionEventsCount: u64,
-    #[doc = " Pointer to a read-only array of pointers to GameActivityHistoricalPointerAxes."]
+    #[doc = " The number of motion events that `motionEvents` has space for."]
+    pub motionEventsCapacity: u64,
+    #[doc = " Pointer to a read-only array of GameActivityHistoricalPointerAxes."]
     #[doc = ""]
     #[doc = " Only the first historicalSamplesCount samples are valid."]
     #[doc = " Refer to event->historicalStart, event->pointerCount and event->historicalCount"]
@@ -10263,20 +10266,24 @@ pub struct android_input_buffer {
     #[doc = " E.g. you would access historic sample index 3 for pointer 2 of an event with:"]
     #[doc = ""]
     #[doc = "   historicalAxisSamples[event->historicalStart + (event->historicalCount * 2) + 3];"]
-    pub historicalAxisSamples: [GameActivityHistoricalPointerAxes; 64usize],
+    pub historicalAxisSamples: *mut GameActivityHistoricalPointerAxes,
     #[doc = " The number of valid historical samples in `historicalAxisSamples`."]
     pub historicalSamplesCount: u64,
-    #[doc = " Pointer to a read-only array of pointers to GameActivityKeyEvent."]
+    #[doc = " The number of historical samples that `historicalAxisSamples` has space for."]
+    pub historicalSamplesCapacity: u64,
+    #[doc = " Pointer to a read-only array of GameActivityKeyEvent."]
     #[doc = " Only the first keyEventsCount events are valid."]
-    pub keyEvents: [GameActivityKeyEvent; 4usize],
+    pub keyEvents: *mut GameActivityKeyEvent,
     #[doc = " The number of valid \"Key\" events in `keyEvents`."]
     pub keyEventsCount: u64,
+    #[doc = " The number of key events that `keyEvents` has space for."]
+    pub keyEventsCapacity: u64,
 }
 #[test]
 fn bindgen_test_layout_android_input_buffer() {
     assert_eq!(
         ::std::mem::size_of::<android_input_buffer>(),
-        40312usize,
+        72usize,
         concat!("Size of: ", stringify!(android_input_buffer))
     );
     assert_eq!(
@@ -10300,7 +10307,7 @@ fn bindgen_test_layout_android_input_buffer() {
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).motionEventsCount as *const _ as usize
         },
-        27264usize,
+        8usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10308,12 +10315,25 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(motionEventsCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).motionEventsCapacity as *const _
+                as usize
+        },
+        16usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(motionEventsCapacity)
+        )
+    );
     assert_eq!(
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).historicalAxisSamples as *const _
                 as usize
         },
-        27272usize,
+        24usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10326,7 +10346,7 @@ fn bindgen_test_layout_android_input_buffer() {
             &(*(::std::ptr::null::<android_input_buffer>())).historicalSamplesCount as *const _
                 as usize
         },
-        40072usize,
+        32usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10334,9 +10354,22 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(historicalSamplesCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).historicalSamplesCapacity as *const _
+                as usize
+        },
+        40usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(historicalSamplesCapacity)
+        )
+    );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_input_buffer>())).keyEvents as *const _ as usize },
-        40080usize,
+        48usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10348,7 +10381,7 @@ fn bindgen_test_layout_android_input_buffer() {
         unsafe {
             &(*(::std::ptr::null::<android_input_buffer>())).keyEventsCount as *const _ as usize
         },
-        40304usize,
+        56usize,
         concat!(
             "Offset of field: ",
             stringify!(android_input_buffer),
@@ -10356,6 +10389,18 @@ fn bindgen_test_layout_android_input_buffer() {
             stringify!(keyEventsCount)
         )
     );
+    assert_eq!(
+        unsafe {
+            &(*(::std::ptr::null::<android_input_buffer>())).keyEventsCapacity as *const _ as usize
+        },
+        64usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_input_buffer),
+            "::",
+            stringify!(keyEventsCapacity)
+        )
+    );
 }
 #[doc = " Function pointer declaration for the filtering of key events."]
 #[doc = " A function with this signature should be passed to"]
@@ -10473,12 +10518,23 @@ pub struct android_app {
     pub pendingContentRect: ARect,
     pub keyEventFilter: android_key_event_filter,
     pub motionEventFilter: android_motion_event_filter,
+    pub editorAction: ::std::os::raw::c_int,
+    pub trimMemoryLevel: ::std::os::raw::c_int,
+    pub droppedInputEvents: u64,
+    pub keyEventsQueued: u64,
+    pub keyEventsDispatched: u64,
+    pub keyEventsFinished: u64,
+    pub motionEventsQueued: u64,
+    pub motionEventsDispatched: u64,
+    pub motionEventsFinished: u64,
+    pub keyEventHandled: bool,
+    pub motionEventHandled: bool,
 }
 #[test]
 fn bindgen_test_layout_android_app() {
     assert_eq!(
         ::std::mem::size_of::<android_app>(),
-        80904usize,
+        496usize,
         concat!("Size of: ", stringify!(android_app))
     );
     assert_eq!(
@@ -10608,7 +10664,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).currentInputBuffer as *const _ as usize },
-        80712usize,
+        232usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10618,7 +10674,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).textInputState as *const _ as usize },
-        80716usize,
+        236usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10628,7 +10684,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).mutex as *const _ as usize },
-        80720usize,
+        240usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10638,7 +10694,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).cond as *const _ as usize },
-        80760usize,
+        280usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10648,7 +10704,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).msgread as *const _ as usize },
-        80808usize,
+        328usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10658,7 +10714,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).msgwrite as *const _ as usize },
-        80812usize,
+        332usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10668,7 +10724,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).thread as *const _ as usize },
-        80816usize,
+        336usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10678,7 +10734,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).cmdPollSource as *const _ as usize },
-        80824usize,
+        344usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10688,7 +10744,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).running as *const _ as usize },
-        80848usize,
+        368usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10698,7 +10754,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).stateSaved as *const _ as usize },
-        80852usize,
+        372usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10708,7 +10764,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).destroyed as *const _ as usize },
-        80856usize,
+        376usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10718,7 +10774,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).redrawNeeded as *const _ as usize },
-        80860usize,
+        380usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10728,7 +10784,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).pendingWindow as *const _ as usize },
-        80864usize,
+        384usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10738,7 +10794,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).pendingContentRect as *const _ as usize },
-        80872usize,
+        392usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10748,7 +10804,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).keyEventFilter as *const _ as usize },
-        80888usize,
+        408usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10758,7 +10814,7 @@ fn bindgen_test_layout_android_app() {
     );
     assert_eq!(
         unsafe { &(*(::std::ptr::null::<android_app>())).motionEventFilter as *const _ as usize },
-        80896usize,
+        416usize,
         concat!(
             "Offset of field: ",
             stringify!(android_app),
@@ -10766,6 +10822,116 @@ fn bindgen_test_layout_android_app() {
             stringify!(motionEventFilter)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).editorAction as *const _ as usize },
+        424usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(editorAction)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).trimMemoryLevel as *const _ as usize },
+        428usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(trimMemoryLevel)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).droppedInputEvents as *const _ as usize },
+        432usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(droppedInputEvents)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsQueued as *const _ as usize },
+        440usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsQueued)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsDispatched as *const _ as usize },
+        448usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsDispatched)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventsFinished as *const _ as usize },
+        456usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventsFinished)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsQueued as *const _ as usize },
+        464usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsQueued)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsDispatched as *const _ as usize },
+        472usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsDispatched)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventsFinished as *const _ as usize },
+        480usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventsFinished)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).keyEventHandled as *const _ as usize },
+        488usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(keyEventHandled)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).motionEventHandled as *const _ as usize },
+        489usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(motionEventHandled)
+        )
+    );
 }
 #[doc = " Looper data ID of commands coming from the app's main thread, which"]
 #[doc = " is returned as an identifier from ALooper_pollOnce().  The data for this"]
@@ -10876,6 +11042,32 @@ extern "C" {
     #[doc = " your game loop. You should handle events at each iteration of your game loop."]
     pub fn android_app_clear_key_events(inputBuffer: *mut android_input_buffer);
 }
+extern "C" {
+    #[doc = " Report whether the application handled the next key event from the buffer"]
+    #[doc = " returned by android_app_swap_input_buffers(), in order. If it's not handled,"]
+    #[doc = " GameActivity's onKeyDown() or onKeyUp() returns false so that the system can"]
+    #[doc = " handle the event instead (e.g. for volume or Back keys)."]
+    #[doc = ""]
+    #[doc = " Any events in the buffer that aren't reported by the next call to"]
+    #[doc = " android_app_swap_input_buffers() are treated as handled."]
+    pub fn android_app_finish_key_event(android_app: *mut android_app, handled: bool);
+}
+extern "C" {
+    #[doc = " Report whether the application handled the next motion event from the buffer"]
+    #[doc = " returned by android_app_swap_input_buffers(), in order. If it's not handled,"]
+    #[doc = " GameActivity's onTouchEvent() returns false so that the system can handle the"]
+    #[doc = " event instead."]
+    #[doc = ""]
+    #[doc = " Any events in the buffer that aren't reported by the next call to"]
+    #[doc = " android_app_swap_input_buffers() are treated as handled."]
+    pub fn android_app_finish_motion_event(android_app: *mut android_app, handled: bool);
+}
+extern "C" {
+    #[doc = " Get the total number of key and motion events that have been dropped because"]
+    #[doc = " the input buffers were full (see NATIVE_APP_GLUE_MAX_NUM_MOTION_EVENTS and"]
+    #[doc = " NATIVE_APP_GLUE_MAX_NUM_KEY_EVENTS) or couldn't be grown."]
+    pub fn android_app_get_dropped_input_events(android_app: *mut android_app) -> u64;
+}
 extern "C" {
     #[doc = " This is a springboard into the Rust glue layer that wraps calling the"]
     #[doc = " main entry for the app itself."]
@@ -16,6 +16,7 @@
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use std::{convert::TryInto, ops::Deref};
 use crate::game_activity::ffi::{GameActivityMotionEvent, GameActivityKeyEvent};
+use crate::{ImeAction, TextInputState};
 
 use bitflags::bitflags;
 
@@ -25,7 +26,14 @@ use bitflags::bitflags;
 #[non_exhaustive]
 pub enum InputEvent {
     MotionEvent(MotionEvent),
-    KeyEvent(KeyEvent)
+    KeyEvent(KeyEvent),
+    /// The soft keyboard (IME) has edited the text, giving its new state
+    TextEvent(TextInputState),
+    /// The soft keyboard's (IME's) action key has been pressed
+    ///
+    /// This requires the `GameActivity` subclass to forward editor actions, see
+    /// [`AndroidApp::set_ime_editor_info()`](crate::AndroidApp::set_ime_editor_info)
+    EditorAction(ImeAction),
 }
 
 /// An enum representing the source of an [`MotionEvent`] or [`KeyEvent`]
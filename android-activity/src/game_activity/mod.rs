@@ -1,34 +1,50 @@
 #![cfg(feature="game-activity")]
 
+use std::convert::TryFrom;
 use std::ffi::{CStr, CString};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::marker::PhantomData;
-use std::os::raw;
 use std::ptr::NonNull;
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
+use std::io;
 use std::time::Duration;
-use std::{thread, ptr};
+use std::panic::AssertUnwindSafe;
+use std::ptr;
 use std::os::unix::prelude::*;
 
-use log::{Level, error, trace};
+#[cfg(not(feature = "epoll"))]
+use log::error;
+use log::{trace, Level};
 
 use jni_sys::*;
 
-use ndk_sys::{ALooper_wake};
-use ndk_sys::{ALooper, ALooper_pollAll};
+#[cfg(not(feature = "epoll"))]
+use ndk_sys::{ALooper, ALooper_pollAll, ALooper_wake};
 
 use ndk::asset::AssetManager;
 use ndk::configuration::Configuration;
 use ndk::looper::{FdEvent};
 use ndk::native_window::NativeWindow;
 
-use crate::{MainEvent, Rect, PollEvent, AndroidApp, NativeWindowRef};
+use crate::{InputStatus, MainEvent, MemoryTrimLevel, Rect, ShowSoftInputFlags, HideSoftInputFlags, TextInputState, InputType, ImeAction, ImeOptions, RawPollEvent, AndroidApp, NativeWindowRef, PollError};
+use crate::looper::{self, FdRegistry, FdToken, TimerId, TimerKind};
+use crate::proxy::UserEventQueue;
+use crate::filter::{EventFilters, KeyEventFilter, MotionEventFilter};
+use crate::frame::FrameCallbacks;
+use crate::frame::Choreographer;
+use crate::lifecycle::LifecycleTracker;
+use crate::window::{WindowManagerFlags, WindowState};
+use crate::insets::{InsetsState, InsetsType};
+use crate::recording::Recorder;
+use crate::entry::{self, MainEntry};
+use crate::util;
+#[cfg(feature = "epoll")]
+use crate::epoll::{EpollLooper, EpollPoll, TriggerMode};
 
 mod ffi;
 
 pub mod input;
+mod text_input;
 use input::{MotionEvent, KeyEvent, Axis, InputEvent};
 
 
@@ -96,20 +112,34 @@ impl<'a> StateLoader<'a> {
     }
 }
 
+/// The backend-specific identifier for a command from the Java main thread
+pub(crate) type MainCmd = i8;
+
 #[derive(Clone)]
 pub struct AndroidAppWaker {
     // The looper pointer is owned by the android_app and effectively
     // has a 'static lifetime, and the ALooper_wake C API is thread
     // safe, so this can be cloned safely and is send + sync safe
-    looper: NonNull<ALooper>
+    #[cfg(not(feature = "epoll"))]
+    looper: NonNull<ALooper>,
+    #[cfg(feature = "epoll")]
+    looper: Arc<EpollLooper>,
 }
+#[cfg(not(feature = "epoll"))]
 unsafe impl Send for AndroidAppWaker {}
+#[cfg(not(feature = "epoll"))]
 unsafe impl Sync for AndroidAppWaker {}
 
 impl AndroidAppWaker {
+    #[cfg(not(feature = "epoll"))]
     pub fn wake(&self) {
         unsafe { ALooper_wake(self.looper.as_ptr()); }
     }
+
+    #[cfg(feature = "epoll")]
+    pub fn wake(&self) {
+        self.looper.wake();
+    }
 }
 
 impl AndroidApp {
@@ -123,11 +153,40 @@ impl AndroidApp {
         // config state with a deep copy.
         let config = Configuration::clone_from_ptr(NonNull::new_unchecked((*ptr.as_ptr()).config));
 
+        // With the "epoll" feature we multiplex the glue's command pipe along with any
+        // user fds and timers ourselves. The epoll instance is nested in the ALooper that
+        // the native app glue prepared for this thread, which is only polled to block
+        // until there's an event, so it can still run callbacks and be woken up. Since
+        // user fds are only added to the epoll instance, its ident isn't otherwise used
+        // by the ALooper.
+        #[cfg(feature = "epoll")]
+        let epoll = {
+            let epoll = EpollLooper::new().expect("Failed to create epoll looper");
+            epoll.add_fd((*ptr.as_ptr()).msgread, ffi::NativeAppGlueLooperId_LOOPER_ID_MAIN as i32, FdEvent::INPUT, TriggerMode::Level)
+                .expect("Failed to add glue command pipe to epoll looper");
+            epoll.nest_in_alooper((*ptr.as_ptr()).looper, ffi::NativeAppGlueLooperId_LOOPER_ID_USER as i32)
+                .expect("Failed to add epoll looper to the ALooper");
+            Arc::new(epoll)
+        };
+
+        let mut insets = InsetsState::default();
+        insets.update(|ty| read_insets(ptr, ty));
+
         Self {
             inner: Arc::new(AndroidAppInner {
                 ptr,
                 config: RwLock::new(config),
-                native_window: Default::default(),
+                window: RwLock::new(WindowState::with_content_rect(read_content_rect(ptr))),
+                insets: RwLock::new(insets),
+                trim_memory_level: RwLock::new(MemoryTrimLevel::Complete),
+                fd_registry: Mutex::new(FdRegistry::new(ffi::NativeAppGlueLooperId_LOOPER_ID_USER as i32)),
+                user_events: Default::default(),
+                frames: Default::default(),
+                choreographer: Choreographer::new(),
+                lifecycle: Default::default(),
+                recorder: Default::default(),
+                #[cfg(feature = "epoll")]
+                epoll,
             })
         }
     }
@@ -137,24 +196,81 @@ impl AndroidApp {
 pub struct AndroidAppInner {
     ptr: NonNull<ffi::android_app>,
     config: RwLock<Configuration>,
-    native_window: RwLock<Option<NativeWindow>>,
+    window: RwLock<WindowState>,
+    insets: RwLock<InsetsState>,
+    trim_memory_level: RwLock<MemoryTrimLevel>,
+    fd_registry: Mutex<FdRegistry>,
+    user_events: Arc<UserEventQueue>,
+    frames: Arc<FrameCallbacks>,
+    choreographer: Choreographer,
+    lifecycle: Mutex<LifecycleTracker>,
+    recorder: Mutex<Option<Recorder>>,
+    #[cfg(feature = "epoll")]
+    epoll: Arc<EpollLooper>,
+}
+
+// The android_app is owned by the native app glue and stays valid until after
+// android_main returns. Its state is only accessed via the glue's thread-safe APIs,
+// or the locked copies above, except from the APIs that are documented as only being
+// safe to call from the application's main thread.
+unsafe impl Send for AndroidAppInner {}
+unsafe impl Sync for AndroidAppInner {}
+
+unsafe fn read_content_rect(app_ptr: NonNull<ffi::android_app>) -> Rect {
+    let rect = (*app_ptr.as_ptr()).contentRect;
+    Rect {
+        left: rect.left,
+        right: rect.right,
+        top: rect.top,
+        bottom: rect.bottom,
+    }
+}
+
+unsafe fn read_insets(app_ptr: NonNull<ffi::android_app>, ty: InsetsType) -> Rect {
+    let mut rect = ffi::ARect { left: 0, top: 0, right: 0, bottom: 0 };
+    ffi::GameActivity_getWindowInsets((*app_ptr.as_ptr()).activity, ty as ffi::GameCommonInsetsType, &mut rect);
+    Rect {
+        left: rect.left,
+        right: rect.right,
+        top: rect.top,
+        bottom: rect.bottom,
+    }
+}
+
+// The glue's event filters don't take a context pointer, so the application's
+// filters are kept globally and called from these trampolines
+static EVENT_FILTERS: EventFilters = EventFilters::new();
+
+// A panic mustn't unwind into the glue, so the event is delivered instead
+unsafe extern "C" fn key_event_filter(event: *const ffi::GameActivityKeyEvent) -> bool {
+    let event = KeyEvent::new(*event);
+    std::panic::catch_unwind(|| EVENT_FILTERS.accept_key(&event)).unwrap_or(true)
+}
+
+unsafe extern "C" fn motion_event_filter(event: *const ffi::GameActivityMotionEvent) -> bool {
+    let event = MotionEvent::new(*event);
+    std::panic::catch_unwind(|| EVENT_FILTERS.accept_motion(&event)).unwrap_or(true)
+}
+
+// The glue sets the level from onTrimMemory() before sending APP_CMD_LOW_MEMORY
+unsafe fn read_trim_memory_level(app_ptr: NonNull<ffi::android_app>) -> i32 {
+    let app_ptr = app_ptr.as_ptr();
+    let mutex = ptr::addr_of_mut!((*app_ptr).mutex).cast::<libc::pthread_mutex_t>();
+    libc::pthread_mutex_lock(mutex);
+    let level = (*app_ptr).trimMemoryLevel;
+    libc::pthread_mutex_unlock(mutex);
+    level
 }
 
 impl AndroidAppInner {
 
     pub fn native_window<'a>(&self) -> Option<NativeWindowRef> {
-        let guard = self.native_window.read().unwrap();
-        if let Some(ref window) = *guard {
-            Some(NativeWindowRef::new(window))
-        } else {
-            None
-        }
+        self.window.read().unwrap().native_window()
     }
 
-    pub fn poll_events<F>(&self, timeout: Option<Duration>, mut callback: F)
-        where F: FnMut(PollEvent)
-    {
-        trace!("poll_events");
+    #[cfg(not(feature = "epoll"))]
+    pub(crate) fn poll_raw(&self, timeout: Option<Duration>) -> Option<RawPollEvent> {
+        trace!("poll_raw");
 
         unsafe {
             let app_ptr = self.ptr;
@@ -169,26 +285,26 @@ impl AndroidAppInner {
             match id {
                 ffi::ALOOPER_POLL_WAKE => {
                     trace!("ALooper_pollAll returned POLL_WAKE");
-                    callback(PollEvent::Wake);
+                    Some(RawPollEvent::Wake)
                 }
                 ffi::ALOOPER_POLL_CALLBACK => {
                     // ALooper_pollAll is documented to handle all callback sources internally so it should
                     // never return a _CALLBACK source id...
                     error!("Spurious ALOOPER_POLL_CALLBACK from ALopper_pollAll() (ignored)");
+                    None
                 }
                 ffi::ALOOPER_POLL_TIMEOUT => {
                     trace!("ALooper_pollAll returned POLL_TIMEOUT");
-                    callback(PollEvent::Timeout);
+                    Some(RawPollEvent::Timeout)
                 }
                 ffi::ALOOPER_POLL_ERROR => {
                     trace!("ALooper_pollAll returned POLL_ERROR");
-                    callback(PollEvent::Error);
 
                     // Considering that this API is quite likely to be used in `android_main`
                     // it's rather unergonomic to require the call to unwrap a Result for each
-                    // call to poll_events(). Alternatively we could maybe even just panic!()
-                    // here, while it's hard to imagine practically being able to recover
-                    //return Err(LooperError);
+                    // call to poll_events(), so the error is only surfaced as a Result
+                    // via try_poll_events()
+                    Some(RawPollEvent::Error(PollError::Looper))
                 }
                 id if id >= 0 => {
                     match id as u32 {
@@ -196,70 +312,211 @@ impl AndroidAppInner {
                             trace!("ALooper_pollAll returned ID_MAIN");
                             let source: *mut ffi::android_poll_source = source.cast();
                             if source != ptr::null_mut() {
+                                // Note: the command is only read here, and it's up to the caller
+                                // to call pre_exec_cmd() and post_exec_cmd() either side of
+                                // dispatching the corresponding MainEvent
                                 let cmd_i = ffi::android_app_read_cmd(app_ptr.as_ptr());
-
-                                let cmd = match cmd_i as u32 {
-                                    //NativeAppGlueAppCmd_UNUSED_APP_CMD_INPUT_CHANGED => AndroidAppMainEvent::InputChanged,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_INIT_WINDOW => MainEvent::InitWindow {},
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_TERM_WINDOW => MainEvent::TerminateWindow {},
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_RESIZED => MainEvent::WindowResized {},
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_REDRAW_NEEDED => MainEvent::RedrawNeeded {},
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_CONTENT_RECT_CHANGED => MainEvent::ContentRectChanged,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_GAINED_FOCUS => MainEvent::GainedFocus,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_LOST_FOCUS => MainEvent::LostFocus,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_CONFIG_CHANGED => MainEvent::ConfigChanged,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_LOW_MEMORY => MainEvent::LowMemory,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_START => MainEvent::Start,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_RESUME => MainEvent::Resume { loader: StateLoader { app: &self } },
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_SAVE_STATE => MainEvent::SaveState { saver: StateSaver { app: &self } },
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_PAUSE => MainEvent::Pause,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_STOP => MainEvent::Stop,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_DESTROY => MainEvent::Destroy,
-                                    ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_INSETS_CHANGED => MainEvent::InsetsChanged {},
-                                    _ => unreachable!()
-                                };
-
-                                trace!("Read ID_MAIN command {cmd_i} = {cmd:?}");
-
-                                trace!("Calling android_app_pre_exec_cmd({cmd_i})");
-                                ffi::android_app_pre_exec_cmd(app_ptr.as_ptr(), cmd_i);
-                                match cmd {
-                                    MainEvent::ConfigChanged => {
-                                        *self.config.write().unwrap() =
-                                            Configuration::clone_from_ptr(NonNull::new_unchecked((*app_ptr.as_ptr()).config));
-                                    }
-                                    MainEvent::InitWindow { .. } => {
-                                        let win_ptr = (*app_ptr.as_ptr()).window;
-                                        *self.native_window.write().unwrap() =
-                                            Some(NativeWindow::from_ptr(NonNull::new(win_ptr).unwrap()));
-                                    }
-                                    MainEvent::TerminateWindow { .. } => {
-                                        *self.native_window.write().unwrap() = None;
-                                    }
-                                    _ => {}
+                                trace!("Read ID_MAIN command {cmd_i}");
+                                if cmd_i < 0 {
+                                    Some(RawPollEvent::Error(PollError::ReadCommand))
+                                } else {
+                                    Some(RawPollEvent::Main(cmd_i))
                                 }
-
-                                trace!("Invoking callback for ID_MAIN command = {:?}", cmd);
-                                callback(PollEvent::Main(cmd));
-
-                                trace!("Calling android_app_post_exec_cmd({cmd_i})");
-                                ffi::android_app_post_exec_cmd(app_ptr.as_ptr(), cmd_i);
                             } else {
-                                panic!("ALooper_pollAll returned ID_MAIN event with NULL android_poll_source!");
+                                Some(RawPollEvent::Error(PollError::NullPollSource))
                             }
                         }
                         _ => {
-                            let events = FdEvent::from_bits(events as u32)
-                                .expect(&format!("Spurious ALooper_pollAll event flags {:#04x}", events as u32));
+                            let events = match FdEvent::from_bits(events as u32) {
+                                Some(events) => events,
+                                None => return Some(RawPollEvent::Error(PollError::InvalidFdEvents { ident: id, fd, events: events as u32 })),
+                            };
                             trace!("Custom ALooper event source: id = {id}, fd = {fd}, events = {events:?}, data = {source:?}");
-                            callback(PollEvent::FdEvent{ ident: id, fd: fd as RawFd, events, data: source });
+                            self.fd_registry.lock().unwrap().raw_event(id, fd as RawFd, events)
                         }
                     }
                 }
                 _ => {
                     error!("Spurious ALooper_pollAll return value {id} (ignored)");
+                    None
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "epoll")]
+    pub(crate) fn poll_raw(&self, timeout: Option<Duration>) -> Option<RawPollEvent> {
+        trace!("poll_raw");
+
+        match self.epoll.poll_alooper(timeout) {
+            Ok(EpollPoll::Wake) => Some(RawPollEvent::Wake),
+            Ok(EpollPoll::Timeout) => Some(RawPollEvent::Timeout),
+            Ok(EpollPoll::Fd { ident, .. }) if ident == ffi::NativeAppGlueLooperId_LOOPER_ID_MAIN as i32 => {
+                // Note: as with ALooper, it's up to the caller to call pre_exec_cmd()
+                // and post_exec_cmd() either side of dispatching the corresponding MainEvent
+                let cmd_i = unsafe { ffi::android_app_read_cmd(self.ptr.as_ptr()) };
+                trace!("Read ID_MAIN command {cmd_i}");
+                if cmd_i < 0 {
+                    Some(RawPollEvent::Error(PollError::ReadCommand))
+                } else {
+                    Some(RawPollEvent::Main(cmd_i))
                 }
             }
+            Ok(EpollPoll::Fd { ident, fd, events }) => self.fd_registry.lock().unwrap().raw_event(ident, fd, events),
+            Err(err) => Some(RawPollEvent::Error(PollError::Io(err))),
+        }
+    }
+
+    pub(crate) fn pre_exec_cmd(&self, cmd: &MainCmd) {
+        let cmd_i = *cmd;
+        unsafe {
+            let app_ptr = self.ptr;
+
+            trace!("Calling android_app_pre_exec_cmd({cmd_i})");
+            ffi::android_app_pre_exec_cmd(app_ptr.as_ptr(), cmd_i);
+            match cmd_i as u32 {
+                ffi::NativeAppGlueAppCmd_APP_CMD_CONFIG_CHANGED => {
+                    *self.config.write().unwrap() =
+                        Configuration::clone_from_ptr(NonNull::new_unchecked((*app_ptr.as_ptr()).config));
+                }
+                ffi::NativeAppGlueAppCmd_APP_CMD_INIT_WINDOW => {
+                    let win_ptr = (*app_ptr.as_ptr()).window;
+                    self.window.write().unwrap().init_window(NativeWindow::from_ptr(NonNull::new(win_ptr).unwrap()));
+                }
+                ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_RESIZED => {
+                    self.window.write().unwrap().resize_window();
+                }
+                ffi::NativeAppGlueAppCmd_APP_CMD_CONTENT_RECT_CHANGED => {
+                    self.window.write().unwrap().set_content_rect(read_content_rect(app_ptr));
+                }
+                ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_INSETS_CHANGED => {
+                    self.insets.write().unwrap().update(|ty| read_insets(app_ptr, ty));
+                }
+                ffi::NativeAppGlueAppCmd_APP_CMD_LOW_MEMORY => {
+                    *self.trim_memory_level.write().unwrap() = MemoryTrimLevel::from_raw(read_trim_memory_level(app_ptr));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn main_event(&self, cmd: &MainCmd) -> Result<Option<MainEvent<'_>>, PollError> {
+        let cmd_i = *cmd;
+        let cmd = match cmd_i as u32 {
+            //NativeAppGlueAppCmd_UNUSED_APP_CMD_INPUT_CHANGED => AndroidAppMainEvent::InputChanged,
+            ffi::NativeAppGlueAppCmd_APP_CMD_INIT_WINDOW => return Ok(self.window.read().unwrap().init_window_event()),
+            ffi::NativeAppGlueAppCmd_APP_CMD_TERM_WINDOW => return Ok(self.window.read().unwrap().terminate_window_event()),
+            ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_RESIZED => return Ok(self.window.read().unwrap().window_resized_event()),
+            ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_REDRAW_NEEDED => MainEvent::RedrawNeeded {},
+            ffi::NativeAppGlueAppCmd_APP_CMD_CONTENT_RECT_CHANGED => self.window.read().unwrap().content_rect_changed_event(),
+            ffi::NativeAppGlueAppCmd_APP_CMD_GAINED_FOCUS => MainEvent::GainedFocus,
+            ffi::NativeAppGlueAppCmd_APP_CMD_LOST_FOCUS => MainEvent::LostFocus,
+            ffi::NativeAppGlueAppCmd_APP_CMD_CONFIG_CHANGED => MainEvent::ConfigChanged,
+            ffi::NativeAppGlueAppCmd_APP_CMD_LOW_MEMORY => MainEvent::TrimMemory { level: *self.trim_memory_level.read().unwrap() },
+            ffi::NativeAppGlueAppCmd_APP_CMD_START => MainEvent::Start,
+            ffi::NativeAppGlueAppCmd_APP_CMD_RESUME => MainEvent::Resume { loader: StateLoader { app: self } },
+            ffi::NativeAppGlueAppCmd_APP_CMD_SAVE_STATE => MainEvent::SaveState { saver: StateSaver { app: self } },
+            ffi::NativeAppGlueAppCmd_APP_CMD_PAUSE => MainEvent::Pause,
+            ffi::NativeAppGlueAppCmd_APP_CMD_STOP => MainEvent::Stop,
+            ffi::NativeAppGlueAppCmd_APP_CMD_DESTROY => MainEvent::Destroy,
+            ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_INSETS_CHANGED => MainEvent::InsetsChanged { changed: self.insets.read().unwrap().changed() },
+            _ => return Err(PollError::UnknownCommand(cmd_i as i32)),
+        };
+        Ok(Some(cmd))
+    }
+
+    pub(crate) fn post_exec_cmd(&self, cmd: &MainCmd) {
+        let cmd_i = *cmd;
+        // The window remains valid until the Java main thread is released
+        if cmd_i as u32 == ffi::NativeAppGlueAppCmd_APP_CMD_TERM_WINDOW {
+            self.window.write().unwrap().terminate_window();
+        }
+        unsafe {
+            trace!("Calling android_app_post_exec_cmd({cmd_i})");
+            ffi::android_app_post_exec_cmd(self.ptr.as_ptr(), cmd_i);
+        }
+    }
+
+    pub fn show_soft_input(&self, flags: ShowSoftInputFlags) {
+        unsafe {
+            ffi::GameActivity_showSoftInput((*self.ptr.as_ptr()).activity, flags.bits());
+        }
+    }
+
+    pub fn hide_soft_input(&self, flags: HideSoftInputFlags) {
+        unsafe {
+            ffi::GameActivity_hideSoftInput((*self.ptr.as_ptr()).activity, flags.bits());
+        }
+    }
+
+    /// Takes whether the IME has edited the text, and the last editor action it
+    /// performed, since this was last called
+    fn take_ime_events(&self) -> (bool, Option<i32>) {
+        unsafe {
+            let app_ptr = self.ptr.as_ptr();
+            let mutex = ptr::addr_of_mut!((*app_ptr).mutex).cast::<libc::pthread_mutex_t>();
+            libc::pthread_mutex_lock(mutex);
+            let changed = (*app_ptr).textInputState != 0;
+            (*app_ptr).textInputState = 0;
+            let action = std::mem::replace(&mut (*app_ptr).editorAction, -1);
+            libc::pthread_mutex_unlock(mutex);
+            (changed, (action >= 0).then_some(action))
+        }
+    }
+
+    pub fn text_input_state(&self) -> TextInputState {
+        let mut state = TextInputState::default();
+        unsafe {
+            ffi::GameActivity_getTextInputState(
+                (*self.ptr.as_ptr()).activity,
+                Some(text_input::copy_state_callback),
+                (&mut state as *mut TextInputState).cast(),
+            );
+        }
+        state
+    }
+
+    pub fn set_text_input_state(&self, state: TextInputState) {
+        text_input::with_ffi(&state, |ffi_state| unsafe {
+            ffi::GameActivity_setTextInputState((*self.ptr.as_ptr()).activity, ffi_state);
+        });
+    }
+
+    pub fn set_ime_editor_info(&self, input_type: InputType, options: ImeOptions) {
+        unsafe {
+            ffi::GameActivity_setImeEditorInfo(
+                (*self.ptr.as_ptr()).activity,
+                input_type.bits() as i32,
+                options.action() as i32,
+                options.bits() as i32,
+            );
+        }
+    }
+
+    pub fn set_window_flags(&self, add: WindowManagerFlags, remove: WindowManagerFlags) {
+        unsafe {
+            ffi::GameActivity_setWindowFlags((*self.ptr.as_ptr()).activity, add.bits(), remove.bits());
+        }
+    }
+
+    pub fn set_key_event_filter(&self, filter: KeyEventFilter) {
+        EVENT_FILTERS.set_key_filter(filter);
+        unsafe {
+            ffi::android_app_set_key_event_filter(self.ptr.as_ptr(), Some(key_event_filter));
+        }
+    }
+
+    pub fn set_motion_event_filter(&self, filter: MotionEventFilter) {
+        EVENT_FILTERS.set_motion_filter(filter);
+        unsafe {
+            ffi::android_app_set_motion_event_filter(self.ptr.as_ptr(), Some(motion_event_filter));
+        }
+    }
+
+    /// Asks the Java main thread to finish (close) the `Activity`
+    pub fn finish(&self) {
+        unsafe {
+            ffi::GameActivity_finish((*self.ptr.as_ptr()).activity);
         }
     }
 
@@ -275,6 +532,121 @@ impl AndroidAppInner {
         }
     }
 
+    #[cfg(not(feature = "epoll"))]
+    fn looper(&self) -> *mut ALooper {
+        unsafe { (*self.ptr.as_ptr()).looper }
+    }
+
+    #[cfg(not(feature = "epoll"))]
+    fn add_looper_fd(&self, fd: RawFd, ident: i32, events: FdEvent) -> io::Result<()> {
+        let ret = unsafe {
+            ndk_sys::ALooper_addFd(self.looper(), fd, ident, events.bits() as i32, None, ptr::null_mut())
+        };
+        if ret < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, format!("ALooper_addFd failed for file descriptor {fd}")));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "epoll"))]
+    fn remove_looper_fd(&self, fd: RawFd) -> io::Result<()> {
+        let ret = unsafe { ndk_sys::ALooper_removeFd(self.looper(), fd) };
+        if ret < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, format!("ALooper_removeFd failed for file descriptor {fd}")));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "epoll")]
+    fn add_looper_fd(&self, fd: RawFd, ident: i32, events: FdEvent) -> io::Result<()> {
+        self.epoll.add_fd(fd, ident, events, TriggerMode::Level)
+    }
+
+    #[cfg(feature = "epoll")]
+    fn remove_looper_fd(&self, fd: RawFd) -> io::Result<()> {
+        self.epoll.remove_fd(fd)
+    }
+
+    pub fn register_fd(&self, fd: RawFd, events: FdEvent) -> io::Result<FdToken> {
+        let mut registry = self.fd_registry.lock().unwrap();
+        let token = registry.allocate(fd)?;
+        if let Err(err) = self.add_looper_fd(fd, token.ident(), events) {
+            let _ = registry.release(token);
+            return Err(err);
+        }
+        Ok(token)
+    }
+
+    #[cfg(feature = "epoll")]
+    pub fn register_fd_with_trigger(&self, fd: RawFd, events: FdEvent, mode: TriggerMode) -> io::Result<FdToken> {
+        let mut registry = self.fd_registry.lock().unwrap();
+        let token = registry.allocate(fd)?;
+        if let Err(err) = self.epoll.add_fd(fd, token.ident(), events, mode) {
+            let _ = registry.release(token);
+            return Err(err);
+        }
+        Ok(token)
+    }
+
+    pub fn unregister_fd(&self, token: FdToken) -> io::Result<()> {
+        let fd = self.fd_registry.lock().unwrap().release(token)?;
+        self.remove_looper_fd(fd)
+    }
+
+    pub fn add_timer(&self, kind: TimerKind) -> io::Result<TimerId> {
+        let timerfd = looper::create_timerfd(kind)?;
+        let mut registry = self.fd_registry.lock().unwrap();
+        let id = match registry.allocate_timer(timerfd) {
+            Ok(id) => id,
+            Err(err) => {
+                unsafe { libc::close(timerfd); }
+                return Err(err);
+            }
+        };
+        if let Err(err) = self.add_looper_fd(timerfd, id.ident(), FdEvent::INPUT) {
+            let _ = registry.release_timer(id);
+            unsafe { libc::close(timerfd); }
+            return Err(err);
+        }
+        Ok(id)
+    }
+
+    pub fn remove_timer(&self, id: TimerId) -> io::Result<()> {
+        // Note: the registry lock is held until the timerfd is closed so that
+        // poll_raw() can't read from the fd after it's closed
+        let mut registry = self.fd_registry.lock().unwrap();
+        let timerfd = registry.release_timer(id)?;
+        let result = self.remove_looper_fd(timerfd);
+        unsafe { libc::close(timerfd); }
+        result
+    }
+
+    pub(crate) fn user_events(&self) -> &Arc<UserEventQueue> {
+        &self.user_events
+    }
+
+    pub(crate) fn frames(&self) -> &Arc<FrameCallbacks> {
+        &self.frames
+    }
+
+    pub fn request_frame_callback(&self) -> io::Result<()> {
+        self.frames.request(|| self.choreographer.post(&self.frames, self.create_waker()))
+    }
+
+    pub(crate) fn lifecycle(&self) -> &Mutex<LifecycleTracker> {
+        &self.lifecycle
+    }
+
+    pub(crate) fn recorder(&self) -> &Mutex<Option<Recorder>> {
+        &self.recorder
+    }
+
+    #[cfg(feature = "epoll")]
+    pub fn create_waker(&self) -> AndroidAppWaker {
+        AndroidAppWaker { looper: self.epoll.clone() }
+    }
+
+    #[cfg(not(feature = "epoll"))]
     pub fn create_waker(&self) -> AndroidAppWaker {
         unsafe {
             // From the application's pov we assume the app_ptr and looper pointer
@@ -289,15 +661,11 @@ impl AndroidAppInner {
     }
 
     pub fn content_rect(&self) -> Rect {
-        unsafe {
-            let app_ptr = self.ptr.as_ptr();
-            Rect {
-                left: (*app_ptr).contentRect.left,
-                right: (*app_ptr).contentRect.right,
-                top: (*app_ptr).contentRect.top,
-                bottom: (*app_ptr).contentRect.bottom,
-            }
-        }
+        self.window.read().unwrap().content_rect()
+    }
+
+    pub fn window_insets(&self, ty: InsetsType) -> Rect {
+        self.insets.read().unwrap().get(ty)
     }
 
     pub fn asset_manager(&self) -> AssetManager {
@@ -309,8 +677,23 @@ impl AndroidAppInner {
     }
 
     pub fn input_events<'b, F>(&self, mut callback: F)
-        where F: FnMut(&InputEvent)
+        where F: FnMut(&InputEvent) -> InputStatus
     {
+        // The glue only flags that the text has been edited, and the new state is
+        // then read from GameTextInput
+        let (text_changed, editor_action) = self.take_ime_events();
+        if text_changed {
+            callback(&InputEvent::TextEvent(self.text_input_state()));
+        }
+        if let Some(action) = editor_action {
+            match ImeAction::try_from(action as u32) {
+                Ok(action) => {
+                    callback(&InputEvent::EditorAction(action));
+                }
+                Err(action) => trace!("Ignoring unknown IME editor action {action}"),
+            }
+        }
+
         let buf = unsafe {
             let app_ptr = self.ptr.as_ptr();
             let input_buffer = ffi::android_app_swap_input_buffers(app_ptr);
@@ -320,14 +703,24 @@ impl AndroidAppInner {
             InputBuffer::from_ptr(NonNull::new_unchecked(input_buffer))
         };
 
+        // The Java main thread waits (briefly) for each event to be finished, so
+        // GameActivity's onKeyDown()/onTouchEvent() can pass unhandled events back
+        // to the system
+        let app_ptr = self.ptr.as_ptr();
         for key_event in buf.key_events_iter() {
-            callback(&InputEvent::KeyEvent(key_event));
+            let status = callback(&InputEvent::KeyEvent(key_event));
+            unsafe { ffi::android_app_finish_key_event(app_ptr, status == InputStatus::Handled) };
         }
         for motion_event in buf.motion_events_iter() {
-            callback(&InputEvent::MotionEvent(motion_event));
+            let status = callback(&InputEvent::MotionEvent(motion_event));
+            unsafe { ffi::android_app_finish_motion_event(app_ptr, status == InputStatus::Handled) };
         }
     }
 
+    pub fn dropped_input_events(&self) -> u64 {
+        unsafe { ffi::android_app_get_dropped_input_events(self.ptr.as_ptr()) }
+    }
+
     fn try_get_path_from_ptr(path: *const u8) -> Option<std::path::PathBuf> {
         if path == ptr::null() { return None; }
         let cstr = unsafe {
@@ -372,7 +765,7 @@ impl<'a> Iterator for MotionEventsIterator<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         if self.pos < self.count {
             unsafe {
-                let ga_event = (*self.buffer.ptr.as_ptr()).motionEvents[self.pos];
+                let ga_event = *(*self.buffer.ptr.as_ptr()).motionEvents.add(self.pos);
                 let event = MotionEvent::new(ga_event);
                 self.pos += 1;
                 Some(event)
@@ -395,7 +788,7 @@ impl<'a> Iterator for KeyEventsIterator<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         if self.pos < self.count {
             unsafe {
-                let ga_event = (*self.buffer.ptr.as_ptr()).keyEvents[self.pos];
+                let ga_event = *(*self.buffer.ptr.as_ptr()).keyEvents.add(self.pos);
                 let event = KeyEvent::new(ga_event);
                 self.pos += 1;
                 Some(event)
@@ -498,51 +891,11 @@ pub unsafe extern "C" fn GameActivity_onCreate(
     GameActivity_onCreate_C(activity, saved_state, saved_state_size);
 }
 
-fn android_log(level: Level, tag: &CStr, msg: &CStr) {
-    let prio = match level {
-        Level::Error => ndk_sys::android_LogPriority_ANDROID_LOG_ERROR,
-        Level::Warn => ndk_sys::android_LogPriority_ANDROID_LOG_WARN,
-        Level::Info => ndk_sys::android_LogPriority_ANDROID_LOG_INFO,
-        Level::Debug => ndk_sys::android_LogPriority_ANDROID_LOG_DEBUG,
-        Level::Trace => ndk_sys::android_LogPriority_ANDROID_LOG_VERBOSE,
-    };
-    unsafe {
-        ndk_sys::__android_log_write(prio as raw::c_int, tag.as_ptr(), msg.as_ptr());
-    }
-}
-
-extern "Rust" {
-    pub fn android_main(app: AndroidApp);
-}
-
 // This is a spring board between android_native_app_glue and the user's
 // `app_main` function. This is run on a dedicated thread spawned
-// by android_native_app_glue.
-#[no_mangle]
-pub unsafe extern "C" fn _rust_glue_entry(app: *mut ffi::android_app) {
-
-    // Maybe make this stdout/stderr redirection an optional / opt-in feature?...
-    let mut logpipe: [RawFd; 2] = Default::default();
-    libc::pipe(logpipe.as_mut_ptr());
-    libc::dup2(logpipe[1], libc::STDOUT_FILENO);
-    libc::dup2(logpipe[1], libc::STDERR_FILENO);
-    thread::spawn(move || {
-        let tag = CStr::from_bytes_with_nul(b"RustStdoutStderr\0").unwrap();
-        let file = File::from_raw_fd(logpipe[0]);
-        let mut reader = BufReader::new(file);
-        let mut buffer = String::new();
-        loop {
-            buffer.clear();
-            if let Ok(len) = reader.read_line(&mut buffer) {
-                if len == 0 {
-                    break;
-                } else if let Ok(msg) = CString::new(buffer.clone()) {
-                    android_log(Level::Info, tag, &msg);
-                }
-            }
-        }
-    });
-
+// by android_native_app_glue, via the `_rust_glue_entry` function that's
+// exported by the application (see `export_main!`).
+pub(crate) unsafe fn glue_entry(app: *mut ffi::android_app, entry: &MainEntry) {
     let jvm: *mut JavaVM = (*(*app).activity).vm;
     let activity: jobject = (*(*app).activity).javaGameActivity;
     ndk_context::initialize_android_context(jvm.cast(), activity.cast());
@@ -565,7 +918,17 @@ pub unsafe extern "C" fn _rust_glue_entry(app: *mut ffi::android_app) {
     // when calling FindClass to lookup a suitable classLoader, instead of
     // defaulting to the system loader. Without this then it's difficult for native
     // code to look up non-standard Java classes.
-    android_main(app);
+    //
+    // A panic must not unwind into the C glue code that called us, so it's caught
+    // here and the Activity is finished instead of being left running without an
+    // `android_main` to handle its events.
+    let main_app = app.clone();
+    if std::panic::catch_unwind(AssertUnwindSafe(|| entry.call(main_app))).is_err() {
+        let tag = CString::new("android-activity").unwrap();
+        let msg = CString::new("android_main panicked, finishing the Activity").unwrap();
+        util::android_log(Level::Error, &tag, &msg);
+        entry::finish_and_wait_for_destroy(&app);
+    }
 
     // Since this is a newly spawned thread then the JVM hasn't been attached
     // to the thread yet. Attach before calling the applications main function
@@ -0,0 +1,272 @@
+//! Conversions between [`TextInputState`] and the `GameTextInputState` of the
+//! `game-text-input` library
+//!
+//! `GameTextInputState` holds its text in Java's "modified" UTF-8 encoding, where
+//! NUL is encoded as two bytes and each character outside the Basic Multilingual
+//! Plane is encoded as a UTF-16 surrogate pair (three bytes per surrogate), and its
+//! spans are in terms of UTF-16 code units, as for a Java `String`.
+
+use std::ffi::c_void;
+use std::slice;
+
+use crate::{TextInputState, TextSpan};
+
+use super::ffi;
+
+fn decode_modified_utf8(bytes: &[u8]) -> Vec<u16> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let continuation = |n: usize| bytes.get(i + n).filter(|b| *b & 0xc0 == 0x80).map(|b| (b & 0x3f) as u16);
+        let b = bytes[i];
+        let (unit, len) = if b < 0x80 {
+            (b as u16, 1)
+        } else if let (0xc0, Some(b1)) = (b & 0xe0, continuation(1)) {
+            (((b & 0x1f) as u16) << 6 | b1, 2)
+        } else if let (0xe0, Some(b1), Some(b2)) = (b & 0xf0, continuation(1), continuation(2)) {
+            (((b & 0x0f) as u16) << 12 | b1 << 6 | b2, 3)
+        } else {
+            (char::REPLACEMENT_CHARACTER as u16, 1)
+        };
+        units.push(unit);
+        i += len;
+    }
+    units
+}
+
+fn encode_modified_utf8(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len() + 1);
+    for unit in text.encode_utf16() {
+        match unit {
+            0x0001..=0x007f => bytes.push(unit as u8),
+            0x0000 | 0x0080..=0x07ff => {
+                bytes.extend_from_slice(&[0xc0 | (unit >> 6) as u8, 0x80 | (unit & 0x3f) as u8]);
+            }
+            _ => {
+                bytes.extend_from_slice(&[
+                    0xe0 | (unit >> 12) as u8,
+                    0x80 | ((unit >> 6) & 0x3f) as u8,
+                    0x80 | (unit & 0x3f) as u8,
+                ]);
+            }
+        }
+    }
+    bytes
+}
+
+/// Converts an offset in UTF-16 code units into a byte offset, clamped to the
+/// length of `text`
+///
+/// An offset in the middle of a surrogate pair is rounded down to the start of
+/// the character.
+fn utf16_to_byte_offset(text: &str, utf16_offset: i32) -> usize {
+    let mut utf16_len = 0;
+    for (byte_offset, c) in text.char_indices() {
+        utf16_len += c.len_utf16() as i32;
+        if utf16_len > utf16_offset {
+            return byte_offset;
+        }
+    }
+    text.len()
+}
+
+fn byte_to_utf16_offset(text: &str, byte_offset: usize) -> i32 {
+    text[..byte_offset].encode_utf16().count() as i32
+}
+
+fn span_from_ffi(text: &str, span: ffi::GameTextInputSpan) -> TextSpan {
+    TextSpan {
+        start: utf16_to_byte_offset(text, span.start),
+        end: utf16_to_byte_offset(text, span.end),
+    }
+}
+
+fn span_to_ffi(text: &str, span: TextSpan) -> ffi::GameTextInputSpan {
+    // Also clamps each offset to a character boundary within the text
+    let offset = |offset: usize| {
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        byte_to_utf16_offset(text, offset)
+    };
+    ffi::GameTextInputSpan { start: offset(span.start), end: offset(span.end) }
+}
+
+/// Copies a `GameTextInputState` that's owned by the `game-text-input` library
+///
+/// # Safety
+/// `state.text_UTF8` must point to at least `state.text_length` bytes
+pub(crate) unsafe fn from_ffi(state: &ffi::GameTextInputState) -> TextInputState {
+    let bytes = if state.text_UTF8.is_null() || state.text_length <= 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(state.text_UTF8.cast::<u8>(), state.text_length as usize)
+    };
+    // Any unpaired surrogates are each replaced by a single U+FFFD, which is also
+    // a single UTF-16 code unit, so the spans still line up with the text
+    let text = String::from_utf16_lossy(&decode_modified_utf8(bytes));
+    let compose_region = state.composingRegion;
+    TextInputState {
+        selection: span_from_ffi(&text, state.selection),
+        compose_region: (compose_region.start >= 0 && compose_region.end >= 0)
+            .then(|| span_from_ffi(&text, compose_region)),
+        text,
+    }
+}
+
+/// Calls `f` with a `GameTextInputState` that borrows from `state`
+pub(crate) fn with_ffi<T>(state: &TextInputState, f: impl FnOnce(&ffi::GameTextInputState) -> T) -> T {
+    let mut text = encode_modified_utf8(&state.text);
+    let text_length = text.len() as i32;
+    text.push(0);
+    let ffi_state = ffi::GameTextInputState {
+        text_UTF8: text.as_ptr().cast(),
+        text_length,
+        selection: span_to_ffi(&state.text, state.selection),
+        composingRegion: match state.compose_region {
+            Some(region) => span_to_ffi(&state.text, region),
+            None => ffi::GameTextInputSpan { start: -1, end: -1 },
+        },
+    };
+    f(&ffi_state)
+}
+
+/// A `GameTextInputGetStateCallback` whose `context` is a `*mut TextInputState` to copy the state into
+pub(crate) unsafe extern "C" fn copy_state_callback(context: *mut c_void, state: *const ffi::GameTextInputState) {
+    if let Some(state) = state.as_ref() {
+        *context.cast::<TextInputState>() = from_ffi(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(start: i32, end: i32) -> ffi::GameTextInputSpan {
+        ffi::GameTextInputSpan { start, end }
+    }
+
+    fn state_from_bytes(bytes: &[u8], selection: ffi::GameTextInputSpan, compose_region: ffi::GameTextInputSpan) -> TextInputState {
+        let state = ffi::GameTextInputState {
+            text_UTF8: bytes.as_ptr().cast(),
+            text_length: bytes.len() as i32,
+            selection,
+            composingRegion: compose_region,
+        };
+        unsafe { from_ffi(&state) }
+    }
+
+    fn round_trip(state: &TextInputState) -> TextInputState {
+        with_ffi(state, |ffi_state| unsafe { from_ffi(ffi_state) })
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        // U+1F600 is the surrogate pair D83D DE00, with each surrogate encoded separately
+        let bytes = b"a\xed\xa0\xbd\xed\xb8\x80b";
+        let state = state_from_bytes(bytes, span(3, 4), span(1, 3));
+        assert_eq!(state.text, "a\u{1f600}b");
+        assert_eq!(state.selection, TextSpan { start: 5, end: 6 });
+        assert_eq!(state.compose_region, Some(TextSpan { start: 1, end: 5 }));
+
+        assert_eq!(encode_modified_utf8(&state.text), bytes);
+        with_ffi(&state, |ffi_state| {
+            assert_eq!((ffi_state.selection.start, ffi_state.selection.end), (3, 4));
+            assert_eq!((ffi_state.composingRegion.start, ffi_state.composingRegion.end), (1, 3));
+        });
+        assert_eq!(round_trip(&state), state);
+    }
+
+    #[test]
+    fn embedded_nul() {
+        let bytes = b"a\xc0\x80b";
+        let state = state_from_bytes(bytes, span(2, 3), span(-1, -1));
+        assert_eq!(state.text, "a\0b");
+        assert_eq!(state.selection, TextSpan { start: 2, end: 3 });
+
+        // The NUL mustn't be encoded as a 0 byte, which would terminate the C string
+        assert_eq!(encode_modified_utf8(&state.text), bytes);
+        with_ffi(&state, |ffi_state| {
+            assert_eq!(ffi_state.text_length, 4);
+            let text = unsafe { std::ffi::CStr::from_ptr(ffi_state.text_UTF8) };
+            assert_eq!(text.to_bytes(), bytes);
+        });
+        assert_eq!(round_trip(&state), state);
+    }
+
+    #[test]
+    fn offsets_within_surrogate_pair() {
+        // Offset 2 is between the two surrogates of U+1F600
+        let bytes = b"a\xed\xa0\xbd\xed\xb8\x80b";
+        let state = state_from_bytes(bytes, span(2, 2), span(0, 2));
+        assert_eq!(state.selection, TextSpan { start: 1, end: 1 });
+        assert_eq!(state.compose_region, Some(TextSpan { start: 0, end: 1 }));
+
+        // Byte offsets within the character are also moved back to its start
+        let state = TextInputState {
+            text: "a\u{1f600}b".to_string(),
+            selection: TextSpan { start: 3, end: 4 },
+            compose_region: None,
+        };
+        with_ffi(&state, |ffi_state| {
+            assert_eq!((ffi_state.selection.start, ffi_state.selection.end), (1, 1));
+        });
+    }
+
+    #[test]
+    fn negative_spans() {
+        let state = state_from_bytes(b"abc", span(-1, -1), span(-1, -1));
+        assert_eq!(state.selection, TextSpan { start: 0, end: 0 });
+        assert_eq!(state.compose_region, None);
+
+        let state = state_from_bytes(b"abc", span(-5, 2), span(1, -1));
+        assert_eq!(state.selection, TextSpan { start: 0, end: 2 });
+        assert_eq!(state.compose_region, None);
+
+        with_ffi(&state, |ffi_state| {
+            assert_eq!((ffi_state.composingRegion.start, ffi_state.composingRegion.end), (-1, -1));
+        });
+    }
+
+    #[test]
+    fn out_of_range_offsets() {
+        let state = state_from_bytes(b"ab\xc3\xa9", span(2, 100), span(4, i32::MAX));
+        assert_eq!(state.text, "ab\u{e9}");
+        assert_eq!(state.selection, TextSpan { start: 2, end: 4 });
+        assert_eq!(state.compose_region, Some(TextSpan { start: 4, end: 4 }));
+
+        let state = TextInputState {
+            text: "ab\u{e9}".to_string(),
+            selection: TextSpan { start: 10, end: usize::MAX },
+            compose_region: Some(TextSpan { start: 3, end: 4 }),
+        };
+        with_ffi(&state, |ffi_state| {
+            assert_eq!((ffi_state.selection.start, ffi_state.selection.end), (3, 3));
+            assert_eq!((ffi_state.composingRegion.start, ffi_state.composingRegion.end), (2, 3));
+        });
+    }
+
+    #[test]
+    fn invalid_encoding() {
+        // A lone surrogate, a truncated sequence and a stray continuation byte are
+        // each replaced by a single U+FFFD, so the following spans still line up
+        let bytes = b"\xed\xa0\xbdx\xc3y\x80z";
+        let state = state_from_bytes(bytes, span(5, 5), span(-1, -1));
+        assert_eq!(state.text, "\u{fffd}x\u{fffd}y\u{fffd}z");
+        assert_eq!(&state.text[state.selection.start..], "z");
+    }
+
+    #[test]
+    fn null_text() {
+        let state = ffi::GameTextInputState {
+            text_UTF8: std::ptr::null(),
+            text_length: 10,
+            selection: span(1, 2),
+            composingRegion: span(-1, -1),
+        };
+        let state = unsafe { from_ffi(&state) };
+        assert_eq!(state.text, "");
+        assert_eq!(state.selection, TextSpan { start: 0, end: 0 });
+    }
+}
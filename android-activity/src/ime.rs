@@ -0,0 +1,301 @@
+//! Control of the soft keyboard (IME)
+
+use std::convert::TryFrom;
+
+use bitflags::bitflags;
+
+bitflags! {
+    /// Flags for [`AndroidApp::show_soft_input()`](crate::AndroidApp::show_soft_input),
+    /// corresponding to the `InputMethodManager.SHOW_*` flags
+    #[derive(Default)]
+    pub struct ShowSoftInputFlags: u32 {
+        /// The soft keyboard is being shown implicitly, rather than as the result
+        /// of an explicit request from the user, and may be hidden by
+        /// [`HideSoftInputFlags::IMPLICIT_ONLY`]
+        const IMPLICIT = 0x0001;
+
+        /// The user has forced the soft keyboard open (such as by long-pressing
+        /// menu) so it shouldn't be closed until they explicitly do so
+        const FORCED = 0x0002;
+    }
+}
+
+bitflags! {
+    /// Flags for [`AndroidApp::hide_soft_input()`](crate::AndroidApp::hide_soft_input),
+    /// corresponding to the `InputMethodManager.HIDE_*` flags
+    #[derive(Default)]
+    pub struct HideSoftInputFlags: u32 {
+        /// Only hide the soft keyboard if it wasn't explicitly shown by the user
+        const IMPLICIT_ONLY = 0x0001;
+
+        /// Hide the soft keyboard, unless it was shown with [`ShowSoftInputFlags::FORCED`]
+        const NOT_ALWAYS = 0x0002;
+    }
+}
+
+/// A span of the text in a [`TextInputState`], as byte offsets
+///
+/// `start` may be greater than `end` for a selection that was made backwards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The state of the text that's being edited via the soft keyboard (IME)
+///
+/// See [`AndroidApp::text_input_state()`](crate::AndroidApp::text_input_state)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextInputState {
+    pub text: String,
+
+    /// The selected text, which is empty for a plain cursor position
+    pub selection: TextSpan,
+
+    /// The region of the text that the IME is composing, such as a word that
+    /// hasn't been accepted from its suggestions yet
+    pub compose_region: Option<TextSpan>,
+}
+
+/// The kind of content that's edited via the soft keyboard (IME), which decides
+/// the keyboard that's shown, corresponding to [`EditorInfo.inputType`]
+///
+/// This combines one of the `InputType.TYPE_CLASS_*` classes with a variation of
+/// that class and any flags that apply to it. The default is plain text.
+///
+/// [`EditorInfo.inputType`]: https://developer.android.com/reference/android/view/inputmethod/EditorInfo#inputType
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InputType {
+    bits: u32,
+}
+
+impl InputType {
+    const CLASS_TEXT: u32 = 0x1;
+    const CLASS_NUMBER: u32 = 0x2;
+    const CLASS_PHONE: u32 = 0x3;
+    const CLASS_DATETIME: u32 = 0x4;
+    const CLASS_MASK: u32 = 0xf;
+
+    /// No editor, so the IME only sends key events
+    pub const fn null() -> Self {
+        Self { bits: 0 }
+    }
+
+    pub const fn text(variation: TextVariation) -> Self {
+        Self { bits: Self::CLASS_TEXT | variation as u32 }
+    }
+
+    pub const fn number(variation: NumberVariation) -> Self {
+        Self { bits: Self::CLASS_NUMBER | variation as u32 }
+    }
+
+    pub const fn phone() -> Self {
+        Self { bits: Self::CLASS_PHONE }
+    }
+
+    pub const fn date_time(variation: DateTimeVariation) -> Self {
+        Self { bits: Self::CLASS_DATETIME | variation as u32 }
+    }
+
+    /// Adds flags for a [`text()`](Self::text) input type, which are ignored for
+    /// any other class
+    pub fn with_text_flags(mut self, flags: TextFlags) -> Self {
+        if self.bits & Self::CLASS_MASK == Self::CLASS_TEXT {
+            self.bits |= flags.bits();
+        }
+        self
+    }
+
+    /// Adds flags for a [`number()`](Self::number) input type, which are ignored
+    /// for any other class
+    pub fn with_number_flags(mut self, flags: NumberFlags) -> Self {
+        if self.bits & Self::CLASS_MASK == Self::CLASS_NUMBER {
+            self.bits |= flags.bits();
+        }
+        self
+    }
+
+    /// The `EditorInfo.inputType` value
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+}
+
+impl Default for InputType {
+    fn default() -> Self {
+        Self::text(TextVariation::Normal)
+    }
+}
+
+/// A variation of [`InputType::text()`], from the `InputType.TYPE_TEXT_VARIATION_*`
+/// constants
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum TextVariation {
+    Normal = 0x00,
+    Uri = 0x10,
+    EmailAddress = 0x20,
+    EmailSubject = 0x30,
+    ShortMessage = 0x40,
+    LongMessage = 0x50,
+    PersonName = 0x60,
+    PostalAddress = 0x70,
+    Password = 0x80,
+    VisiblePassword = 0x90,
+    WebEditText = 0xa0,
+    Filter = 0xb0,
+    Phonetic = 0xc0,
+    WebEmailAddress = 0xd0,
+    WebPassword = 0xe0,
+}
+
+/// A variation of [`InputType::number()`], from the `InputType.TYPE_NUMBER_VARIATION_*`
+/// constants
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum NumberVariation {
+    Normal = 0x00,
+    Password = 0x10,
+}
+
+/// A variation of [`InputType::date_time()`], from the
+/// `InputType.TYPE_DATETIME_VARIATION_*` constants
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum DateTimeVariation {
+    Normal = 0x00,
+    Date = 0x10,
+    Time = 0x20,
+}
+
+bitflags! {
+    /// Flags for [`InputType::with_text_flags()`], corresponding to the
+    /// `InputType.TYPE_TEXT_FLAG_*` flags
+    #[derive(Default)]
+    pub struct TextFlags: u32 {
+        const CAP_CHARACTERS = 0x0000_1000;
+        const CAP_WORDS = 0x0000_2000;
+        const CAP_SENTENCES = 0x0000_4000;
+        const AUTO_CORRECT = 0x0000_8000;
+        const AUTO_COMPLETE = 0x0001_0000;
+
+        /// Enter inserts a new line, rather than performing the editor action
+        const MULTI_LINE = 0x0002_0000;
+
+        /// The IME may show multiple lines, even though the editor itself only
+        /// accepts a single line
+        const IME_MULTI_LINE = 0x0004_0000;
+
+        const NO_SUGGESTIONS = 0x0008_0000;
+    }
+}
+
+bitflags! {
+    /// Flags for [`InputType::with_number_flags()`], corresponding to the
+    /// `InputType.TYPE_NUMBER_FLAG_*` flags
+    #[derive(Default)]
+    pub struct NumberFlags: u32 {
+        const SIGNED = 0x0000_1000;
+        const DECIMAL = 0x0000_2000;
+    }
+}
+
+/// The action of the soft keyboard's (IME's) action key, such as "Done" or "Search",
+/// corresponding to the `EditorInfo.IME_ACTION_*` constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum ImeAction {
+    /// The IME picks an action, which is usually [`ImeAction::Done`] or
+    /// [`ImeAction::Next`]
+    #[default]
+    Unspecified = 0,
+    None = 1,
+    Go = 2,
+    Search = 3,
+    Send = 4,
+    Next = 5,
+    Done = 6,
+    Previous = 7,
+}
+
+impl TryFrom<u32> for ImeAction {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => ImeAction::Unspecified,
+            1 => ImeAction::None,
+            2 => ImeAction::Go,
+            3 => ImeAction::Search,
+            4 => ImeAction::Send,
+            5 => ImeAction::Next,
+            6 => ImeAction::Done,
+            7 => ImeAction::Previous,
+            _ => return Err(value),
+        })
+    }
+}
+
+bitflags! {
+    /// Flags for [`ImeOptions::with_flags()`], corresponding to the
+    /// `EditorInfo.IME_FLAG_*` flags
+    #[derive(Default)]
+    pub struct ImeFlags: u32 {
+        /// Don't let the IME learn from what's typed, such as for an incognito mode
+        const NO_PERSONALIZED_LEARNING = 0x0100_0000;
+
+        /// Never use the IME's fullscreen mode, such as in landscape
+        const NO_FULLSCREEN = 0x0200_0000;
+
+        const NAVIGATE_PREVIOUS = 0x0400_0000;
+        const NAVIGATE_NEXT = 0x0800_0000;
+
+        /// Don't show the IME's extracted text view in its fullscreen mode
+        const NO_EXTRACT_UI = 0x1000_0000;
+
+        const NO_ACCESSORY_ACTION = 0x2000_0000;
+
+        /// Show Enter on the action key, rather than the [`ImeAction`]
+        const NO_ENTER_ACTION = 0x4000_0000;
+
+        const FORCE_ASCII = 0x8000_0000;
+    }
+}
+
+/// Options for the soft keyboard (IME), corresponding to [`EditorInfo.imeOptions`]
+///
+/// [`EditorInfo.imeOptions`]: https://developer.android.com/reference/android/view/inputmethod/EditorInfo#imeOptions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ImeOptions {
+    action: ImeAction,
+    flags: ImeFlags,
+}
+
+impl ImeOptions {
+    /// Options for an IME whose action key performs `action`
+    pub fn new(action: ImeAction) -> Self {
+        Self { action, flags: ImeFlags::empty() }
+    }
+
+    pub fn with_flags(mut self, flags: ImeFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn action(&self) -> ImeAction {
+        self.action
+    }
+
+    pub fn flags(&self) -> ImeFlags {
+        self.flags
+    }
+
+    /// The `EditorInfo.imeOptions` value
+    pub fn bits(&self) -> u32 {
+        self.action as u32 | self.flags.bits()
+    }
+}
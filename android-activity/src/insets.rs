@@ -0,0 +1,80 @@
+//! Window insets, the areas of the window that are covered by system UI (such as
+//! the status bar or soft keyboard) or are otherwise unsuitable for content
+
+use std::convert::TryFrom;
+
+use crate::Rect;
+
+/// A type of window inset, corresponding to a [`WindowInsetsCompat.Type`]
+///
+/// [`WindowInsetsCompat.Type`]: https://developer.android.com/reference/androidx/core/view/WindowInsetsCompat.Type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(u32)]
+#[non_exhaustive]
+pub enum InsetsType {
+    CaptionBar = 0,
+    DisplayCutout = 1,
+    Ime = 2,
+    MandatorySystemGestures = 3,
+    NavigationBars = 4,
+    StatusBars = 5,
+    SystemBars = 6,
+    SystemGestures = 7,
+    TappableElement = 8,
+    Waterfall = 9,
+}
+
+impl InsetsType {
+    /// All of the types of insets, ordered by their `u32` value
+    pub const ALL: [InsetsType; 10] = [
+        InsetsType::CaptionBar,
+        InsetsType::DisplayCutout,
+        InsetsType::Ime,
+        InsetsType::MandatorySystemGestures,
+        InsetsType::NavigationBars,
+        InsetsType::StatusBars,
+        InsetsType::SystemBars,
+        InsetsType::SystemGestures,
+        InsetsType::TappableElement,
+        InsetsType::Waterfall,
+    ];
+}
+
+impl TryFrom<u32> for InsetsType {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Self::ALL.get(value as usize).copied().ok_or(value)
+    }
+}
+
+/// The insets of each [`InsetsType`], as of the most recent main event, along with
+/// the types that changed with that event
+#[derive(Debug, Default)]
+pub(crate) struct InsetsState {
+    insets: [Rect; InsetsType::ALL.len()],
+    changed: Vec<InsetsType>,
+}
+
+impl InsetsState {
+    pub(crate) fn get(&self, ty: InsetsType) -> Rect {
+        self.insets[ty as usize].clone()
+    }
+
+    /// Sets the insets of each type, via `read`, and tracks which have changed
+    pub(crate) fn update(&mut self, mut read: impl FnMut(InsetsType) -> Rect) {
+        self.changed.clear();
+        for ty in InsetsType::ALL {
+            let rect = read(ty);
+            if self.insets[ty as usize] != rect {
+                self.insets[ty as usize] = rect;
+                self.changed.push(ty);
+            }
+        }
+    }
+
+    /// The types whose insets changed with the last [`update()`](Self::update)
+    pub(crate) fn changed(&self) -> Vec<InsetsType> {
+        self.changed.clone()
+    }
+}
@@ -3,25 +3,35 @@ use std::{os::unix::prelude::RawFd, sync::Arc};
 use std::hash::Hash;
 use std::ops::Deref;
 
+use log::{error, trace};
+
+#[cfg(not(feature = "mock-activity"))]
 use ndk::asset::AssetManager;
-use ndk::configuration::Configuration;
 // TODO: import FdEvent and avoid depending on ndk Looper abstraction in case we want to
 // support using epoll directly in the future.
 use ndk::looper::FdEvent;
+
+#[cfg(not(feature = "mock-activity"))]
+use ndk::configuration::Configuration;
+#[cfg(not(feature = "mock-activity"))]
 use ndk::native_window::NativeWindow;
+#[cfg(feature = "mock-activity")]
+pub use mock_activity::{Asset, AssetDir, AssetManager, Configuration, NativeWindow};
 
-#[cfg(not(target_os = "android"))]
-compile_error!("android-activity only supports compiling for Android");
+#[cfg(all(not(target_os = "android"), not(feature = "mock-activity")))]
+compile_error!("android-activity only supports compiling for Android (or for the host with the \"mock-activity\" feature)");
 
 #[cfg(all(feature = "game-activity", feature = "native-activity"))]
 compile_error!("The \"game-activity\" and \"native-activity\" features cannot be enabled at the same time");
-#[cfg(all(not(any(feature = "game-activity", feature = "native-activity")), not(doc)))]
-compile_error!("Either \"game-activity\" or \"native-activity\" must be enabled as features");
+#[cfg(all(feature = "mock-activity", any(feature = "game-activity", feature = "native-activity")))]
+compile_error!("The \"mock-activity\" feature cannot be enabled at the same time as \"game-activity\" or \"native-activity\"");
+#[cfg(all(not(any(feature = "game-activity", feature = "native-activity", feature = "mock-activity")), not(doc)))]
+compile_error!("Either \"game-activity\", \"native-activity\" or \"mock-activity\" must be enabled as features");
 
 
-#[cfg(any(feature="native-activity", doc))]
+#[cfg(any(feature="native-activity", all(doc, not(feature="mock-activity"))))]
 mod native_activity;
-#[cfg(any(feature="native-activity", doc))]
+#[cfg(any(feature="native-activity", all(doc, not(feature="mock-activity"))))]
 use native_activity as activity_impl;
 
 #[cfg(feature="game-activity")]
@@ -29,8 +39,74 @@ mod game_activity;
 #[cfg(feature="game-activity")]
 use game_activity as activity_impl;
 
+#[cfg(feature="mock-activity")]
+mod mock_activity;
+#[cfg(feature="mock-activity")]
+use mock_activity as activity_impl;
+#[cfg(feature="mock-activity")]
+pub use mock_activity::{MockActivity, MockEvent, MOCK_SDK_VERSION};
+
 pub use activity_impl::input;
 
+mod error;
+pub use error::PollError;
+
+mod entry;
+pub use entry::MainEntry;
+#[doc(hidden)]
+pub use entry::macro_support as __macro_support;
+#[cfg(feature = "macros")]
+pub use android_activity_macros::main;
+
+mod epoll;
+#[cfg(feature = "epoll")]
+pub use epoll::TriggerMode;
+
+mod filter;
+
+mod frame;
+
+mod ime;
+pub use ime::{
+    DateTimeVariation, HideSoftInputFlags, ImeAction, ImeFlags, ImeOptions, InputType, NumberFlags,
+    NumberVariation, ShowSoftInputFlags, TextFlags, TextInputState, TextSpan, TextVariation,
+};
+
+mod insets;
+pub use insets::InsetsType;
+
+mod lifecycle;
+pub use lifecycle::LifecycleState;
+
+pub mod logger;
+
+mod looper;
+pub use looper::{FdToken, TimerId, TimerKind};
+
+mod memory;
+pub use memory::MemoryTrimLevel;
+
+mod panic_hook;
+pub use panic_hook::PanicHook;
+
+mod proxy;
+pub use proxy::{EventProxy, UserEvent};
+
+mod recording;
+pub use recording::{Recording, RECORDING_VERSION};
+
+mod stdio;
+pub use stdio::StdioRedirect;
+
+mod stream;
+#[cfg(feature = "async")]
+pub use stream::{EventGuard, EventStream};
+
+mod util;
+
+mod window;
+pub use window::{WindowInfo, WindowManagerFlags};
+
 // Note: unlike in ndk-glue this has signed components (consistent
 // with Android's ARect) which generally allows for representing
 // rectangles with a negative/off-screen origin. Even though this
@@ -53,15 +129,18 @@ pub struct Rect {
 // become invalid, even it it gets 'terminated'.
 
 /// A reference to a `NativeWindow`, used for rendering
+#[derive(Debug)]
 pub struct NativeWindowRef {
     inner: NativeWindow
 }
 impl NativeWindowRef {
     pub fn new(native_window: &NativeWindow) -> Self {
+        #[cfg(not(feature = "mock-activity"))]
         unsafe { ndk_sys::ANativeWindow_acquire(native_window.ptr().as_ptr()); }
         Self { inner: native_window.clone() }
     }
 }
+#[cfg(not(feature = "mock-activity"))]
 impl Drop for NativeWindowRef {
     fn drop(&mut self) {
         unsafe { ndk_sys::ANativeWindow_release(self.inner.ptr().as_ptr()) }
@@ -90,20 +169,19 @@ pub enum MainEvent<'a> {
     /// Command from main thread: a new [`NativeWindow`] is ready for use.  Upon
     /// receiving this command, [`native_window()`] will return the new window
     #[non_exhaustive]
-    InitWindow { },
+    InitWindow { window: NativeWindowRef, info: WindowInfo },
 
     /// Command from main thread: the existing [`NativeWindow`] needs to be
     /// terminated.  Upon receiving this command, [`native_window()`] still
     /// returns the existing window; after returning from the [`AndroidApp::poll_events()`]
     /// callback then [`native_window()`] will return `None`.
     #[non_exhaustive]
-    TerminateWindow {},
+    TerminateWindow { window: NativeWindowRef },
 
-    // TODO: include the prev and new size in the event
     /// Command from main thread: the current [`NativeWindow`] has been resized.
     /// Please redraw with its new size.
     #[non_exhaustive]
-    WindowResized {},
+    WindowResized { window: NativeWindowRef, old: WindowInfo, new: WindowInfo },
 
     /// Command from main thread: the current [`NativeWindow`] needs to be redrawn.
     /// You should redraw the window before the [`AndroidApp::poll_events()`]
@@ -112,9 +190,10 @@ pub enum MainEvent<'a> {
     RedrawNeeded {},
 
     /// Command from main thread: the content area of the window has changed,
-    /// such as from the soft input window being shown or hidden.  You can
-    /// get the new content rect by calling [`AndroidApp::content_rect()`]
-    ContentRectChanged,
+    /// such as from the soft input window being shown or hidden.  The new
+    /// content rect is also returned by [`AndroidApp::content_rect()`]
+    #[non_exhaustive]
+    ContentRectChanged { old: Rect, new: Rect },
 
     /// Command from main thread: the app's activity window has gained
     /// input focus.
@@ -129,9 +208,21 @@ pub enum MainEvent<'a> {
     /// [`AndroidApp::config()`]
     ConfigChanged,
 
-    /// Command from main thread: the system is running low on memory.
-    /// Try to reduce your memory use.
-    LowMemory,
+    /// Command from main thread: the system has asked the app to trim its memory
+    /// use, to a degree that depends on the `level` of memory pressure.
+    ///
+    /// `NativeActivity` doesn't forward `onTrimMemory()`, so with the `native-activity`
+    /// backend an `Activity` subclass has to declare
+    /// `native void onTrimMemoryNative(int level)` and call it from its
+    /// `onTrimMemory()` override for every level to be reported. The native method
+    /// is registered when the `Activity` is created.
+    ///
+    /// Otherwise this is only sent when the whole system is low on memory (via
+    /// `onLowMemory()`), with the level of the last `onTrimMemory()` call, as
+    /// reported by `ActivityManager.getMyMemoryState()` (or
+    /// [`MemoryTrimLevel::Complete`] if there hasn't been one).
+    #[non_exhaustive]
+    TrimMemory { level: MemoryTrimLevel },
 
     /// Command from main thread: the app's activity has been started.
     Start,
@@ -158,9 +249,15 @@ pub enum MainEvent<'a> {
     /// and waiting for the app thread to clean up and exit before proceeding.
     Destroy,
 
-    /// Command from main thread: the app's insets have changed.
+    /// Command from main thread: the app's insets have changed.  The new insets
+    /// of each changed type are returned by [`AndroidApp::window_insets()`]
+    ///
+    /// `NativeActivity` isn't notified of changes to its insets, so with the
+    /// `native-activity` backend this is only sent when the insets are found to have
+    /// changed after the window, content rect, focus or configuration changes (see
+    /// [`AndroidApp::window_insets()`]).
     #[non_exhaustive]
-    InsetsChanged {},
+    InsetsChanged { changed: Vec<InsetsType> },
 }
 
 #[derive(Debug)]
@@ -170,12 +267,94 @@ pub enum PollEvent<'a> {
     Timeout,
     Main(MainEvent<'a>),
 
+    /// Events for a file descriptor registered via [`AndroidApp::register_fd()`]
+    #[non_exhaustive]
+    FdEvent { token: FdToken, fd: RawFd, events: FdEvent },
+
+    /// A timer added via [`AndroidApp::add_timer()`] has fired
+    #[non_exhaustive]
+    Timer { id: TimerId },
+
+    /// An event that was sent via an [`EventProxy`]
+    User(&'a UserEvent),
+
+    /// A new frame is starting, following a call to [`AndroidApp::request_frame_callback()`]
+    ///
+    /// `vsync_time_ns` is the time (in the `CLOCK_MONOTONIC` time base) of the vsync that
+    /// started the frame.
     #[non_exhaustive]
-    FdEvent { ident: i32, fd: RawFd, events: FdEvent, data: *mut std::ffi::c_void },
+    Frame { vsync_time_ns: i64 },
 
     Error
 }
 
+/// Whether the application handled an input event, as returned by the callback
+/// for [`AndroidApp::input_events()`]
+///
+/// An event that's left unhandled is passed back to the system, which may then
+/// handle it itself (such as for the volume keys or Back) or generate a fallback
+/// event for it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputStatus {
+    #[default]
+    Handled,
+    Unhandled,
+}
+
+/// An event that has been read from the looper but not yet dispatched
+///
+/// For [`RawPollEvent::Main`] commands the backend's `pre_exec_cmd()` must
+/// be called before the corresponding [`MainEvent`] is dispatched and
+/// `post_exec_cmd()` must be called afterwards, in order to synchronize with
+/// the Java main thread.
+#[derive(Debug)]
+pub(crate) enum RawPollEvent {
+    Wake,
+    Timeout,
+    Main(activity_impl::MainCmd),
+    FdEvent { ident: i32, fd: RawFd, events: FdEvent },
+    Timer(TimerId),
+    User(UserEvent),
+    Frame(i64),
+    Error(PollError),
+}
+
+impl RawPollEvent {
+    /// Maps to the public [`PollEvent`] type, which may borrow from the `AndroidAppInner`
+    /// or from the raw event itself
+    ///
+    /// Returns `Ok(None)` for internal commands that aren't forwarded to applications.
+    pub(crate) fn poll_event<'a>(&'a self, inner: &'a AndroidAppInner) -> Result<Option<PollEvent<'a>>, PollError> {
+        let event = match self {
+            RawPollEvent::Wake => PollEvent::Wake,
+            RawPollEvent::Timeout => PollEvent::Timeout,
+            RawPollEvent::Main(cmd) => match inner.main_event(cmd)? {
+                Some(event) => PollEvent::Main(event),
+                None => return Ok(None),
+            },
+            RawPollEvent::FdEvent { ident, fd, events } =>
+                PollEvent::FdEvent { token: FdToken::from_ident(*ident), fd: *fd, events: *events },
+            RawPollEvent::Timer(id) => PollEvent::Timer { id: *id },
+            RawPollEvent::User(event) => PollEvent::User(event),
+            RawPollEvent::Frame(vsync_time_ns) => PollEvent::Frame { vsync_time_ns: *vsync_time_ns },
+            RawPollEvent::Error(_) => PollEvent::Error,
+        };
+        Ok(Some(event))
+    }
+}
+
+/// Calls the backend's `post_exec_cmd()` when dropped, including while unwinding
+struct PostExecGuard<'a> {
+    inner: &'a AndroidAppInner,
+    cmd: &'a activity_impl::MainCmd,
+}
+
+impl Drop for PostExecGuard<'_> {
+    fn drop(&mut self) {
+        self.inner.post_exec_cmd(self.cmd);
+    }
+}
+
 use activity_impl::AndroidAppInner;
 
 pub use activity_impl::AndroidAppWaker;
@@ -226,12 +405,310 @@ impl AndroidApp {
     /// main thread. The [`MainEvent::SaveState`] event is also synchronized with the
     /// Java main thread.
     ///
+    /// With the `epoll` feature enabled, this polls an `epoll` instance that's managed by
+    /// this crate, which is nested in the thread's `ALooper`. In that case `timeout` has
+    /// nanosecond precision. The `ALooper` still runs any callbacks that are added to it
+    /// (e.g. by `AChoreographer`) and calling `ALooper_wake` still wakes up the event loop,
+    /// but any fds that are added directly to the `ALooper` without a callback (via
+    /// `ALooper_addFd`) won't be reported.
+    ///
     /// # Safety
     /// This API must only be called from the application's main thread
-    pub fn poll_events<F>(&self, timeout: Option<Duration>, callback: F)
+    pub fn poll_events<F>(&self, timeout: Option<Duration>, mut callback: F)
         where F: FnMut(PollEvent)
     {
-        self.inner.poll_events(timeout, callback);
+        if let Err(err) = self.try_poll_events(timeout, &mut callback) {
+            error!("Failed to poll events: {err}");
+            callback(PollEvent::Error);
+        }
+    }
+
+    /// A fallible variant of [`poll_events()`](Self::poll_events)
+    ///
+    /// Instead of logging errors and reporting them to the callback as a
+    /// [`PollEvent::Error`] this returns a [`PollError`] that describes what went wrong,
+    /// so that an application can decide whether to continue polling or shut down.
+    ///
+    /// Even if an error is returned for a command from the Java main thread, any
+    /// synchronization with the main thread is still completed so the application
+    /// can safely continue polling for events.
+    ///
+    /// # Safety
+    /// This API must only be called from the application's main thread
+    pub fn try_poll_events<F>(&self, timeout: Option<Duration>, mut callback: F) -> Result<(), PollError>
+        where F: FnMut(PollEvent)
+    {
+        trace!("poll_events");
+
+        // Events that were read by `block_on()` but never taken from an
+        // `EventStream` are dispatched first
+        #[cfg(feature = "async")]
+        let raw = stream::take_pending().or_else(|| self.poll_raw(timeout));
+        #[cfg(not(feature = "async"))]
+        let raw = self.poll_raw(timeout);
+
+        let raw = match raw {
+            Some(RawPollEvent::Error(err)) => return Err(err),
+            Some(raw) => raw,
+            None => return Ok(()),
+        };
+
+        // The guard makes sure `post_exec_cmd()` still runs if the callback panics,
+        // since the Java main thread may be blocked waiting for it
+        let _guard = match &raw {
+            RawPollEvent::Main(cmd) => {
+                self.pre_exec_cmd(cmd);
+                Some(PostExecGuard { inner: &self.inner, cmd })
+            }
+            _ => None,
+        };
+
+        raw.poll_event(&self.inner).map(|event| {
+            if let Some(event) = event {
+                trace!("Invoking callback for {:?}", event);
+                callback(event);
+            }
+        })
+    }
+
+    /// Runs the backend's `pre_exec_cmd()` and then updates the tracked lifecycle
+    /// state (and any active recording), before the corresponding [`MainEvent`] is dispatched
+    pub(crate) fn pre_exec_cmd(&self, cmd: &activity_impl::MainCmd) {
+        self.inner.pre_exec_cmd(cmd);
+        if let Ok(Some(event)) = self.inner.main_event(cmd) {
+            self.inner.lifecycle().lock().unwrap().update(&event);
+            recording::record_with(self, |recorder| recorder.record_main(&event, self));
+        }
+    }
+
+    /// Polls the backend's looper, taking care of delivering user events and frames
+    ///
+    /// A single (coalesced) wake up may have been sent for multiple user events and
+    /// frames, so while any are still queued the looper is only polled without
+    /// blocking. The looper is still polled first though, so that a thread that keeps
+    /// sending user events can't starve the looper's own events, such as the commands
+    /// that the Java main thread may be blocked waiting on.
+    pub(crate) fn poll_raw(&self, timeout: Option<Duration>) -> Option<RawPollEvent> {
+        let queued = self.has_woken_event();
+        let timeout = if queued { Some(Duration::ZERO) } else { timeout };
+        match self.inner.poll_raw(timeout) {
+            // The wake up for an event that was sent while we were polling
+            Some(RawPollEvent::Wake) => Some(self.take_woken_event().unwrap_or(RawPollEvent::Wake)),
+            Some(RawPollEvent::Timeout) | None if queued => self.take_woken_event(),
+            raw => raw,
+        }
+    }
+
+    fn has_woken_event(&self) -> bool {
+        !self.inner.user_events().is_empty() || self.inner.frames().is_pending()
+    }
+
+    fn take_woken_event(&self) -> Option<RawPollEvent> {
+        self.inner.user_events().pop().map(RawPollEvent::User)
+            .or_else(|| self.inner.frames().take().map(RawPollEvent::Frame))
+    }
+
+    /// Requests a [`PollEvent::Frame`] at the start of the next display frame
+    ///
+    /// This is backed by an `AChoreographer` frame callback, so that rendering can be
+    /// paced to the display's vsync. Each request results in a single `Frame` event (and
+    /// multiple requests before the next frame are coalesced), so an application that
+    /// renders continuously should request another callback for each frame.
+    ///
+    /// Requires Android 10 (API level 29), otherwise an
+    /// [`Unsupported`](std::io::ErrorKind::Unsupported) error is returned.
+    ///
+    /// # Safety
+    /// This API must only be called from the application's main thread, whose looper
+    /// runs the callback
+    pub fn request_frame_callback(&self) -> std::io::Result<()> {
+        self.inner.request_frame_callback()
+    }
+
+    /// Registers a file descriptor with the looper associated with this [AndroidApp]
+    ///
+    /// Once registered, [`poll_events()`](Self::poll_events) will report a
+    /// [`PollEvent::FdEvent`] tagged with the returned [`FdToken`] whenever
+    /// any of the requested `events` occur for the file descriptor.
+    ///
+    /// Only [`FdEvent::INPUT`] and [`FdEvent::OUTPUT`] are meaningful as
+    /// requested events; errors and hang ups are always reported.
+    ///
+    /// The caller retains ownership of the file descriptor and must unregister it
+    /// via [`unregister_fd()`](Self::unregister_fd) before closing it.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn register_fd(&self, fd: RawFd, events: FdEvent) -> std::io::Result<FdToken> {
+        self.inner.register_fd(fd, events)
+    }
+
+    /// Registers a file descriptor with the given [`TriggerMode`]
+    ///
+    /// This is the same as [`register_fd()`](Self::register_fd) except that the file
+    /// descriptor may be edge-triggered, in which case a [`PollEvent::FdEvent`] is only
+    /// reported each time the file descriptor becomes ready.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    #[cfg(feature = "epoll")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "epoll")))]
+    pub fn register_fd_with_trigger(&self, fd: RawFd, events: FdEvent, mode: TriggerMode) -> std::io::Result<FdToken> {
+        self.inner.register_fd_with_trigger(fd, events, mode)
+    }
+
+    /// Unregisters a file descriptor that was registered via [`register_fd()`](Self::register_fd)
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn unregister_fd(&self, token: FdToken) -> std::io::Result<()> {
+        self.inner.unregister_fd(token)
+    }
+
+    /// Adds a timer to the looper associated with this [AndroidApp]
+    ///
+    /// Each time the timer fires, [`poll_events()`](Self::poll_events) will report a
+    /// [`PollEvent::Timer`] tagged with the returned [`TimerId`].
+    ///
+    /// Timers are implemented with a `timerfd` that's registered with the looper,
+    /// so they are measured against `CLOCK_MONOTONIC` (the same clock as [`std::time::Instant`])
+    /// and, unlike the `timeout` passed to [`poll_events()`](Self::poll_events), they aren't
+    /// restarted each time the looper is woken by another event.
+    ///
+    /// The timer must be removed via [`remove_timer()`](Self::remove_timer) to release its
+    /// file descriptor, including a [`TimerKind::Deadline`] timer that has already fired.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn add_timer(&self, kind: TimerKind) -> std::io::Result<TimerId> {
+        self.inner.add_timer(kind)
+    }
+
+    /// Removes a timer that was added via [`add_timer()`](Self::add_timer)
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn remove_timer(&self, id: TimerId) -> std::io::Result<()> {
+        self.inner.remove_timer(id)
+    }
+
+    /// Shows the soft keyboard (IME), via `InputMethodManager.showSoftInput()`
+    ///
+    /// The request is forwarded to the Java main thread, so the keyboard won't
+    /// be shown by the time this returns.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn show_soft_input(&self, flags: ShowSoftInputFlags) {
+        self.inner.show_soft_input(flags);
+    }
+
+    /// Hides the soft keyboard (IME), via `InputMethodManager.hideSoftInputFromWindow()`
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn hide_soft_input(&self, flags: HideSoftInputFlags) {
+        self.inner.hide_soft_input(flags);
+    }
+
+    /// Returns the state of the text that's being edited via the soft keyboard (IME)
+    ///
+    /// With the `game-activity` backend, an `InputEvent::TextEvent` is
+    /// delivered via [`input_events()`](Self::input_events) each time the IME edits
+    /// the text. `NativeActivity` doesn't support text input, so with the
+    /// `native-activity` backend this always returns an empty state.
+    ///
+    /// # Safety
+    /// This API must only be called from the applications main thread
+    pub fn text_input_state(&self) -> TextInputState {
+        self.inner.text_input_state()
+    }
+
+    /// Replaces the text that's being edited via the soft keyboard (IME), along with
+    /// its selection and compose region
+    ///
+    /// Spans that don't fall on a character boundary are moved back to the start of
+    /// the character. This does nothing with the `native-activity` backend.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn set_text_input_state(&self, state: TextInputState) {
+        self.inner.set_text_input_state(state);
+    }
+
+    /// Configures the soft keyboard (IME) for the kind of text that's edited, such
+    /// as showing a numeric keyboard or a "Search" action key
+    ///
+    /// This takes effect the next time the IME starts a new input session, such as
+    /// when it's next shown. This does nothing with the `native-activity` backend.
+    ///
+    /// With the `game-activity` backend, pressing the action key is only delivered as
+    /// an `InputEvent::EditorAction`, via [`input_events()`](Self::input_events), if the
+    /// application's `GameActivity` subclass forwards editor actions, since
+    /// `GameActivity` itself doesn't. To do so, the subclass must declare
+    /// `native boolean onEditorActionNative(int action)`, which is registered when the
+    /// `Activity` is created, and call it from its `InputConnection`'s
+    /// `onEditorAction()`. Otherwise, the action key is seen as an `Enter` key event.
+    ///
+    /// # Safety
+    /// This API must only be called from the applications main thread
+    pub fn set_ime_editor_info(&self, input_type: InputType, options: ImeOptions) {
+        self.inner.set_ime_editor_info(input_type, options);
+    }
+
+    /// Changes the flags of the `Activity`'s window, via `Window.setFlags()`, by
+    /// adding the flags in `add` and removing those in `remove`
+    ///
+    /// The request is forwarded to the Java main thread, so the flags won't have
+    /// changed by the time this returns.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn set_window_flags(&self, add: WindowManagerFlags, remove: WindowManagerFlags) {
+        self.inner.set_window_flags(add, remove);
+    }
+
+    /// Asks for the `Activity` to be finished (closed), via `Activity.finish()`
+    ///
+    /// The request is forwarded to the Java main thread, after which the application
+    /// is taken through the usual [`MainEvent::Pause`], [`MainEvent::Stop`] and
+    /// [`MainEvent::Destroy`] events, so it should keep handling events until it
+    /// sees `Destroy` and then return from `android_main`.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn finish(&self) {
+        self.inner.finish();
+    }
+
+    /// Sets a filter that decides which key events are delivered via
+    /// [`input_events()`](Self::input_events), replacing any previous filter
+    ///
+    /// Any event that the filter returns `false` for is left for the system to
+    /// handle, such as for the volume keys or Back. By default the `game-activity`
+    /// backend leaves the volume, camera and zoom keys for the system, while the
+    /// `native-activity` backend delivers every key event.
+    ///
+    /// With the `game-activity` backend the filter is called on the Java main thread,
+    /// as each event arrives, so it mustn't block or call back into this `AndroidApp`.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn set_key_event_filter(&self, filter: impl Fn(&input::KeyEvent) -> bool + Send + Sync + 'static) {
+        self.inner.set_key_event_filter(Box::new(filter));
+    }
+
+    /// Sets a filter that decides which motion events are delivered via
+    /// [`input_events()`](Self::input_events), replacing any previous filter
+    ///
+    /// This works in the same way as [`set_key_event_filter()`](Self::set_key_event_filter).
+    /// By default the `game-activity` backend only delivers touchscreen events, while
+    /// the `native-activity` backend delivers every motion event.
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn set_motion_event_filter(&self, filter: impl Fn(&input::MotionEvent) -> bool + Send + Sync + 'static) {
+        self.inner.set_motion_event_filter(Box::new(filter));
     }
 
     /// Creates a means to wake up the main loop while it is blocked waiting for
@@ -246,6 +723,58 @@ impl AndroidApp {
         self.inner.create_waker()
     }
 
+    /// Creates a proxy for sending user events of type `T` to the main loop from any thread
+    ///
+    /// Events sent via an [`EventProxy`] are delivered by [`poll_events()`](Self::poll_events)
+    /// as a [`PollEvent::User`], in the order they were sent (including across proxies
+    /// for different types). The main loop is woken up via the same mechanism as
+    /// [`AndroidAppWaker::wake()`], which the proxy is built on.
+    ///
+    /// ```ignore
+    /// let proxy = app.create_proxy::<String>();
+    /// std::thread::spawn(move || proxy.send("Hello".to_string()));
+    ///
+    /// app.poll_events(None, |event| {
+    ///     if let PollEvent::User(event) = event {
+    ///         if let Some(msg) = event.take::<String>() {
+    ///             info!("Received: {msg}");
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// # Safety
+    /// This API can be used from any thread
+    pub fn create_proxy<T: Send + 'static>(&self) -> EventProxy<T> {
+        EventProxy::new(self.inner.user_events().clone(), self.inner.create_waker())
+    }
+
+    /// Returns the current lifecycle state of the application's `Activity`
+    ///
+    /// This is tracked from the [`MainEvent`]s that have been delivered, and while handling
+    /// a lifecycle event (such as [`MainEvent::Resume`]) it already reflects the new state.
+    ///
+    /// In debug builds a warning is logged if events arrive in an order that doesn't
+    /// follow the documented Android Activity lifecycle.
+    pub fn lifecycle_state(&self) -> LifecycleState {
+        self.inner.lifecycle().lock().unwrap().state()
+    }
+
+    /// Returns `true` between [`MainEvent::GainedFocus`] and [`MainEvent::LostFocus`] events
+    pub fn has_focus(&self) -> bool {
+        self.inner.lifecycle().lock().unwrap().has_focus()
+    }
+
+    /// Returns `true` between [`MainEvent::InitWindow`] and [`MainEvent::TerminateWindow`] events
+    ///
+    /// Like [`lifecycle_state()`](Self::lifecycle_state) this already reflects the new
+    /// state while handling either event, so it's `false` while handling `TerminateWindow`,
+    /// even though [`native_window()`](Self::native_window) can still be accessed until
+    /// the callback returns.
+    pub fn is_window_ready(&self) -> bool {
+        self.inner.lifecycle().lock().unwrap().is_window_ready()
+    }
+
     /// Returns a deep copy of this application's [`Configuration`]
     pub fn config(&self) -> Configuration {
         self.inner.config()
@@ -260,10 +789,33 @@ impl AndroidApp {
         self.inner.content_rect()
     }
 
+    /// Queries the insets of the given type, relative to each edge of the window
+    ///
+    /// These are the insets as of the last [`MainEvent::InsetsChanged`] event.
+    ///
+    /// With the `native-activity` backend the insets are queried from the
+    /// `Activity`'s root `WindowInsets`, on the Java main thread, whenever the
+    /// activity resumes, or its window, content rect, focus or configuration
+    /// changes. A `NativeActivity` subclass that's notified of other changes (e.g.
+    /// via a `View.OnApplyWindowInsetsListener`) can forward them by declaring and
+    /// calling `private native void onWindowInsetsChangedNative()`. The individual
+    /// types of insets require Android 11 (API level 30): before that only the
+    /// system bars, display cutout and (from API level 29) gesture and tappable
+    /// element insets are reported, and all insets are zero before API level 23.
+    ///
+    /// # Safety
+    /// This API must only be called from the applications main thread
+    pub fn window_insets(&self, ty: InsetsType) -> Rect {
+        self.inner.window_insets(ty)
+    }
+
     /// Queries the Asset Manager instance for the application.
     ///
     /// Use this to access binary assets bundled inside your application's .apk file.
     ///
+    /// With the `mock-activity` backend, assets are read from a host directory instead,
+    /// which is set via `MockActivity::set_asset_dir()`.
+    ///
     /// # Safety
     /// This API must only be called from the applications main thread
     pub fn asset_manager(&self) -> AssetManager {
@@ -278,15 +830,101 @@ impl AndroidApp {
         self.inner.disable_motion_axis(axis);
     }
 
-    pub fn input_events<'b, F>(&self, callback: F)
-        where F: FnMut(&input::InputEvent)
+    /// Calls `callback` for each of the input events that have been received since
+    /// this was last called
+    ///
+    /// The callback returns whether it handled each event, and an
+    /// [`InputStatus::Unhandled`] event is passed back to the system.
+    ///
+    /// With the `game-activity` backend, `GameActivity`'s `onKeyDown()` and
+    /// `onTouchEvent()` wait for the application to handle each event, up to 100ms,
+    /// before reporting whether it was handled. Events that aren't handled in time
+    /// (or while the application is behind on handling its input) are reported as
+    /// handled, so input should be handled promptly, e.g. once per frame.
+    ///
+    /// With the `native-activity` backend, new input events result in a single
+    /// [`PollEvent::Wake`] until they are read via this API.
+    ///
+    /// # Safety
+    /// This API must only be called from the applications main thread
+    pub fn input_events<'b, F>(&self, mut callback: F)
+        where F: FnMut(&input::InputEvent) -> InputStatus
     {
-        self.inner.input_events(callback);
+        self.inner.input_events(|event| {
+            recording::record_with(self, |recorder| recorder.record_input(event));
+            callback(event)
+        });
+    }
+
+    /// The total number of key and motion events that have been dropped, since the
+    /// application started, because they couldn't be buffered until the next call to
+    /// [`input_events()`](Self::input_events)
+    ///
+    /// With the `game-activity` backend, events are buffered by the glue layer, which
+    /// grows its buffers as needed but only up to a limit (1024 motion events and
+    /// 1024 key events, by default), in case the application isn't handling input.
+    /// The historical samples of motion events are buffered separately (up to 8192
+    /// samples) and if they don't fit, the event is still delivered but without its
+    /// historical samples, which isn't counted here (though a warning is logged).
+    /// With the `native-activity` backend, events stay queued by the system, so this
+    /// is always zero.
+    pub fn dropped_input_events(&self) -> u64 {
+        self.inner.dropped_input_events()
+    }
+
+    /// Starts recording the events that are delivered by [`poll_events()`](Self::poll_events)
+    /// and [`input_events()`](Self::input_events)
+    ///
+    /// The recording is written to a new file in a `recordings/` directory under
+    /// [`internal_data_path()`](Self::internal_data_path) and the path of the file is
+    /// returned. It starts with a snapshot of the current [`Configuration`], lifecycle
+    /// state and window, followed by each [`MainEvent`] and input event along with a
+    /// timestamp. Events from the application's own sources (such as timers, file
+    /// descriptors and user events) aren't recorded.
+    ///
+    /// A recording can be loaded via [`Recording::load()`] and, with the `mock-activity`
+    /// backend, replayed via `MockActivity::replay()` so that an application's event
+    /// handling can be reproduced on a development machine.
+    ///
+    /// Any recording that's already active is stopped first. If writing to the file fails
+    /// then an error is logged and recording stops.
+    ///
+    /// # Safety
+    /// This API must only be called from the application's main thread
+    pub fn start_recording(&self) -> std::io::Result<std::path::PathBuf> {
+        let dir = self.internal_data_path()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "No internal data path to write the recording to"))?
+            .join("recordings");
+        self.stop_recording()?;
+        let recorder = recording::Recorder::create(&dir, self)?;
+        let path = recorder.path().to_owned();
+        *self.inner.recorder().lock().unwrap() = Some(recorder);
+        Ok(path)
+    }
+
+    /// Stops the active recording (if any) and returns the path it was written to
+    ///
+    /// # Safety
+    /// This API must only be called from the application's main thread
+    pub fn stop_recording(&self) -> std::io::Result<Option<std::path::PathBuf>> {
+        let recorder = self.inner.recorder().lock().unwrap().take();
+        recorder.map(recording::Recorder::finish).transpose()
+    }
+
+    /// The user-visible SDK version of the framework
+    ///
+    /// Also referred to as [`Build.VERSION_CODES`](https://developer.android.com/reference/android/os/Build.VERSION_CODES)
+    ///
+    /// With the `mock-activity` backend this is always [`MOCK_SDK_VERSION`]
+    #[cfg(feature = "mock-activity")]
+    pub fn sdk_version() -> i32 {
+        mock_activity::MOCK_SDK_VERSION
     }
 
     /// The user-visible SDK version of the framework
     ///
     /// Also referred to as [`Build.VERSION_CODES`](https://developer.android.com/reference/android/os/Build.VERSION_CODES)
+    #[cfg(not(feature = "mock-activity"))]
     pub fn sdk_version() -> i32 {
         let mut prop = android_properties::getprop("ro.build.version.sdk");
         if let Some(val) = prop.value() {
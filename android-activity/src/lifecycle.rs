@@ -0,0 +1,112 @@
+use log::warn;
+
+use crate::MainEvent;
+
+/// The state of the application's `Activity`, as tracked from the [`MainEvent`]s that
+/// have been delivered
+///
+/// See the [Android Activity lifecycle](https://developer.android.com/guide/components/activities/activity-lifecycle)
+/// documentation for details.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LifecycleState {
+    /// The activity has been created but hasn't started yet
+    Created,
+
+    /// The activity is visible ([`MainEvent::Start`])
+    Started,
+
+    /// The activity is in the foreground and interactive ([`MainEvent::Resume`])
+    Resumed,
+
+    /// The activity is no longer in the foreground but may still be visible ([`MainEvent::Pause`])
+    Paused,
+
+    /// The activity is no longer visible ([`MainEvent::Stop`])
+    Stopped,
+
+    /// The activity is being destroyed and the application should exit `android_main`
+    /// ([`MainEvent::Destroy`])
+    Destroying,
+}
+
+#[derive(Debug)]
+pub(crate) struct LifecycleTracker {
+    state: LifecycleState,
+    focused: bool,
+    window_ready: bool,
+}
+
+impl Default for LifecycleTracker {
+    fn default() -> Self {
+        Self {
+            state: LifecycleState::Created,
+            focused: false,
+            window_ready: false,
+        }
+    }
+}
+
+impl LifecycleTracker {
+    pub(crate) fn state(&self) -> LifecycleState {
+        self.state
+    }
+
+    pub(crate) fn has_focus(&self) -> bool {
+        self.focused
+    }
+
+    pub(crate) fn is_window_ready(&self) -> bool {
+        self.window_ready
+    }
+
+    /// Updates the tracked state for an event that's about to be dispatched
+    ///
+    /// In debug builds this also warns about events that arrive in an order that
+    /// doesn't match the documented Android lifecycle, which would likely indicate
+    /// a bug in the glue layer (or in a mock test script). The state is updated
+    /// regardless.
+    pub(crate) fn update(&mut self, event: &MainEvent) {
+        use LifecycleState::*;
+
+        let (next, expected): (LifecycleState, &[LifecycleState]) = match event {
+            MainEvent::Start => (Started, &[Created, Stopped]),
+            MainEvent::Resume { .. } => (Resumed, &[Started, Paused]),
+            MainEvent::Pause => (Paused, &[Resumed]),
+            MainEvent::Stop => (Stopped, &[Started, Paused]),
+            MainEvent::Destroy => (Destroying, &[Created, Stopped]),
+            MainEvent::GainedFocus => {
+                self.check(!self.focused, "GainedFocus while already focused");
+                self.focused = true;
+                return;
+            }
+            MainEvent::LostFocus => {
+                self.check(self.focused, "LostFocus while not focused");
+                self.focused = false;
+                return;
+            }
+            MainEvent::InitWindow { .. } => {
+                self.check(!self.window_ready, "InitWindow while a window is already initialized");
+                self.window_ready = true;
+                return;
+            }
+            MainEvent::TerminateWindow { .. } => {
+                self.check(self.window_ready, "TerminateWindow without an initialized window");
+                self.window_ready = false;
+                return;
+            }
+            _ => return,
+        };
+
+        if cfg!(debug_assertions) && !expected.contains(&self.state) {
+            warn!("Unexpected lifecycle transition from {:?} to {next:?} (expected to be in one of {expected:?})", self.state);
+        }
+        self.state = next;
+    }
+
+    fn check(&self, ok: bool, what: &str) {
+        if cfg!(debug_assertions) && !ok {
+            warn!("Unexpected lifecycle event: {what} (state = {:?})", self.state);
+        }
+    }
+}
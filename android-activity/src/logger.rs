@@ -0,0 +1,267 @@
+//! A [`log`] backend that writes to logcat
+//!
+//! ```ignore
+//! use android_activity::logger::{self, Config};
+//!
+//! #[android_activity::main(logger = false)]
+//! fn android_main(app: AndroidApp) {
+//!     logger::init(Config::default().with_filter("info,my_app::render=trace".parse().unwrap()))
+//!         .expect("Another logger was already installed");
+//!     ...
+//! }
+//! ```
+//!
+//! Each message is tagged with the crate or module that it was logged from (see
+//! [`Tag`]) and messages that are longer than the maximum length of a logcat message
+//! are split into multiple messages.
+//!
+//! Which messages are logged is controlled by a [`Filter`] that uses the same syntax as
+//! `env_logger`'s `RUST_LOG` variable and which can be changed at runtime via [`set_filter()`].
+//!
+//! With the `mock-activity` backend messages are written to stderr instead.
+
+use std::ffi::CString;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use crate::util::{android_log, split_log_message, MAX_LOG_MESSAGE_LEN};
+
+/// Determines the logcat tag for each message
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tag {
+    /// The name of the crate that the message was logged from, with the module path
+    /// prefixed to messages from submodules (e.g. `my_app` and `my_app::render: message`)
+    Crate,
+
+    /// The full module path that the message was logged from
+    Module,
+
+    /// The same tag for all messages, with the module path prefixed to the message
+    Fixed(String),
+}
+
+/// An error from parsing a [`Filter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFilterError {
+    directive: String,
+}
+
+impl fmt::Display for ParseFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid log filter directive {:?}", self.directive)
+    }
+}
+
+impl std::error::Error for ParseFilterError {}
+
+/// Determines which messages are logged, based on their level and module path
+///
+/// A filter is parsed from a comma-separated list of directives, like `env_logger`'s
+/// `RUST_LOG` variable:
+///
+/// - `level` sets the maximum level for all modules (e.g. `warn`)
+/// - `path=level` sets the maximum level for a module and its children (e.g. `wgpu_core=error`)
+/// - `path` enables all levels for a module and its children
+///
+/// Levels are one of `off`, `error`, `warn`, `info`, `debug` or `trace`. The most specific
+/// matching path takes precedence, and messages from modules that don't match any path
+/// are filtered by the default level, which is `error` unless specified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    default: LevelFilter,
+    // Sorted so the longest (most specific) paths come first
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// A filter that logs messages up to the given level for all modules
+    pub const fn new(level: LevelFilter) -> Self {
+        Self {
+            default: level,
+            directives: Vec::new(),
+        }
+    }
+
+    /// Sets the maximum level for messages from the module at `path` and its children
+    pub fn with_module(mut self, path: &str, level: LevelFilter) -> Self {
+        self.directives.retain(|(existing, _)| existing != path);
+        self.directives.push((path.to_string(), level));
+        self.directives.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+        self
+    }
+
+    /// Returns the maximum level of any message that could be logged
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives.iter().map(|(_, level)| *level).fold(self.default, |a, b| a.max(b))
+    }
+
+    /// Returns `true` if a message from `target` with the given level should be logged
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let max = self.directives.iter()
+            .find(|(path, _)| {
+                target.strip_prefix(path.as_str())
+                    .map(|rest| rest.is_empty() || rest.starts_with("::"))
+                    .unwrap_or(false)
+            })
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default);
+        level <= max
+    }
+}
+
+impl Default for Filter {
+    /// Logs messages up to [`Level::Info`] for all modules
+    fn default() -> Self {
+        Self::new(LevelFilter::Info)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ParseFilterError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut filter = Filter::new(LevelFilter::Error);
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let invalid = || ParseFilterError { directive: directive.to_string() };
+            match directive.split_once('=') {
+                Some((path, level)) => {
+                    let level = level.trim().parse().map_err(|_| invalid())?;
+                    let path = path.trim();
+                    if path.is_empty() {
+                        return Err(invalid());
+                    }
+                    filter = filter.with_module(path, level);
+                }
+                None => match directive.parse() {
+                    Ok(level) => filter.default = level,
+                    Err(_) => filter = filter.with_module(directive, LevelFilter::Trace),
+                },
+            }
+        }
+        Ok(filter)
+    }
+}
+
+/// The configuration for the logger, passed to [`init()`]
+#[derive(Debug, Clone)]
+pub struct Config {
+    filter: Filter,
+    tag: Tag,
+}
+
+impl Default for Config {
+    /// Logs messages up to [`Level::Info`], tagged with their crate name
+    fn default() -> Self {
+        Self {
+            filter: Filter::default(),
+            tag: Tag::Crate,
+        }
+    }
+}
+
+impl Config {
+    /// Sets the [`Filter`] that determines which messages are logged
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Logs messages up to `level` for all modules
+    ///
+    /// This is a shorthand for `with_filter(Filter::new(level))`
+    pub fn with_max_level(self, level: LevelFilter) -> Self {
+        self.with_filter(Filter::new(level))
+    }
+
+    /// Sets how the logcat tag for each message is chosen
+    pub fn with_tag(mut self, tag: Tag) -> Self {
+        self.tag = tag;
+        self
+    }
+}
+
+struct State {
+    installed: bool,
+    filter: Filter,
+    tag: Tag,
+}
+
+struct Logger {
+    state: RwLock<State>,
+}
+
+static LOGGER: Logger = Logger {
+    state: RwLock::new(State {
+        installed: false,
+        filter: Filter::new(LevelFilter::Info),
+        tag: Tag::Crate,
+    }),
+};
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.state.read().unwrap().filter.enabled(metadata.target(), metadata.level())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        // Formatted before taking the lock, in case formatting the arguments logs anything
+        let args = record.args().to_string();
+        let target = record.target();
+        let (tag, msg) = match &self.state.read().unwrap().tag {
+            Tag::Crate => match target.split_once("::") {
+                Some((crate_name, _)) => (crate_name.to_string(), format!("{target}: {args}")),
+                None => (target.to_string(), args),
+            },
+            Tag::Module => (target.to_string(), args),
+            Tag::Fixed(tag) => (tag.clone(), format!("{target}: {args}")),
+        };
+
+        // Logcat tags and messages are C strings, so any NUL bytes are dropped
+        let tag = CString::new(tag.replace('\0', "")).unwrap();
+        let msg = msg.replace('\0', "");
+        for chunk in split_log_message(&msg, MAX_LOG_MESSAGE_LEN.saturating_sub(tag.as_bytes().len())) {
+            android_log(record.level(), &tag, &CString::new(chunk).unwrap());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the logcat logger as the global [`log`] logger, with the given configuration
+///
+/// Since `android_main` may be called more than once in the same process (each time
+/// the `Activity` is re-created), this can safely be called again, in which case it
+/// just replaces the configuration of the logger that's already installed.
+///
+/// Returns an error if a different logger has already been installed.
+pub fn init(config: Config) -> Result<(), SetLoggerError> {
+    let mut state = LOGGER.state.write().unwrap();
+    if !state.installed {
+        log::set_logger(&LOGGER)?;
+        state.installed = true;
+    }
+    log::set_max_level(config.filter.max_level());
+    state.filter = config.filter;
+    state.tag = config.tag;
+    Ok(())
+}
+
+/// Replaces the [`Filter`] that determines which messages are logged
+///
+/// This can be called at any time, from any thread, e.g. to temporarily enable more
+/// verbose logging for a particular module while debugging. If the logger hasn't been
+/// installed yet the filter will apply once it is.
+pub fn set_filter(filter: Filter) {
+    let mut state = LOGGER.state.write().unwrap();
+    if state.installed {
+        log::set_max_level(filter.max_level());
+    }
+    state.filter = filter;
+}
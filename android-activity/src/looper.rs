@@ -0,0 +1,215 @@
+//! Bookkeeping for file descriptors and timers that are registered with the
+//! application's looper, shared by all backends.
+
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::prelude::RawFd;
+use std::time::{Duration, Instant};
+
+use log::trace;
+
+use ndk::looper::FdEvent;
+
+use crate::RawPollEvent;
+
+/// Identifies a file descriptor that was registered via [`AndroidApp::register_fd()`](crate::AndroidApp::register_fd)
+///
+/// Events for the file descriptor are delivered as a
+/// [`PollEvent::FdEvent`](crate::PollEvent::FdEvent) that's tagged with this token.
+///
+/// Internally each token corresponds to a unique looper identifier that's
+/// allocated after the identifiers used by the native app glue
+/// (`LOOPER_ID_MAIN` and `LOOPER_ID_INPUT`). If a file descriptor is added
+/// directly via `ALooper_addFd` then its events will also be tagged with an
+/// `FdToken` whose [`ident()`](Self::ident) is the identifier that was given.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FdToken(i32);
+
+impl FdToken {
+    pub(crate) fn from_ident(ident: i32) -> Self {
+        Self(ident)
+    }
+
+    /// The looper identifier associated with this token
+    pub fn ident(&self) -> i32 {
+        self.0
+    }
+}
+
+/// Identifies a timer that was added via [`AndroidApp::add_timer()`](crate::AndroidApp::add_timer)
+///
+/// Expirations of the timer are delivered as a [`PollEvent::Timer`](crate::PollEvent::Timer)
+/// that's tagged with this id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TimerId(i32);
+
+impl TimerId {
+    #[cfg_attr(feature = "mock-activity", allow(dead_code))]
+    pub(crate) fn ident(&self) -> i32 {
+        self.0
+    }
+}
+
+/// Determines when a timer that's added via [`AndroidApp::add_timer()`](crate::AndroidApp::add_timer) fires
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimerKind {
+    /// Fire once, at the given point in time
+    ///
+    /// A deadline that has already passed fires immediately. The timer stays
+    /// registered after it fires, until it's removed via
+    /// [`AndroidApp::remove_timer()`](crate::AndroidApp::remove_timer).
+    Deadline(Instant),
+
+    /// Fire repeatedly with the given period, starting one period from now
+    ///
+    /// If the application doesn't poll for events often enough then multiple
+    /// expirations are coalesced into a single event.
+    Interval(Duration),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Source {
+    Fd,
+    Timer,
+}
+
+#[derive(Debug)]
+struct Registration {
+    fd: RawFd,
+    source: Source,
+}
+
+/// Allocates looper identifiers for registered file descriptors and timers
+///
+/// Identifiers are never recycled after a file descriptor is unregistered, so
+/// that a stale event that was already queued by the looper can't be mistaken for
+/// an event from a newer registration.
+#[derive(Debug)]
+pub(crate) struct FdRegistry {
+    first_ident: i32,
+    next_ident: i32,
+    fds: HashMap<i32, Registration>,
+}
+
+impl FdRegistry {
+    /// Creates a registry that will allocate identifiers starting from `first_ident`
+    pub(crate) fn new(first_ident: i32) -> Self {
+        Self {
+            first_ident,
+            next_ident: first_ident,
+            fds: HashMap::new(),
+        }
+    }
+
+    fn allocate_ident(&mut self, fd: RawFd, source: Source) -> io::Result<i32> {
+        if self.fds.values().any(|registered| registered.fd == fd) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("File descriptor {fd} is already registered")));
+        }
+        let ident = self.next_ident;
+        self.next_ident = self.next_ident.checked_add(1)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Exhausted looper identifiers"))?;
+        self.fds.insert(ident, Registration { fd, source });
+        Ok(ident)
+    }
+
+    fn release_ident(&mut self, ident: i32, source: Source) -> io::Result<RawFd> {
+        match self.fds.get(&ident) {
+            Some(registered) if registered.source == source => Ok(self.fds.remove(&ident).unwrap().fd),
+            _ => Err(io::Error::new(io::ErrorKind::NotFound, format!("Looper identifier {ident} is not registered"))),
+        }
+    }
+
+    /// Allocates a new token for `fd`
+    ///
+    /// A file descriptor can only be registered once, since the looper would otherwise
+    /// silently replace the previous registration.
+    pub(crate) fn allocate(&mut self, fd: RawFd) -> io::Result<FdToken> {
+        self.allocate_ident(fd, Source::Fd).map(FdToken)
+    }
+
+    /// Releases the identifier for `token`, returning the file descriptor that was registered
+    pub(crate) fn release(&mut self, token: FdToken) -> io::Result<RawFd> {
+        self.release_ident(token.0, Source::Fd)
+    }
+
+    /// Allocates a new id for a timer that's backed by `timerfd`
+    pub(crate) fn allocate_timer(&mut self, timerfd: RawFd) -> io::Result<TimerId> {
+        self.allocate_ident(timerfd, Source::Timer).map(TimerId)
+    }
+
+    /// Releases the identifier for a timer, returning its timerfd
+    pub(crate) fn release_timer(&mut self, id: TimerId) -> io::Result<RawFd> {
+        self.release_ident(id.0, Source::Timer)
+    }
+
+    /// Maps an event that was reported by the looper for `ident` into a [`RawPollEvent`]
+    ///
+    /// Timer expirations are acknowledged by reading the timerfd here, otherwise the
+    /// looper would keep reporting the same expiration. Returns `None` for stale events
+    /// that were queued by the looper before the corresponding fd was unregistered.
+    pub(crate) fn raw_event(&self, ident: i32, fd: RawFd, events: FdEvent) -> Option<RawPollEvent> {
+        match self.fds.get(&ident) {
+            Some(registered) if registered.source == Source::Timer => {
+                read_timerfd(registered.fd);
+                Some(RawPollEvent::Timer(TimerId(ident)))
+            }
+            None if ident >= self.first_ident && ident < self.next_ident => {
+                trace!("Ignoring stale looper event for ident = {ident}, fd = {fd}");
+                None
+            }
+            _ => Some(RawPollEvent::FdEvent { ident, fd, events }),
+        }
+    }
+
+}
+
+pub(crate) fn timespec(duration: Duration) -> libc::timespec {
+    libc::timespec {
+        tv_sec: duration.as_secs() as libc::time_t,
+        tv_nsec: duration.subsec_nanos() as libc::c_long,
+    }
+}
+
+/// Creates a non-blocking `CLOCK_MONOTONIC` timerfd that's armed according to `kind`
+pub(crate) fn create_timerfd(kind: TimerKind) -> io::Result<RawFd> {
+    let (value, interval) = match kind {
+        TimerKind::Deadline(deadline) => {
+            // A zero it_value would disarm the timer instead of firing immediately
+            let value = deadline.saturating_duration_since(Instant::now()).max(Duration::from_nanos(1));
+            (value, Duration::ZERO)
+        }
+        TimerKind::Interval(period) => {
+            if period.is_zero() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Timer interval must be non-zero"));
+            }
+            (period, period)
+        }
+    };
+
+    unsafe {
+        let fd = libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK | libc::TFD_CLOEXEC);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let spec = libc::itimerspec {
+            it_interval: timespec(interval),
+            it_value: timespec(value),
+        };
+        if libc::timerfd_settime(fd, 0, &spec, std::ptr::null_mut()) != 0 {
+            let err = io::Error::last_os_error();
+            libc::close(fd);
+            return Err(err);
+        }
+        Ok(fd)
+    }
+}
+
+fn read_timerfd(fd: RawFd) {
+    let mut expirations: u64 = 0;
+    // The timerfd is non-blocking, so this just fails with EAGAIN if there's
+    // no expiration to read
+    let ret = unsafe { libc::read(fd, (&mut expirations as *mut u64).cast(), std::mem::size_of::<u64>()) };
+    if ret > 0 {
+        trace!("Timer fd = {fd} expired {expirations} time(s)");
+    }
+}
@@ -0,0 +1,62 @@
+//! Memory pressure levels, as reported by `ComponentCallbacks2.onTrimMemory()`
+
+/// How much the application should trim its memory use, corresponding to the
+/// [`ComponentCallbacks2.TRIM_MEMORY_*`] levels
+///
+/// Levels are ordered by their value, as Android compares them, so an application
+/// can check for a level "at least" as severe as another, such as
+/// `level >= MemoryTrimLevel::Background`.
+///
+/// [`ComponentCallbacks2.TRIM_MEMORY_*`]: https://developer.android.com/reference/android/content/ComponentCallbacks2
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(i32)]
+#[non_exhaustive]
+pub enum MemoryTrimLevel {
+    /// The application is running, but the device is beginning to run low on memory
+    RunningModerate = 5,
+
+    /// The application is running, but the device is running much lower on memory
+    /// and unused resources should be released
+    RunningLow = 10,
+
+    /// The application is running, but the device is so low on memory that
+    /// background processes are being killed
+    RunningCritical = 15,
+
+    /// The application's UI is no longer visible, so large UI resources can be released
+    UiHidden = 20,
+
+    /// The application is in the background and near the start of the list of
+    /// processes to be killed
+    Background = 40,
+
+    /// The application is in the background and around the middle of the list of
+    /// processes to be killed
+    Moderate = 60,
+
+    /// The application is in the background and will be one of the first processes
+    /// to be killed if memory isn't freed
+    Complete = 80,
+}
+
+impl MemoryTrimLevel {
+    /// All of the levels, ordered by their `i32` value
+    const ALL: [MemoryTrimLevel; 7] = [
+        MemoryTrimLevel::RunningModerate,
+        MemoryTrimLevel::RunningLow,
+        MemoryTrimLevel::RunningCritical,
+        MemoryTrimLevel::UiHidden,
+        MemoryTrimLevel::Background,
+        MemoryTrimLevel::Moderate,
+        MemoryTrimLevel::Complete,
+    ];
+
+    /// Converts a `TRIM_MEMORY_*` value, rounding any value that isn't a known
+    /// level down to the nearest known level
+    pub(crate) fn from_raw(level: i32) -> Self {
+        Self::ALL.iter().rev()
+            .find(|known| **known as i32 <= level)
+            .copied()
+            .unwrap_or(MemoryTrimLevel::RunningModerate)
+    }
+}
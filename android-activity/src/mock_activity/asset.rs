@@ -0,0 +1,119 @@
+//! Host-side stand ins for the `ndk::asset` types, backed by a directory that's
+//! set via [`MockActivity::set_asset_dir()`](super::MockActivity::set_asset_dir)
+
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+
+/// A host-side stand in for an `ndk::asset::AssetManager`
+///
+/// Assets are read from the directory that was set via
+/// [`MockActivity::set_asset_dir()`](super::MockActivity::set_asset_dir), and
+/// nothing can be opened if no directory has been set.
+#[derive(Clone, Debug)]
+pub struct AssetManager {
+    root: Option<PathBuf>,
+}
+
+impl AssetManager {
+    pub(crate) fn new(root: Option<PathBuf>) -> Self {
+        Self { root }
+    }
+
+    /// Maps an asset name to a path under the asset directory
+    ///
+    /// As with an `AAssetManager`, names are relative and can't refer to a parent
+    /// directory.
+    fn path(&self, filename: &CStr) -> Option<PathBuf> {
+        let name = Path::new(std::ffi::OsStr::from_bytes(filename.to_bytes()));
+        if !name.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+            return None;
+        }
+        Some(self.root.as_ref()?.join(name))
+    }
+
+    /// Opens the asset, reading all of its data up front. Returns `None` if opening
+    /// the asset fails.
+    pub fn open(&self, filename: &CStr) -> Option<Asset> {
+        let data = fs::read(self.path(filename)?).ok()?;
+        Some(Asset { data: io::Cursor::new(data) })
+    }
+
+    /// Opens an asset directory. Returns `None` if opening the directory fails.
+    pub fn open_dir(&self, filename: &CStr) -> Option<AssetDir> {
+        // Like `AAssetDir`, only the files in the directory are listed
+        let mut names = fs::read_dir(self.path(filename)?).ok()?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| matches!(entry.file_type(), Ok(ty) if ty.is_file()))
+            .filter_map(|entry| CString::new(entry.file_name().as_bytes()).ok())
+            .collect::<Vec<_>>();
+        names.sort();
+        Some(AssetDir { names, pos: 0 })
+    }
+}
+
+/// A host-side stand in for an `ndk::asset::AssetDir`
+#[derive(Debug)]
+pub struct AssetDir {
+    names: Vec<CString>,
+    pos: usize,
+}
+
+impl AssetDir {
+    /// Gets the next filename, if any, and processes it
+    pub fn with_next<T>(&mut self, f: impl for<'a> FnOnce(&'a CStr) -> T) -> Option<T> {
+        let name = self.names.get(self.pos)?;
+        self.pos += 1;
+        Some(f(name))
+    }
+
+    /// Resets the iteration state
+    pub fn rewind(&mut self) {
+        self.pos = 0;
+    }
+}
+
+impl Iterator for AssetDir {
+    type Item = CString;
+
+    fn next(&mut self) -> Option<CString> {
+        self.with_next(|cstr| cstr.to_owned())
+    }
+}
+
+/// A host-side stand in for an `ndk::asset::Asset`
+#[derive(Debug)]
+pub struct Asset {
+    data: io::Cursor<Vec<u8>>,
+}
+
+impl Asset {
+    /// Returns the total length of the asset, in bytes
+    pub fn get_length(&self) -> usize {
+        self.data.get_ref().len()
+    }
+
+    /// Returns the remaining length of the asset, in bytes
+    pub fn get_remaining_length(&self) -> usize {
+        self.get_length().saturating_sub(self.data.position() as usize)
+    }
+
+    /// Returns all of the asset's data
+    pub fn get_buffer(&mut self) -> io::Result<&[u8]> {
+        Ok(self.data.get_ref())
+    }
+}
+
+impl io::Read for Asset {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+impl io::Seek for Asset {
+    fn seek(&mut self, seek: io::SeekFrom) -> io::Result<u64> {
+        self.data.seek(seek)
+    }
+}
@@ -0,0 +1,834 @@
+// This module provides owned, host-side equivalents of the `ndk::event` types
+// so that input can be scripted by tests. The API intentionally mirrors the
+// `ndk::event` and `game_activity::input` types so that application code
+// that handles input doesn't need to be aware of which backend it's built
+// against.
+//
+// The enum and flag types are re-exported from the `ndk` since they are
+// plain data and don't depend on any Android system library.
+
+pub use ndk::event::{
+    Source, MetaState,
+    MotionAction, Axis, ButtonState, EdgeFlags, MotionEventFlags,
+    KeyAction, Keycode, KeyEventFlags,
+};
+
+/// An enum representing the types of input event that can be
+/// scripted via [`MockActivity::send_input()`](super::MockActivity::send_input)
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum InputEvent {
+    MotionEvent(MotionEvent),
+    KeyEvent(KeyEvent),
+    /// The soft keyboard (IME) has edited the text, giving its new state, which is
+    /// also returned by [`AndroidApp::text_input_state()`](crate::AndroidApp::text_input_state)
+    /// once the event has been delivered
+    TextEvent(crate::TextInputState),
+    /// The soft keyboard's (IME's) action key has been pressed
+    EditorAction(crate::ImeAction),
+}
+
+/// The state of a single pointer, used to build a [`MotionEvent`]
+///
+/// Any axis that's not explicitly given a value will read as `0.0`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockPointer {
+    pub id: i32,
+    pub axes: Vec<(Axis, f32)>,
+}
+
+impl MockPointer {
+    /// Creates a pointer with the given id and [`Axis::X`] / [`Axis::Y`] values
+    pub fn new(id: i32, x: f32, y: f32) -> Self {
+        Self {
+            id,
+            axes: vec![(Axis::X, x), (Axis::Y, y)],
+        }
+    }
+
+    /// Sets the value of an axis for this pointer
+    pub fn with_axis(mut self, axis: Axis, value: f32) -> Self {
+        if let Some(existing) = self.axes.iter_mut().find(|(a, _)| *a == axis) {
+            existing.1 = value;
+        } else {
+            self.axes.push((axis, value));
+        }
+        self
+    }
+
+    fn axis_value(&self, axis: Axis) -> f32 {
+        self.axes
+            .iter()
+            .find(|(a, _)| *a == axis)
+            .map(|(_, value)| *value)
+            .unwrap_or(0.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct HistorySample {
+    event_time: i64,
+    pointers: Vec<MockPointer>,
+}
+
+/// A motion event
+///
+/// For general discussion of motion events in Android, see [the relevant
+/// javadoc](https://developer.android.com/reference/android/view/MotionEvent).
+#[derive(Clone, Debug)]
+pub struct MotionEvent {
+    source: Source,
+    device_id: i32,
+    action: MotionAction,
+    pointer_index: usize,
+    pointers: Vec<MockPointer>,
+    history: Vec<HistorySample>,
+    meta_state: MetaState,
+    button_state: ButtonState,
+    down_time: i64,
+    event_time: i64,
+    edge_flags: EdgeFlags,
+    flags: MotionEventFlags,
+    x_offset: f32,
+    y_offset: f32,
+    x_precision: f32,
+    y_precision: f32,
+}
+
+impl MotionEvent {
+    /// Creates a new motion event with the given action, source and pointers
+    ///
+    /// All other state (times, meta state, flags etc) is zeroed and can be
+    /// set via the `with_` methods.
+    pub fn new(action: MotionAction, source: Source, pointers: Vec<MockPointer>) -> Self {
+        Self {
+            source,
+            device_id: 0,
+            action,
+            pointer_index: 0,
+            pointers,
+            history: vec![],
+            meta_state: MetaState(0),
+            button_state: ButtonState(0),
+            down_time: 0,
+            event_time: 0,
+            edge_flags: EdgeFlags(0),
+            flags: MotionEventFlags(0),
+            x_offset: 0.0,
+            y_offset: 0.0,
+            x_precision: 1.0,
+            y_precision: 1.0,
+        }
+    }
+
+    /// Sets the pointer index associated with an `Up` / `Down` event
+    pub fn with_pointer_index(mut self, index: usize) -> Self {
+        self.pointer_index = index;
+        self
+    }
+
+    /// Sets the device id associated with the event
+    pub fn with_device_id(mut self, device_id: i32) -> Self {
+        self.device_id = device_id;
+        self
+    }
+
+    /// Sets the down and event times for the event, in nanoseconds
+    pub fn with_times(mut self, down_time: i64, event_time: i64) -> Self {
+        self.down_time = down_time;
+        self.event_time = event_time;
+        self
+    }
+
+    /// Sets the meta and button state for the event
+    pub fn with_state(mut self, meta_state: MetaState, button_state: ButtonState) -> Self {
+        self.meta_state = meta_state;
+        self.button_state = button_state;
+        self
+    }
+
+    /// Sets the edge flags and general flags for the event
+    pub fn with_flags(mut self, edge_flags: EdgeFlags, flags: MotionEventFlags) -> Self {
+        self.edge_flags = edge_flags;
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the raw offset and precision of the event coordinates
+    pub fn with_precision(mut self, x_offset: f32, y_offset: f32, x_precision: f32, y_precision: f32) -> Self {
+        self.x_offset = x_offset;
+        self.y_offset = y_offset;
+        self.x_precision = x_precision;
+        self.y_precision = y_precision;
+        self
+    }
+
+    /// Appends a historical sample to the event
+    ///
+    /// Samples should be added from oldest to newest and must have the same number
+    /// of pointers as the event itself.
+    pub fn with_history(mut self, event_time: i64, pointers: Vec<MockPointer>) -> Self {
+        assert_eq!(pointers.len(), self.pointers.len(), "Historical samples must have the same number of pointers as the event");
+        self.history.push(HistorySample { event_time, pointers });
+        self
+    }
+
+    /// Get the source of the event.
+    ///
+    #[inline]
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Get the device id associated with the event.
+    ///
+    #[inline]
+    pub fn device_id(&self) -> i32 {
+        self.device_id
+    }
+
+    /// Returns the motion action associated with the event.
+    ///
+    /// See [the MotionEvent docs](https://developer.android.com/reference/android/view/MotionEvent#getActionMasked())
+    #[inline]
+    pub fn action(&self) -> MotionAction {
+        self.action
+    }
+
+    /// Returns the pointer index of an `Up` or `Down` event.
+    ///
+    /// Pointer indices can change per motion event.  For an identifier that stays the same, see
+    /// [`Pointer::pointer_id()`].
+    ///
+    /// This only has a meaning when the [action](Self::action) is one of [`Up`](MotionAction::Up),
+    /// [`Down`](MotionAction::Down), [`PointerUp`](MotionAction::PointerUp),
+    /// or [`PointerDown`](MotionAction::PointerDown).
+    #[inline]
+    pub fn pointer_index(&self) -> usize {
+        self.pointer_index
+    }
+
+    /// Returns the pointer id associated with the given pointer index.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getpointerid)
+    #[inline]
+    pub fn pointer_id_for(&self, pointer_index: usize) -> i32 {
+        self.pointers[pointer_index].id
+    }
+
+    /// Returns the number of pointers in this event
+    ///
+    /// See [the MotionEvent docs](https://developer.android.com/reference/android/view/MotionEvent#getPointerCount())
+    #[inline]
+    pub fn pointer_count(&self) -> usize {
+        self.pointers.len()
+    }
+
+    /// An iterator over the pointers in this motion event
+    #[inline]
+    pub fn pointers(&self) -> PointersIter<'_> {
+        PointersIter {
+            event: self,
+            next_index: 0,
+            count: self.pointer_count(),
+        }
+    }
+
+    /// The pointer at a given pointer index. Panics if the pointer index is out of bounds.
+    ///
+    /// If you need to loop over all the pointers, prefer the [`pointers()`](Self::pointers) method.
+    #[inline]
+    pub fn pointer_at_index(&self, index: usize) -> Pointer<'_> {
+        if index >= self.pointer_count() {
+            panic!("Pointer index {} is out of bounds", index);
+        }
+        Pointer {
+            event: self,
+            index,
+        }
+    }
+
+    /// Returns the size of the history contained in this event.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_gethistorysize)
+    #[inline]
+    pub fn history_size(&self) -> usize {
+        self.history.len()
+    }
+
+    /// An iterator over the historical events contained in this event.
+    #[inline]
+    pub fn history(&self) -> HistoricalMotionEventsIter<'_> {
+        HistoricalMotionEventsIter {
+            event: self,
+            next_history_index: 0,
+            history_size: self.history_size(),
+        }
+    }
+
+    /// Returns the state of any modifier keys that were pressed during the event.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getmetastate)
+    #[inline]
+    pub fn meta_state(&self) -> MetaState {
+        self.meta_state
+    }
+
+    /// Returns the button state during this event, as a bitfield.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getbuttonstate)
+    #[inline]
+    pub fn button_state(&self) -> ButtonState {
+        self.button_state
+    }
+
+    /// Returns the time of the start of this gesture, in the `java.lang.System.nanoTime()` time
+    /// base
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getdowntime)
+    #[inline]
+    pub fn down_time(&self) -> i64 {
+        self.down_time
+    }
+
+    /// Returns a bitfield indicating which edges were touched by this event.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getedgeflags)
+    #[inline]
+    pub fn edge_flags(&self) -> EdgeFlags {
+        self.edge_flags
+    }
+
+    /// Returns the time of this event, in the `java.lang.System.nanoTime()` time base
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_geteventtime)
+    #[inline]
+    pub fn event_time(&self) -> i64 {
+        self.event_time
+    }
+
+    /// The flags associated with a motion event.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getflags)
+    #[inline]
+    pub fn flags(&self) -> MotionEventFlags {
+        self.flags
+    }
+
+    /// Returns the offset in the x direction between the coordinates and the raw coordinates
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getxoffset)
+    #[inline]
+    pub fn x_offset(&self) -> f32 {
+        self.x_offset
+    }
+
+    /// Returns the offset in the y direction between the coordinates and the raw coordinates
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getyoffset)
+    #[inline]
+    pub fn y_offset(&self) -> f32 {
+        self.y_offset
+    }
+
+    /// Returns the precision of the x value of the coordinates
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getxprecision)
+    #[inline]
+    pub fn x_precision(&self) -> f32 {
+        self.x_precision
+    }
+
+    /// Returns the precision of the y value of the coordinates
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_getyprecision)
+    #[inline]
+    pub fn y_precision(&self) -> f32 {
+        self.y_precision
+    }
+}
+
+/// A view into the data of a specific pointer in a motion event.
+#[derive(Debug)]
+pub struct Pointer<'a> {
+    event: &'a MotionEvent,
+    index: usize,
+}
+
+impl<'a> Pointer<'a> {
+    #[inline]
+    pub fn pointer_index(&self) -> usize {
+        self.index
+    }
+
+    #[inline]
+    pub fn pointer_id(&self) -> i32 {
+        self.event.pointers[self.index].id
+    }
+
+    #[inline]
+    pub fn axis_value(&self, axis: Axis) -> f32 {
+        self.event.pointers[self.index].axis_value(axis)
+    }
+
+    #[inline]
+    pub fn orientation(&self) -> f32 {
+        self.axis_value(Axis::Orientation)
+    }
+
+    #[inline]
+    pub fn pressure(&self) -> f32 {
+        self.axis_value(Axis::Pressure)
+    }
+
+    #[inline]
+    pub fn raw_x(&self) -> f32 {
+        self.x() + self.event.x_offset
+    }
+
+    #[inline]
+    pub fn raw_y(&self) -> f32 {
+        self.y() + self.event.y_offset
+    }
+
+    #[inline]
+    pub fn x(&self) -> f32 {
+        self.axis_value(Axis::X)
+    }
+
+    #[inline]
+    pub fn y(&self) -> f32 {
+        self.axis_value(Axis::Y)
+    }
+
+    #[inline]
+    pub fn size(&self) -> f32 {
+        self.axis_value(Axis::Size)
+    }
+
+    #[inline]
+    pub fn tool_major(&self) -> f32 {
+        self.axis_value(Axis::ToolMajor)
+    }
+
+    #[inline]
+    pub fn tool_minor(&self) -> f32 {
+        self.axis_value(Axis::ToolMinor)
+    }
+
+    #[inline]
+    pub fn touch_major(&self) -> f32 {
+        self.axis_value(Axis::TouchMajor)
+    }
+
+    #[inline]
+    pub fn touch_minor(&self) -> f32 {
+        self.axis_value(Axis::TouchMinor)
+    }
+}
+
+/// An iterator over the pointers in a [`MotionEvent`].
+#[derive(Debug)]
+pub struct PointersIter<'a> {
+    event: &'a MotionEvent,
+    next_index: usize,
+    count: usize,
+}
+
+impl<'a> Iterator for PointersIter<'a> {
+    type Item = Pointer<'a>;
+    fn next(&mut self) -> Option<Pointer<'a>> {
+        if self.next_index < self.count {
+            let ptr = Pointer {
+                event: self.event,
+                index: self.next_index,
+            };
+            self.next_index += 1;
+            Some(ptr)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.count - self.next_index;
+        (size, Some(size))
+    }
+}
+
+impl<'a> ExactSizeIterator for PointersIter<'a> {
+    fn len(&self) -> usize {
+        self.count - self.next_index
+    }
+}
+
+/// Represents a view into a past moment of a motion event
+#[derive(Debug)]
+pub struct HistoricalMotionEvent<'a> {
+    event: &'a MotionEvent,
+    history_index: usize,
+}
+
+impl<'a> HistoricalMotionEvent<'a> {
+    /// Returns the "history index" associated with this historical event.  Older events have smaller indices.
+    #[inline]
+    pub fn history_index(&self) -> usize {
+        self.history_index
+    }
+
+    /// Returns the time of the historical event, in the `java.lang.System.nanoTime()` time base
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#amotionevent_gethistoricaleventtime)
+    #[inline]
+    pub fn event_time(&self) -> i64 {
+        self.event.history[self.history_index].event_time
+    }
+
+    /// An iterator over the pointers of this historical motion event
+    #[inline]
+    pub fn pointers(&self) -> HistoricalPointersIter<'a> {
+        HistoricalPointersIter {
+            event: self.event,
+            history_index: self.history_index,
+            next_pointer_index: 0,
+            pointer_count: self.event.pointer_count(),
+        }
+    }
+}
+
+/// An iterator over all the historical moments in a [`MotionEvent`].
+///
+/// It iterates from oldest to newest.
+#[derive(Debug)]
+pub struct HistoricalMotionEventsIter<'a> {
+    event: &'a MotionEvent,
+    next_history_index: usize,
+    history_size: usize,
+}
+
+impl<'a> Iterator for HistoricalMotionEventsIter<'a> {
+    type Item = HistoricalMotionEvent<'a>;
+
+    fn next(&mut self) -> Option<HistoricalMotionEvent<'a>> {
+        if self.next_history_index < self.history_size {
+            let res = HistoricalMotionEvent {
+                event: self.event,
+                history_index: self.next_history_index,
+            };
+            self.next_history_index += 1;
+            Some(res)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.history_size - self.next_history_index;
+        (size, Some(size))
+    }
+}
+impl ExactSizeIterator for HistoricalMotionEventsIter<'_> {
+    fn len(&self) -> usize {
+        self.history_size - self.next_history_index
+    }
+}
+impl<'a> DoubleEndedIterator for HistoricalMotionEventsIter<'a> {
+    fn next_back(&mut self) -> Option<HistoricalMotionEvent<'a>> {
+        if self.next_history_index < self.history_size {
+            self.history_size -= 1;
+            Some(HistoricalMotionEvent {
+                event: self.event,
+                history_index: self.history_size,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// A view into a pointer at a historical moment
+#[derive(Debug)]
+pub struct HistoricalPointer<'a> {
+    event: &'a MotionEvent,
+    pointer_index: usize,
+    history_index: usize,
+}
+
+impl<'a> HistoricalPointer<'a> {
+    #[inline]
+    pub fn pointer_index(&self) -> usize {
+        self.pointer_index
+    }
+
+    #[inline]
+    pub fn pointer_id(&self) -> i32 {
+        self.event.pointers[self.pointer_index].id
+    }
+
+    #[inline]
+    pub fn history_index(&self) -> usize {
+        self.history_index
+    }
+
+    #[inline]
+    pub fn axis_value(&self, axis: Axis) -> f32 {
+        self.event.history[self.history_index].pointers[self.pointer_index].axis_value(axis)
+    }
+
+    #[inline]
+    pub fn orientation(&self) -> f32 {
+        self.axis_value(Axis::Orientation)
+    }
+
+    #[inline]
+    pub fn pressure(&self) -> f32 {
+        self.axis_value(Axis::Pressure)
+    }
+
+    #[inline]
+    pub fn raw_x(&self) -> f32 {
+        self.x() + self.event.x_offset
+    }
+
+    #[inline]
+    pub fn raw_y(&self) -> f32 {
+        self.y() + self.event.y_offset
+    }
+
+    #[inline]
+    pub fn x(&self) -> f32 {
+        self.axis_value(Axis::X)
+    }
+
+    #[inline]
+    pub fn y(&self) -> f32 {
+        self.axis_value(Axis::Y)
+    }
+
+    #[inline]
+    pub fn size(&self) -> f32 {
+        self.axis_value(Axis::Size)
+    }
+
+    #[inline]
+    pub fn tool_major(&self) -> f32 {
+        self.axis_value(Axis::ToolMajor)
+    }
+
+    #[inline]
+    pub fn tool_minor(&self) -> f32 {
+        self.axis_value(Axis::ToolMinor)
+    }
+
+    #[inline]
+    pub fn touch_major(&self) -> f32 {
+        self.axis_value(Axis::TouchMajor)
+    }
+
+    #[inline]
+    pub fn touch_minor(&self) -> f32 {
+        self.axis_value(Axis::TouchMinor)
+    }
+}
+
+/// An iterator over the pointers in a historical motion event
+#[derive(Debug)]
+pub struct HistoricalPointersIter<'a> {
+    event: &'a MotionEvent,
+    history_index: usize,
+    next_pointer_index: usize,
+    pointer_count: usize,
+}
+
+impl<'a> Iterator for HistoricalPointersIter<'a> {
+    type Item = HistoricalPointer<'a>;
+
+    fn next(&mut self) -> Option<HistoricalPointer<'a>> {
+        if self.next_pointer_index < self.pointer_count {
+            let ptr = HistoricalPointer {
+                event: self.event,
+                history_index: self.history_index,
+                pointer_index: self.next_pointer_index,
+            };
+            self.next_pointer_index += 1;
+            Some(ptr)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.pointer_count - self.next_pointer_index;
+        (size, Some(size))
+    }
+}
+impl ExactSizeIterator for HistoricalPointersIter<'_> {
+    fn len(&self) -> usize {
+        self.pointer_count - self.next_pointer_index
+    }
+}
+
+/// A key event.
+///
+/// For general discussion of key events in Android, see [the relevant
+/// javadoc](https://developer.android.com/reference/android/view/KeyEvent).
+#[derive(Clone, Debug)]
+pub struct KeyEvent {
+    source: Source,
+    device_id: i32,
+    action: KeyAction,
+    key_code: Keycode,
+    scan_code: i32,
+    repeat_count: i32,
+    meta_state: MetaState,
+    flags: KeyEventFlags,
+    down_time: i64,
+    event_time: i64,
+}
+
+impl KeyEvent {
+    /// Creates a new key event with the given action, source and key code
+    ///
+    /// All other state (times, meta state, flags etc) is zeroed and can be
+    /// set via the `with_` methods.
+    pub fn new(action: KeyAction, source: Source, key_code: Keycode) -> Self {
+        Self {
+            source,
+            device_id: 0,
+            action,
+            key_code,
+            scan_code: 0,
+            repeat_count: 0,
+            meta_state: MetaState(0),
+            flags: KeyEventFlags(0),
+            down_time: 0,
+            event_time: 0,
+        }
+    }
+
+    /// Sets the device id associated with the event
+    pub fn with_device_id(mut self, device_id: i32) -> Self {
+        self.device_id = device_id;
+        self
+    }
+
+    /// Sets the down and event times for the event, in nanoseconds
+    pub fn with_times(mut self, down_time: i64, event_time: i64) -> Self {
+        self.down_time = down_time;
+        self.event_time = event_time;
+        self
+    }
+
+    /// Sets the hardware scan code and repeat count for the event
+    pub fn with_scan_code(mut self, scan_code: i32, repeat_count: i32) -> Self {
+        self.scan_code = scan_code;
+        self.repeat_count = repeat_count;
+        self
+    }
+
+    /// Sets the meta state and flags for the event
+    pub fn with_state(mut self, meta_state: MetaState, flags: KeyEventFlags) -> Self {
+        self.meta_state = meta_state;
+        self.flags = flags;
+        self
+    }
+
+    /// Get the source of the event.
+    ///
+    #[inline]
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Get the device id associated with the event.
+    ///
+    #[inline]
+    pub fn device_id(&self) -> i32 {
+        self.device_id
+    }
+
+    /// Returns the key action associated with the event.
+    ///
+    /// See [the KeyEvent docs](https://developer.android.com/reference/android/view/KeyEvent#getAction())
+    #[inline]
+    pub fn action(&self) -> KeyAction {
+        self.action
+    }
+
+    /// Returns the last time the key was pressed.  This is on the scale of
+    /// `java.lang.System.nanoTime()`, which has nanosecond precision, but no defined start time.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getdowntime)
+    #[inline]
+    pub fn down_time(&self) -> i64 {
+        self.down_time
+    }
+
+    /// Returns the time this event occured.  This is on the scale of
+    /// `java.lang.System.nanoTime()`, which has nanosecond precision, but no defined start time.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_geteventtime)
+    #[inline]
+    pub fn event_time(&self) -> i64 {
+        self.event_time
+    }
+
+    /// Returns the keycode associated with this key event
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getkeycode)
+    #[inline]
+    pub fn key_code(&self) -> Keycode {
+        self.key_code
+    }
+
+    /// Returns the number of repeats of a key.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getrepeatcount)
+    #[inline]
+    pub fn repeat_count(&self) -> i32 {
+        self.repeat_count
+    }
+
+    /// Returns the hardware keycode of a key.  This varies from device to device.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getscancode)
+    #[inline]
+    pub fn scan_code(&self) -> i32 {
+        self.scan_code
+    }
+
+    /// Flags associated with this [`KeyEvent`].
+    ///
+    /// See [the NDK docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getflags)
+    #[inline]
+    pub fn flags(&self) -> KeyEventFlags {
+        self.flags
+    }
+
+    /// Returns the state of the modifiers during this key event, represented by a bitmask.
+    ///
+    /// See [the NDK
+    /// docs](https://developer.android.com/ndk/reference/group/input#akeyevent_getmetastate)
+    #[inline]
+    pub fn meta_state(&self) -> MetaState {
+        self.meta_state
+    }
+}
@@ -0,0 +1,853 @@
+#![cfg(feature="mock-activity")]
+
+//! A host-side backend that runs on top of an in-process event queue instead
+//! of an Android `Activity`.
+//!
+//! This makes it possible to run application logic that's written against
+//! [`AndroidApp`] on a regular Linux host (e.g. for unit testing on CI)
+//! by scripting lifecycle events, window changes, configuration changes
+//! and input via a [`MockActivity`].
+
+use std::collections::VecDeque;
+use std::io;
+use std::os::unix::prelude::RawFd;
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::trace;
+
+use ndk::configuration::{LayoutDir, Orientation, UiModeNight, UiModeType};
+use ndk::hardware_buffer_format::HardwareBufferFormat;
+use ndk::looper::FdEvent;
+
+use crate::{InputStatus, MainEvent, MainEntry, MemoryTrimLevel, Rect, ShowSoftInputFlags, HideSoftInputFlags, TextInputState, InputType, ImeOptions, RawPollEvent, AndroidApp, NativeWindowRef, PollError};
+use crate::looper::{self, FdRegistry, FdToken, TimerId, TimerKind};
+use crate::proxy::UserEventQueue;
+use crate::filter::{EventFilters, KeyEventFilter, MotionEventFilter};
+use crate::frame::FrameCallbacks;
+use crate::lifecycle::LifecycleTracker;
+use crate::window::{WindowManagerFlags, WindowState};
+use crate::insets::{InsetsState, InsetsType};
+use crate::recording::Recorder;
+use crate::epoll::{EpollLooper, EpollPoll, TriggerMode};
+
+mod asset;
+pub use asset::{Asset, AssetDir, AssetManager};
+
+pub mod input;
+mod replay;
+
+// Consistent with the native app glue, which reserves LOOPER_ID_MAIN (1) and
+// LOOPER_ID_INPUT (2)
+const MOCK_LOOPER_ID_USER: i32 = 3;
+
+/// The SDK version reported by [`AndroidApp::sdk_version()`] and the default [`Configuration`]
+pub const MOCK_SDK_VERSION: i32 = 30;
+
+/// A host-side stand in for an `ndk::native_window::NativeWindow`
+///
+/// There's no surface that can be rendered to, this just tracks the
+/// geometry of the window that was scripted via [`MockEvent::InitWindow`]
+/// and [`MockEvent::WindowResized`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeWindow {
+    width: i32,
+    height: i32,
+    format: HardwareBufferFormat,
+}
+
+impl NativeWindow {
+    pub fn new(width: i32, height: i32, format: HardwareBufferFormat) -> Self {
+        Self { width, height, format }
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn format(&self) -> HardwareBufferFormat {
+        self.format
+    }
+}
+
+/// A host-side stand in for an `ndk::configuration::Configuration`
+///
+/// This only tracks a subset of the state that can be queried from a real
+/// `AConfiguration` but the accessors have the same names and types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Configuration {
+    pub density: Option<u32>,
+    pub orientation: Orientation,
+    pub screen_width_dp: Option<i32>,
+    pub screen_height_dp: Option<i32>,
+    pub smallest_screen_width_dp: Option<i32>,
+    pub sdk_version: i32,
+    pub language: Option<String>,
+    pub country: Option<String>,
+    pub layout_direction: LayoutDir,
+    pub ui_mode_type: UiModeType,
+    pub ui_mode_night: UiModeNight,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            density: Some(160),
+            orientation: Orientation::Port,
+            screen_width_dp: None,
+            screen_height_dp: None,
+            smallest_screen_width_dp: None,
+            sdk_version: MOCK_SDK_VERSION,
+            language: None,
+            country: None,
+            layout_direction: LayoutDir::Ltr,
+            ui_mode_type: UiModeType::Normal,
+            ui_mode_night: UiModeNight::No,
+        }
+    }
+}
+
+impl Configuration {
+    pub fn density(&self) -> Option<u32> {
+        self.density
+    }
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+    pub fn screen_width_dp(&self) -> Option<i32> {
+        self.screen_width_dp
+    }
+    pub fn screen_height_dp(&self) -> Option<i32> {
+        self.screen_height_dp
+    }
+    pub fn smallest_screen_width_dp(&self) -> Option<i32> {
+        self.smallest_screen_width_dp
+    }
+    pub fn sdk_version(&self) -> i32 {
+        self.sdk_version
+    }
+    pub fn language(&self) -> Option<String> {
+        self.language.clone()
+    }
+    pub fn country(&self) -> Option<String> {
+        self.country.clone()
+    }
+    pub fn layout_direction(&self) -> LayoutDir {
+        self.layout_direction
+    }
+    pub fn ui_mode_type(&self) -> UiModeType {
+        self.ui_mode_type
+    }
+    pub fn ui_mode_night(&self) -> UiModeNight {
+        self.ui_mode_night
+    }
+}
+
+/// A scripted event, sent to the application via [`MockActivity::send()`]
+///
+/// Each variant corresponds to an `APP_CMD_*` command that would be sent
+/// by the Java main thread, and any associated state is applied before the
+/// corresponding [`MainEvent`] is delivered to the application.
+#[derive(Clone, Debug)]
+pub enum MockEvent {
+    /// Makes a new window available via [`AndroidApp::native_window()`]
+    InitWindow { width: i32, height: i32, format: HardwareBufferFormat },
+    /// Removes the window, after the application has handled the event
+    TerminateWindow,
+    /// Updates the size of the current window
+    WindowResized { width: i32, height: i32 },
+    RedrawNeeded,
+    ContentRectChanged(Rect),
+    GainedFocus,
+    LostFocus,
+    ConfigChanged(Configuration),
+    TrimMemory(MemoryTrimLevel),
+    Start,
+    Resume,
+    SaveState,
+    Pause,
+    Stop,
+    Destroy,
+    /// Updates the insets of the given types, leaving any others unchanged
+    InsetsChanged(Vec<(InsetsType, Rect)>),
+}
+
+#[derive(Debug)]
+struct MockState {
+    commands: VecDeque<MockEvent>,
+    poll_errors: VecDeque<PollError>,
+    input: VecDeque<input::InputEvent>,
+    unhandled_input: Vec<input::InputEvent>,
+    saved_state: Option<Vec<u8>>,
+    busy: bool,
+    woken: bool,
+    fd_registry: FdRegistry,
+    finished: bool,
+    soft_input_shown: bool,
+    text_input: TextInputState,
+    ime_editor_info: Option<(InputType, ImeOptions)>,
+    window_flags: WindowManagerFlags,
+}
+
+// The mock looper is an EpollLooper (which is also used by the real backends
+// with the "epoll" feature), whose wake ups are used to notify the main thread
+// of any changes to the MockState
+#[derive(Debug)]
+struct MockShared {
+    state: Mutex<MockState>,
+    cond: Condvar,
+    looper: EpollLooper,
+}
+
+impl MockShared {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(MockState {
+                commands: VecDeque::new(),
+                poll_errors: VecDeque::new(),
+                input: VecDeque::new(),
+                unhandled_input: Vec::new(),
+                saved_state: None,
+                busy: false,
+                woken: false,
+                fd_registry: FdRegistry::new(MOCK_LOOPER_ID_USER),
+                finished: false,
+                soft_input_shown: false,
+                text_input: TextInputState::default(),
+                ime_editor_info: None,
+                window_flags: WindowManagerFlags::empty(),
+            }),
+            cond: Condvar::new(),
+            looper: EpollLooper::new().expect("Failed to create mock looper"),
+        }
+    }
+
+    // Must be called after updating the state, so the main thread
+    // will see the update when it's woken
+    fn wake_looper(&self) {
+        self.looper.wake();
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockPaths {
+    internal_data_path: Option<PathBuf>,
+    external_data_path: Option<PathBuf>,
+    obb_path: Option<PathBuf>,
+    asset_dir: Option<PathBuf>,
+}
+
+// The only time it's safe to update the saved state is while handling a
+// SaveState event, so this API is only exposed for those events...
+#[derive(Debug)]
+pub struct StateSaver<'a> {
+    app: &'a AndroidAppInner,
+}
+
+impl<'a> StateSaver<'a> {
+    pub fn store(&self, state: &'a [u8]) {
+        self.app.shared.state.lock().unwrap().saved_state = Some(state.to_vec());
+    }
+}
+
+#[derive(Debug)]
+pub struct StateLoader<'a> {
+    app: &'a AndroidAppInner,
+}
+impl<'a> StateLoader<'a> {
+    pub fn load(&self) -> Option<Vec<u8>> {
+        self.app.shared.state.lock().unwrap().saved_state.clone()
+    }
+}
+
+/// The backend-specific identifier for a command from the (mock) Java main thread
+pub(crate) type MainCmd = MockEvent;
+
+#[derive(Clone)]
+pub struct AndroidAppWaker {
+    shared: Arc<MockShared>,
+}
+
+impl AndroidAppWaker {
+    pub fn wake(&self) {
+        self.shared.state.lock().unwrap().woken = true;
+        self.shared.wake_looper();
+    }
+}
+
+/// The host side of a mock application, used to script events
+///
+/// This plays the role of the Java `Activity` and main thread, while the
+/// [`AndroidApp`] returned by [`MockActivity::app()`] is passed to the
+/// code under test (typically an `android_main` function run via
+/// [`MockActivity::run()`]).
+///
+/// Events are queued and then delivered the next time the application
+/// calls [`AndroidApp::poll_events()`]. Like the real backends, input events
+/// don't wake up the application's event loop.
+///
+/// ```
+/// use android_activity::{AndroidApp, MockActivity, MockEvent, PollEvent, MainEvent};
+///
+/// fn android_main(app: AndroidApp) {
+///     let mut quit = false;
+///     while !quit {
+///         app.poll_events(None, |event| {
+///             if let PollEvent::Main(MainEvent::Destroy) = event {
+///                 quit = true;
+///             }
+///         });
+///     }
+/// }
+///
+/// let activity = MockActivity::new();
+/// let main = activity.run(android_main);
+/// activity.send(MockEvent::Start);
+/// activity.send(MockEvent::Resume);
+/// activity.send(MockEvent::Destroy);
+/// main.join().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct MockActivity {
+    app: AndroidApp,
+}
+
+impl Default for MockActivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockActivity {
+    /// Creates a new mock activity, with no window and a default [`Configuration`]
+    pub fn new() -> Self {
+        Self {
+            app: AndroidApp {
+                inner: Arc::new(AndroidAppInner {
+                    shared: Arc::new(MockShared::new()),
+                    config: Default::default(),
+                    window: Default::default(),
+                    insets: Default::default(),
+                    paths: Default::default(),
+                    user_events: Default::default(),
+                    frames: Default::default(),
+                    filters: EventFilters::new(),
+                    lifecycle: Default::default(),
+                    recorder: Default::default(),
+                }),
+            },
+        }
+    }
+
+    /// Creates a new mock activity that will be resumed with the given saved state
+    pub fn with_saved_state(state: Vec<u8>) -> Self {
+        let activity = Self::new();
+        activity.app.inner.shared.state.lock().unwrap().saved_state = Some(state);
+        activity
+    }
+
+    /// The [`AndroidApp`] that should be passed to the code under test
+    pub fn app(&self) -> AndroidApp {
+        self.app.clone()
+    }
+
+    /// Spawns a thread that calls `main` with this activity's [`AndroidApp`]
+    ///
+    /// This runs `main` in the same way that the real backends run `android_main`
+    pub fn run<F>(&self, main: F) -> thread::JoinHandle<()>
+        where F: FnOnce(AndroidApp) + Send + 'static
+    {
+        let app = self.app();
+        thread::Builder::new()
+            .name("android_main".to_string())
+            .spawn(move || main(app))
+            .expect("Failed to spawn android_main thread")
+    }
+
+    /// Spawns a thread that runs an application's exported [`MainEntry`]
+    ///
+    /// Unlike [`MockActivity::run()`], this includes any setup that's done by
+    /// `#[android_activity::main]`, such as finishing the activity if `android_main`
+    /// returns an error. In that case the thread keeps handling the events that
+    /// follow [`AndroidApp::finish()`] until the activity has been destroyed.
+    pub fn run_entry(&self, entry: &MainEntry) -> thread::JoinHandle<()> {
+        let entry = *entry;
+        self.run(move |app| entry.call(app))
+    }
+
+    /// Queues an event for the application and wakes up its event loop
+    pub fn send(&self, event: MockEvent) {
+        let shared = &self.app.inner.shared;
+        shared.state.lock().unwrap().commands.push_back(event);
+        shared.wake_looper();
+    }
+
+    /// Queues an error, to be returned by the application's next call to
+    /// [`AndroidApp::try_poll_events()`] instead of an event
+    pub fn send_poll_error(&self, err: PollError) {
+        let shared = &self.app.inner.shared;
+        shared.state.lock().unwrap().poll_errors.push_back(err);
+        shared.wake_looper();
+    }
+
+    /// Queues an input event, to be returned by [`AndroidApp::input_events()`]
+    pub fn send_input(&self, event: input::InputEvent) {
+        let mut state = self.app.inner.shared.state.lock().unwrap();
+        state.input.push_back(event);
+    }
+
+    /// Takes the input events that would have been passed back to the system, since
+    /// the application either filtered them out or didn't handle them
+    pub fn take_unhandled_input(&self) -> Vec<input::InputEvent> {
+        std::mem::take(&mut self.app.inner.shared.state.lock().unwrap().unhandled_input)
+    }
+
+    /// Simulates the start of a new frame, as reported by an `AChoreographer`
+    ///
+    /// If the application has requested a frame callback, via
+    /// [`AndroidApp::request_frame_callback()`], a [`PollEvent::Frame`](crate::PollEvent::Frame)
+    /// with the given vsync time is delivered and this returns `true`.
+    pub fn send_frame(&self, vsync_time_ns: i64) -> bool {
+        let inner = &self.app.inner;
+        // Like the real frame callback, this wakes up the looper as if via an `AndroidAppWaker`
+        if inner.frames.on_frame(vsync_time_ns) {
+            inner.create_waker().wake();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Blocks until all queued events have been handled by the application
+    ///
+    /// Returns `false` if the events weren't handled within the given `timeout`
+    pub fn wait_idle(&self, timeout: Duration) -> bool {
+        let shared = &self.app.inner.shared;
+        let deadline = Instant::now() + timeout;
+        let mut state = shared.state.lock().unwrap();
+        while !state.commands.is_empty() || state.busy {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = shared.cond.wait_timeout(state, deadline - now).unwrap().0;
+        }
+        true
+    }
+
+    /// Sends a [`MockEvent::SaveState`] and waits for the application to handle it
+    ///
+    /// Returns the state that was stored via [`StateSaver::store()`], if any
+    pub fn save_state(&self, timeout: Duration) -> Option<Vec<u8>> {
+        self.send(MockEvent::SaveState);
+        self.wait_idle(timeout);
+        self.saved_state()
+    }
+
+    /// The most recent state stored by the application
+    pub fn saved_state(&self) -> Option<Vec<u8>> {
+        self.app.inner.shared.state.lock().unwrap().saved_state.clone()
+    }
+
+    /// Returns `true` if the application has asked for the activity to be finished
+    pub fn is_finished(&self) -> bool {
+        self.app.inner.shared.state.lock().unwrap().finished
+    }
+
+    /// Returns `true` if the application has shown the soft keyboard and hasn't hidden it since
+    pub fn is_soft_input_shown(&self) -> bool {
+        self.app.inner.shared.state.lock().unwrap().soft_input_shown
+    }
+
+    /// The most recent configuration from [`AndroidApp::set_ime_editor_info()`], if any
+    pub fn ime_editor_info(&self) -> Option<(InputType, ImeOptions)> {
+        self.app.inner.shared.state.lock().unwrap().ime_editor_info
+    }
+
+    /// The window flags that have been set via [`AndroidApp::set_window_flags()`]
+    pub fn window_flags(&self) -> WindowManagerFlags {
+        self.app.inner.shared.state.lock().unwrap().window_flags
+    }
+
+    /// Sets the path returned by [`AndroidApp::internal_data_path()`]
+    pub fn set_internal_data_path(&self, path: Option<PathBuf>) {
+        self.app.inner.paths.write().unwrap().internal_data_path = path;
+    }
+
+    /// Sets the path returned by [`AndroidApp::external_data_path()`]
+    pub fn set_external_data_path(&self, path: Option<PathBuf>) {
+        self.app.inner.paths.write().unwrap().external_data_path = path;
+    }
+
+    /// Sets the path returned by [`AndroidApp::obb_path()`]
+    pub fn set_obb_path(&self, path: Option<PathBuf>) {
+        self.app.inner.paths.write().unwrap().obb_path = path;
+    }
+
+    /// Sets the host directory that assets are read from, via [`AndroidApp::asset_manager()`]
+    pub fn set_asset_dir(&self, path: Option<PathBuf>) {
+        self.app.inner.paths.write().unwrap().asset_dir = path;
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct AndroidAppInner {
+    shared: Arc<MockShared>,
+    config: RwLock<Configuration>,
+    window: RwLock<WindowState>,
+    insets: RwLock<InsetsState>,
+    paths: RwLock<MockPaths>,
+    user_events: Arc<UserEventQueue>,
+    frames: Arc<FrameCallbacks>,
+    filters: EventFilters,
+    lifecycle: Mutex<LifecycleTracker>,
+    recorder: Mutex<Option<Recorder>>,
+}
+
+impl AndroidAppInner {
+    pub fn native_window<'a>(&self) -> Option<NativeWindowRef> {
+        self.window.read().unwrap().native_window()
+    }
+
+    pub(crate) fn poll_raw(&self, timeout: Option<Duration>) -> Option<RawPollEvent> {
+        trace!("poll_raw");
+
+        let shared = &self.shared;
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            {
+                let mut state = shared.state.lock().unwrap();
+                if let Some(err) = state.poll_errors.pop_front() {
+                    trace!("Returning mock poll error {err}");
+                    return Some(RawPollEvent::Error(err));
+                }
+                if let Some(cmd) = state.commands.pop_front() {
+                    trace!("Read mock command {cmd:?}");
+                    state.busy = true;
+                    return Some(RawPollEvent::Main(cmd));
+                }
+                if state.woken {
+                    trace!("Mock looper woken");
+                    state.woken = false;
+                    return Some(RawPollEvent::Wake);
+                }
+            }
+
+            let remaining = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            match shared.looper.poll(remaining) {
+                // Wake ups are only used to notify us of changes to the MockState
+                Ok(EpollPoll::Wake) => {}
+                Ok(EpollPoll::Timeout) => {
+                    trace!("Mock looper timed out");
+                    return Some(RawPollEvent::Timeout);
+                }
+                Ok(EpollPoll::Fd { ident, fd, events }) => {
+                    if let Some(event) = shared.state.lock().unwrap().fd_registry.raw_event(ident, fd, events) {
+                        return Some(event);
+                    }
+                }
+                Err(err) => return Some(RawPollEvent::Error(PollError::Io(err))),
+            }
+        }
+    }
+
+    // Emulates android_app_pre_exec_cmd()
+    pub(crate) fn pre_exec_cmd(&self, cmd: &MainCmd) {
+        match cmd {
+            MockEvent::InitWindow { width, height, format } => {
+                self.window.write().unwrap().init_window(NativeWindow::new(*width, *height, *format));
+            }
+            MockEvent::WindowResized { width, height } => {
+                let mut state = self.window.write().unwrap();
+                if let Some(window) = state.native_window_mut() {
+                    window.width = *width;
+                    window.height = *height;
+                }
+                state.resize_window();
+            }
+            MockEvent::ContentRectChanged(rect) => {
+                self.window.write().unwrap().set_content_rect(rect.clone());
+            }
+            MockEvent::ConfigChanged(config) => {
+                *self.config.write().unwrap() = config.clone();
+            }
+            MockEvent::InsetsChanged(changes) => {
+                let mut insets = self.insets.write().unwrap();
+                let new = InsetsType::ALL.map(|ty| {
+                    changes.iter().rev().find(|(changed, _)| *changed == ty)
+                        .map(|(_, rect)| rect.clone())
+                        .unwrap_or_else(|| insets.get(ty))
+                });
+                insets.update(|ty| new[ty as usize].clone());
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn main_event(&self, cmd: &MainCmd) -> Result<Option<MainEvent<'_>>, PollError> {
+        let event = match cmd {
+            MockEvent::InitWindow { .. } => return Ok(self.window.read().unwrap().init_window_event()),
+            MockEvent::TerminateWindow => return Ok(self.window.read().unwrap().terminate_window_event()),
+            MockEvent::WindowResized { .. } => return Ok(self.window.read().unwrap().window_resized_event()),
+            MockEvent::RedrawNeeded => MainEvent::RedrawNeeded {},
+            MockEvent::ContentRectChanged(_) => self.window.read().unwrap().content_rect_changed_event(),
+            MockEvent::GainedFocus => MainEvent::GainedFocus,
+            MockEvent::LostFocus => MainEvent::LostFocus,
+            MockEvent::ConfigChanged(_) => MainEvent::ConfigChanged,
+            MockEvent::TrimMemory(level) => MainEvent::TrimMemory { level: *level },
+            MockEvent::Start => MainEvent::Start,
+            MockEvent::Resume => MainEvent::Resume { loader: StateLoader { app: self } },
+            MockEvent::SaveState => MainEvent::SaveState { saver: StateSaver { app: self } },
+            MockEvent::Pause => MainEvent::Pause,
+            MockEvent::Stop => MainEvent::Stop,
+            MockEvent::Destroy => MainEvent::Destroy,
+            MockEvent::InsetsChanged(_) => MainEvent::InsetsChanged { changed: self.insets.read().unwrap().changed() },
+        };
+        Ok(Some(event))
+    }
+
+    // Emulates android_app_post_exec_cmd()
+    pub(crate) fn post_exec_cmd(&self, cmd: &MainCmd) {
+        if let MockEvent::TerminateWindow = cmd {
+            self.window.write().unwrap().terminate_window();
+        }
+
+        let mut state = self.shared.state.lock().unwrap();
+        state.busy = false;
+        self.shared.cond.notify_all();
+    }
+
+    pub fn show_soft_input(&self, _flags: ShowSoftInputFlags) {
+        self.shared.state.lock().unwrap().soft_input_shown = true;
+    }
+
+    pub fn hide_soft_input(&self, _flags: HideSoftInputFlags) {
+        self.shared.state.lock().unwrap().soft_input_shown = false;
+    }
+
+    pub fn text_input_state(&self) -> TextInputState {
+        self.shared.state.lock().unwrap().text_input.clone()
+    }
+
+    pub fn set_text_input_state(&self, state: TextInputState) {
+        self.shared.state.lock().unwrap().text_input = state;
+    }
+
+    pub fn set_ime_editor_info(&self, input_type: InputType, options: ImeOptions) {
+        self.shared.state.lock().unwrap().ime_editor_info = Some((input_type, options));
+    }
+
+    pub fn set_window_flags(&self, add: WindowManagerFlags, remove: WindowManagerFlags) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.window_flags = (state.window_flags - remove) | add;
+    }
+
+    pub fn set_key_event_filter(&self, filter: KeyEventFilter) {
+        self.filters.set_key_filter(filter);
+    }
+
+    pub fn set_motion_event_filter(&self, filter: MotionEventFilter) {
+        self.filters.set_motion_filter(filter);
+    }
+
+    /// Marks the activity as finished, which can be checked via [`MockActivity::is_finished()`]
+    ///
+    /// Like a real `Activity`, this is followed by the lifecycle events that take the
+    /// activity from its current state (including any events that are already queued)
+    /// through to being destroyed: `Pause` (and `LostFocus`), `Stop`, `SaveState`,
+    /// `TerminateWindow` and finally `Destroy`.
+    pub fn finish(&self) {
+        use crate::LifecycleState::*;
+
+        let mut state = self.shared.state.lock().unwrap();
+        if state.finished {
+            return;
+        }
+        state.finished = true;
+
+        let (mut lifecycle, mut focused, mut window_ready) = {
+            let tracker = self.lifecycle.lock().unwrap();
+            (tracker.state(), tracker.has_focus(), tracker.is_window_ready())
+        };
+        for cmd in &state.commands {
+            match cmd {
+                MockEvent::Start => lifecycle = Started,
+                MockEvent::Resume => lifecycle = Resumed,
+                MockEvent::Pause => lifecycle = Paused,
+                MockEvent::Stop => lifecycle = Stopped,
+                MockEvent::Destroy => lifecycle = Destroying,
+                MockEvent::GainedFocus => focused = true,
+                MockEvent::LostFocus => focused = false,
+                MockEvent::InitWindow { .. } => window_ready = true,
+                MockEvent::TerminateWindow => window_ready = false,
+                _ => {}
+            }
+        }
+        if lifecycle == Destroying {
+            return;
+        }
+
+        if lifecycle == Resumed {
+            state.commands.push_back(MockEvent::Pause);
+        }
+        if focused {
+            state.commands.push_back(MockEvent::LostFocus);
+        }
+        if matches!(lifecycle, Started | Resumed | Paused) {
+            state.commands.push_back(MockEvent::Stop);
+            state.commands.push_back(MockEvent::SaveState);
+        }
+        if window_ready {
+            state.commands.push_back(MockEvent::TerminateWindow);
+        }
+        state.commands.push_back(MockEvent::Destroy);
+        drop(state);
+        self.shared.wake_looper();
+    }
+
+    pub fn register_fd(&self, fd: RawFd, events: FdEvent) -> io::Result<FdToken> {
+        self.register_fd_with_trigger(fd, events, TriggerMode::Level)
+    }
+
+    pub fn register_fd_with_trigger(&self, fd: RawFd, events: FdEvent, mode: TriggerMode) -> io::Result<FdToken> {
+        let mut state = self.shared.state.lock().unwrap();
+        let token = state.fd_registry.allocate(fd)?;
+        if let Err(err) = self.shared.looper.add_fd(fd, token.ident(), events, mode) {
+            let _ = state.fd_registry.release(token);
+            return Err(err);
+        }
+        Ok(token)
+    }
+
+    pub fn unregister_fd(&self, token: FdToken) -> io::Result<()> {
+        let fd = self.shared.state.lock().unwrap().fd_registry.release(token)?;
+        self.shared.looper.remove_fd(fd)
+    }
+
+    pub fn add_timer(&self, kind: TimerKind) -> io::Result<TimerId> {
+        let timerfd = looper::create_timerfd(kind)?;
+        let mut state = self.shared.state.lock().unwrap();
+        let result = state.fd_registry.allocate_timer(timerfd).and_then(|id| {
+            match self.shared.looper.add_fd(timerfd, id.ident(), FdEvent::INPUT, TriggerMode::Level) {
+                Ok(()) => Ok(id),
+                Err(err) => {
+                    let _ = state.fd_registry.release_timer(id);
+                    Err(err)
+                }
+            }
+        });
+        if result.is_err() {
+            unsafe { libc::close(timerfd); }
+        }
+        result
+    }
+
+    pub fn remove_timer(&self, id: TimerId) -> io::Result<()> {
+        // Note: the state lock is held until the timerfd is closed so that
+        // poll_raw() can't read from the fd after it's closed
+        let mut state = self.shared.state.lock().unwrap();
+        let timerfd = state.fd_registry.release_timer(id)?;
+        let result = self.shared.looper.remove_fd(timerfd);
+        unsafe { libc::close(timerfd); }
+        result
+    }
+
+    pub(crate) fn user_events(&self) -> &Arc<UserEventQueue> {
+        &self.user_events
+    }
+
+    pub(crate) fn frames(&self) -> &Arc<FrameCallbacks> {
+        &self.frames
+    }
+
+    pub fn request_frame_callback(&self) -> io::Result<()> {
+        self.frames.request(|| Ok(()))
+    }
+
+    pub(crate) fn lifecycle(&self) -> &Mutex<LifecycleTracker> {
+        &self.lifecycle
+    }
+
+    pub(crate) fn recorder(&self) -> &Mutex<Option<Recorder>> {
+        &self.recorder
+    }
+
+    pub fn create_waker(&self) -> AndroidAppWaker {
+        AndroidAppWaker { shared: self.shared.clone() }
+    }
+
+    pub fn config(&self) -> Configuration {
+        self.config.read().unwrap().clone()
+    }
+
+    pub fn content_rect(&self) -> Rect {
+        self.window.read().unwrap().content_rect()
+    }
+
+    pub fn window_insets(&self, ty: InsetsType) -> Rect {
+        self.insets.read().unwrap().get(ty)
+    }
+
+    pub fn asset_manager(&self) -> AssetManager {
+        AssetManager::new(self.paths.read().unwrap().asset_dir.clone())
+    }
+
+    pub fn enable_motion_axis(&self, _axis: input::Axis) {
+        // NOP - All axis values of scripted input events are available
+    }
+
+    pub fn disable_motion_axis(&self, _axis: input::Axis) {
+        // NOP - All axis values of scripted input events are available
+    }
+
+    pub fn input_events<'b, F>(&self, mut callback: F)
+        where F: FnMut(&input::InputEvent) -> InputStatus
+    {
+        // Take the events before invoking the callback so that the
+        // activity can continue to send input while they are handled
+        let events: Vec<input::InputEvent> = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.input.drain(..).collect()
+        };
+        // Notifies a `MockActivity::replay()` that's waiting for input to be handled
+        self.shared.cond.notify_all();
+        for event in events {
+            trace!("Mock input event {event:?}");
+            let accepted = match &event {
+                input::InputEvent::KeyEvent(key_event) => self.filters.accept_key(key_event),
+                input::InputEvent::MotionEvent(motion_event) => self.filters.accept_motion(motion_event),
+                _ => true,
+            };
+            // As with GameTextInput, the state is updated before the event is delivered
+            if let input::InputEvent::TextEvent(text_input) = &event {
+                self.shared.state.lock().unwrap().text_input = text_input.clone();
+            }
+            if !accepted || callback(&event) == InputStatus::Unhandled {
+                self.shared.state.lock().unwrap().unhandled_input.push(event);
+            }
+        }
+    }
+
+    pub fn dropped_input_events(&self) -> u64 {
+        // Like an `AInputQueue`, the mock input queue is unbounded
+        0
+    }
+
+    pub fn internal_data_path(&self) -> Option<std::path::PathBuf> {
+        self.paths.read().unwrap().internal_data_path.clone()
+    }
+
+    pub fn external_data_path(&self) -> Option<std::path::PathBuf> {
+        self.paths.read().unwrap().external_data_path.clone()
+    }
+
+    pub fn obb_path(&self) -> Option<std::path::PathBuf> {
+        self.paths.read().unwrap().obb_path.clone()
+    }
+}
@@ -0,0 +1,212 @@
+use std::convert::TryFrom;
+use std::io;
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+use ndk::configuration::{LayoutDir, Orientation, UiModeNight, UiModeType};
+use ndk::hardware_buffer_format::HardwareBufferFormat;
+
+use crate::recording::{ConfigRecord, KeyRecord, MotionRecord, PointerRecord, Record};
+use crate::{InsetsType, LifecycleState, MemoryTrimLevel, Recording, Rect};
+
+use super::input::{self, Axis, MockPointer};
+use super::{Configuration, MockActivity, MockEvent};
+
+// How long to wait for the application to handle each replayed main event
+const REPLAY_IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn invalid(what: &str, value: u32) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Invalid {what} {value} in recording"))
+}
+
+fn convert<T: TryFrom<u32>>(what: &str, value: u32) -> io::Result<T> {
+    T::try_from(value).map_err(|_| invalid(what, value))
+}
+
+fn config(record: &ConfigRecord) -> io::Result<Configuration> {
+    Ok(Configuration {
+        density: record.density,
+        orientation: convert::<Orientation>("orientation", record.orientation)?,
+        screen_width_dp: record.screen_width_dp,
+        screen_height_dp: record.screen_height_dp,
+        smallest_screen_width_dp: record.smallest_screen_width_dp,
+        sdk_version: record.sdk_version,
+        language: record.language.clone(),
+        country: record.country.clone(),
+        layout_direction: convert::<LayoutDir>("layout direction", record.layout_direction)?,
+        ui_mode_type: convert::<UiModeType>("UI mode type", record.ui_mode_type)?,
+        ui_mode_night: convert::<UiModeNight>("UI night mode", record.ui_mode_night)?,
+    })
+}
+
+fn pointers(records: &[PointerRecord]) -> io::Result<Vec<MockPointer>> {
+    records.iter().map(|record| {
+        record.axes.iter().try_fold(MockPointer::new(record.id, 0.0, 0.0), |pointer, (axis, value)| {
+            Ok(pointer.with_axis(convert::<Axis>("axis", *axis)?, *value))
+        })
+    }).collect()
+}
+
+fn motion_event(record: &MotionRecord) -> io::Result<input::InputEvent> {
+    let mut event = input::MotionEvent::new(
+            convert("motion action", record.action)?,
+            convert("source", record.source)?,
+            pointers(&record.pointers)?,
+        )
+        .with_pointer_index(record.pointer_index)
+        .with_device_id(record.device_id)
+        .with_times(record.down_time, record.event_time)
+        .with_state(input::MetaState(record.meta_state), input::ButtonState(record.button_state))
+        .with_flags(input::EdgeFlags(record.edge_flags), input::MotionEventFlags(record.flags))
+        .with_precision(record.offset.0, record.offset.1, record.precision.0, record.precision.1);
+    for (event_time, historical) in &record.history {
+        event = event.with_history(*event_time, pointers(historical)?);
+    }
+    Ok(input::InputEvent::MotionEvent(event))
+}
+
+fn key_event(record: &KeyRecord) -> io::Result<input::InputEvent> {
+    let event = input::KeyEvent::new(
+            convert("key action", record.action)?,
+            convert("source", record.source)?,
+            convert("key code", record.key_code)?,
+        )
+        .with_device_id(record.device_id)
+        .with_times(record.down_time, record.event_time)
+        .with_scan_code(record.scan_code, record.repeat_count)
+        .with_state(input::MetaState(record.meta_state), input::KeyEventFlags(record.flags));
+    Ok(input::InputEvent::KeyEvent(event))
+}
+
+// The events that take a newly created activity to the recorded lifecycle state
+fn lifecycle_events(state: LifecycleState) -> &'static [MockEvent] {
+    use MockEvent::*;
+    match state {
+        LifecycleState::Started => &[Start],
+        LifecycleState::Resumed => &[Start, Resume],
+        LifecycleState::Paused => &[Start, Resume, Pause],
+        LifecycleState::Stopped => &[Start, Stop],
+        LifecycleState::Destroying => &[Destroy],
+        _ => &[],
+    }
+}
+
+fn main_event(name: &str) -> Option<MockEvent> {
+    let event = match name {
+        "TerminateWindow" => MockEvent::TerminateWindow,
+        "RedrawNeeded" => MockEvent::RedrawNeeded,
+        "GainedFocus" => MockEvent::GainedFocus,
+        "LostFocus" => MockEvent::LostFocus,
+        "Start" => MockEvent::Start,
+        "Resume" => MockEvent::Resume,
+        "SaveState" => MockEvent::SaveState,
+        "Pause" => MockEvent::Pause,
+        "Stop" => MockEvent::Stop,
+        "Destroy" => MockEvent::Destroy,
+                _ => return None,
+    };
+    Some(event)
+}
+
+impl MockActivity {
+    /// Replays a [`Recording`] that was made via [`AndroidApp::start_recording()`](crate::AndroidApp::start_recording)
+    ///
+    /// This should be called on a newly created activity whose application is
+    /// already running (e.g. via [`MockActivity::run()`]). The recorded configuration
+    /// is applied immediately and then the recorded lifecycle state, window and focus
+    /// are re-created by sending the corresponding events, before replaying each of
+    /// the recorded events in order.
+    ///
+    /// Events are replayed as fast as the application handles them, rather than with
+    /// their recorded timing, so that a replay is deterministic: this waits for each
+    /// main event to be handled before sending the next. Input events are queued via
+    /// [`send_input()`](Self::send_input) and the application is woken up, and then any
+    /// queued input must be taken via [`AndroidApp::input_events()`](crate::AndroidApp::input_events)
+    /// before the next main event is sent. Any step that isn't handled within a few seconds
+    /// logs a warning and the replay continues.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the recording contains
+    /// values that can't be represented by the mock backend, in which case any events
+    /// that precede the invalid record will have already been sent.
+    pub fn replay(&self, recording: &Recording) -> io::Result<()> {
+        for (_, record) in &recording.records {
+            match record {
+                Record::Config(record) => {
+                    *self.app.inner.config.write().unwrap() = config(record)?;
+                }
+                Record::State { lifecycle, focused, window } => {
+                    for event in lifecycle_events(*lifecycle) {
+                        self.replay_main(event.clone());
+                    }
+                    if let Some((width, height, format)) = window {
+                        let format = convert::<HardwareBufferFormat>("window format", *format)?;
+                        self.replay_main(MockEvent::InitWindow { width: *width, height: *height, format });
+                    }
+                    if *focused {
+                        self.replay_main(MockEvent::GainedFocus);
+                    }
+                }
+                Record::InitWindow { width, height, format } => {
+                    let format = convert::<HardwareBufferFormat>("window format", *format)?;
+                    self.replay_main(MockEvent::InitWindow { width: *width, height: *height, format });
+                }
+                Record::WindowResized { width, height } => {
+                    self.replay_main(MockEvent::WindowResized { width: *width, height: *height });
+                }
+                Record::ContentRectChanged { left, top, right, bottom } => {
+                    let rect = Rect { left: *left, top: *top, right: *right, bottom: *bottom };
+                    self.replay_main(MockEvent::ContentRectChanged(rect));
+                }
+                Record::ConfigChanged(record) => {
+                    self.replay_main(MockEvent::ConfigChanged(config(record)?));
+                }
+                Record::InsetsChanged(insets) => {
+                    let insets = insets.iter().map(|(ty, [left, top, right, bottom])| {
+                        Ok((convert::<InsetsType>("insets type", *ty)?, Rect { left: *left, top: *top, right: *right, bottom: *bottom }))
+                    }).collect::<io::Result<_>>()?;
+                    self.replay_main(MockEvent::InsetsChanged(insets));
+                }
+                Record::TrimMemory { level } => self.replay_main(MockEvent::TrimMemory(MemoryTrimLevel::from_raw(*level))),
+                Record::Main(name) => match main_event(name) {
+                    Some(event) => self.replay_main(event),
+                    None => warn!("Skipping unknown main event {name:?} in recording"),
+                },
+                Record::Motion(record) => self.replay_input(motion_event(record)?),
+                Record::Key(record) => self.replay_input(key_event(record)?),
+            }
+        }
+        Ok(())
+    }
+
+    fn replay_main(&self, event: MockEvent) {
+        // Any replayed input should be handled before the next main event, as it was when recorded
+        if !self.wait_input_taken(REPLAY_IDLE_TIMEOUT) {
+            warn!("Timed out waiting for the application to handle replayed input");
+        }
+        self.send(event);
+        if !self.wait_idle(REPLAY_IDLE_TIMEOUT) {
+            warn!("Timed out waiting for the application to handle a replayed event");
+        }
+    }
+
+    // Like the native app glue, queued input wakes up the application's event loop
+    fn replay_input(&self, event: input::InputEvent) {
+        self.send_input(event);
+        self.app.create_waker().wake();
+    }
+
+    fn wait_input_taken(&self, timeout: Duration) -> bool {
+        let shared = &self.app.inner.shared;
+        let deadline = Instant::now() + timeout;
+        let mut state = shared.state.lock().unwrap();
+        while !state.input.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = shared.cond.wait_timeout(state, deadline - now).unwrap().0;
+        }
+        true
+    }
+}
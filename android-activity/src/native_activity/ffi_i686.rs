@@ -8913,6 +8913,21 @@ fn bindgen_test_layout_android_poll_source() {
         )
     );
 }
+#[doc = " The types of window insets that are cached in android_app, which correspond"]
+#[doc = " to the WindowInsets.Type masks (except for INSETS_TYPE_WATERFALL, which"]
+#[doc = " corresponds to DisplayCutout.getWaterfallInsets())."]
+pub const INSETS_TYPE_CAPTION_BAR: ::std::os::raw::c_uint = 0;
+pub const INSETS_TYPE_DISPLAY_CUTOUT: ::std::os::raw::c_uint = 1;
+pub const INSETS_TYPE_IME: ::std::os::raw::c_uint = 2;
+pub const INSETS_TYPE_MANDATORY_SYSTEM_GESTURES: ::std::os::raw::c_uint = 3;
+pub const INSETS_TYPE_NAVIGATION_BARS: ::std::os::raw::c_uint = 4;
+pub const INSETS_TYPE_STATUS_BARS: ::std::os::raw::c_uint = 5;
+pub const INSETS_TYPE_SYSTEM_BARS: ::std::os::raw::c_uint = 6;
+pub const INSETS_TYPE_SYSTEM_GESTURES: ::std::os::raw::c_uint = 7;
+pub const INSETS_TYPE_TAPPABLE_ELEMENT: ::std::os::raw::c_uint = 8;
+pub const INSETS_TYPE_WATERFALL: ::std::os::raw::c_uint = 9;
+pub const INSETS_TYPE_COUNT: ::std::os::raw::c_uint = 10;
+pub type _bindgen_ty_27 = ::std::os::raw::c_uint;
 #[doc = " The native activity interface provided by <android/native_activity.h>"]
 #[doc = " is based on a set of application-provided callbacks that will be called"]
 #[doc = " by the Activity's main thread when certain events occur."]
@@ -8993,12 +9008,15 @@ pub struct android_app {
     pub pendingInputQueue: *mut AInputQueue,
     pub pendingWindow: *mut ANativeWindow,
     pub pendingContentRect: ARect,
+    pub trimMemoryLevel: ::std::os::raw::c_int,
+    pub insets: [ARect; 10usize],
+    pub pendingInsets: [ARect; 10usize],
 }
 #[test]
 fn bindgen_test_layout_android_app() {
     assert_eq!(
         ::std::mem::size_of::<android_app>(),
-        148usize,
+        472usize,
         concat!("Size of: ", stringify!(android_app))
     );
     assert_eq!(
@@ -9276,6 +9294,36 @@ fn bindgen_test_layout_android_app() {
             stringify!(pendingContentRect)
         )
     );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).trimMemoryLevel as *const _ as usize },
+        148usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(trimMemoryLevel)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).insets as *const _ as usize },
+        152usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(insets)
+        )
+    );
+    assert_eq!(
+        unsafe { &(*(::std::ptr::null::<android_app>())).pendingInsets as *const _ as usize },
+        312usize,
+        concat!(
+            "Offset of field: ",
+            stringify!(android_app),
+            "::",
+            stringify!(pendingInsets)
+        )
+    );
 }
 #[doc = " Looper data ID of commands coming from the app's main thread, which"]
 #[doc = " is returned as an identifier from ALooper_pollOnce().  The data for this"]
@@ -9291,7 +9339,7 @@ pub const LOOPER_ID_MAIN: ::std::os::raw::c_uint = 1;
 pub const LOOPER_ID_INPUT: ::std::os::raw::c_uint = 2;
 #[doc = " Start of user-defined ALooper identifiers."]
 pub const LOOPER_ID_USER: ::std::os::raw::c_uint = 3;
-pub type _bindgen_ty_27 = ::std::os::raw::c_uint;
+pub type _bindgen_ty_28 = ::std::os::raw::c_uint;
 #[doc = " Command from main thread: the AInputQueue has changed.  Upon processing"]
 #[doc = " this command, android_app->inputQueue will be updated to the new queue"]
 #[doc = " (or NULL)."]
@@ -9344,7 +9392,10 @@ pub const APP_CMD_STOP: ::std::os::raw::c_uint = 14;
 #[doc = " Command from main thread: the app's activity is being destroyed,"]
 #[doc = " and waiting for the app thread to clean up and exit before proceeding."]
 pub const APP_CMD_DESTROY: ::std::os::raw::c_uint = 15;
-pub type _bindgen_ty_28 = ::std::os::raw::c_uint;
+#[doc = " Command from main thread: the window insets have changed.  Upon"]
+#[doc = " processing this command, android_app->insets will be updated."]
+pub const APP_CMD_WINDOW_INSETS_CHANGED: ::std::os::raw::c_uint = 16;
+pub type _bindgen_ty_29 = ::std::os::raw::c_uint;
 extern "C" {
     #[doc = " Call when ALooper_pollAll() returns LOOPER_ID_MAIN, reading the next"]
     #[doc = " app command message."]
@@ -0,0 +1,111 @@
+//! Minimal helpers for calling into Java, for the `Activity` APIs that aren't
+//! exposed via `ANativeActivity`
+
+use std::ffi::CString;
+use std::io;
+use std::ptr;
+
+use jni_sys::*;
+
+fn jni_error(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, msg.into())
+}
+
+macro_rules! jni_fn {
+    ($env:expr, $name:ident) => {
+        (**$env).$name.ok_or_else(|| jni_error(concat!("Missing JNI function ", stringify!($name))))?
+    };
+}
+
+/// The JNI environment of a thread that's attached to the JVM
+pub(crate) struct JniEnv {
+    env: *mut JNIEnv,
+}
+
+impl JniEnv {
+    /// Gets the environment of the current thread, which must already be attached
+    /// to the JVM (as the thread running `android_main` is)
+    pub(crate) unsafe fn current(vm: *mut JavaVM) -> io::Result<Self> {
+        let mut env: *mut std::ffi::c_void = ptr::null_mut();
+        let get_env = jni_fn!(vm, GetEnv);
+        match get_env(vm, &mut env, JNI_VERSION_1_6) {
+            JNI_OK => Ok(Self { env: env.cast() }),
+            JNI_EDETACHED => Err(jni_error("The current thread isn't attached to the JVM")),
+            err => Err(jni_error(format!("Failed to get JNI environment: {err}"))),
+        }
+    }
+
+    /// Calls `f` within a new frame of local references, which are all released
+    /// when it returns
+    pub(crate) unsafe fn with_local_frame<T>(&self, capacity: i32, f: impl FnOnce(&Self) -> io::Result<T>) -> io::Result<T> {
+        let env = self.env;
+        if jni_fn!(env, PushLocalFrame)(env, capacity) != JNI_OK {
+            self.check_exception()?;
+            return Err(jni_error("Failed to push a JNI local frame"));
+        }
+        let result = f(self);
+        jni_fn!(env, PopLocalFrame)(env, ptr::null_mut());
+        result
+    }
+
+    // Any pending exception is cleared, since no further JNI calls could be made
+    unsafe fn check_exception(&self) -> io::Result<()> {
+        let env = self.env;
+        if jni_fn!(env, ExceptionCheck)(env) == JNI_TRUE {
+            jni_fn!(env, ExceptionDescribe)(env);
+            jni_fn!(env, ExceptionClear)(env);
+            return Err(jni_error("Java exception thrown"));
+        }
+        Ok(())
+    }
+
+    unsafe fn find_class(&self, name: &str) -> io::Result<jclass> {
+        let env = self.env;
+        let c_name = CString::new(name).unwrap();
+        let class = jni_fn!(env, FindClass)(env, c_name.as_ptr());
+        self.check_exception()?;
+        if class.is_null() {
+            return Err(jni_error(format!("Failed to find class {name}")));
+        }
+        Ok(class)
+    }
+
+    /// Creates a new object via the constructor with the given signature
+    pub(crate) unsafe fn new_object(&self, class: &str, sig: &str, args: &[jvalue]) -> io::Result<jobject> {
+        let env = self.env;
+        let class = self.find_class(class)?;
+        let c_sig = CString::new(sig).unwrap();
+        let method = jni_fn!(env, GetMethodID)(env, class, b"<init>\0".as_ptr().cast(), c_sig.as_ptr());
+        self.check_exception()?;
+        let obj = jni_fn!(env, NewObjectA)(env, class, method, args.as_ptr());
+        self.check_exception()?;
+        if obj.is_null() {
+            return Err(jni_error("Failed to create a new object"));
+        }
+        Ok(obj)
+    }
+
+    /// Calls a static method that returns `void`
+    pub(crate) unsafe fn call_static_void_method(&self, class: &str, name: &str, sig: &str, args: &[jvalue]) -> io::Result<()> {
+        let env = self.env;
+        let class = self.find_class(class)?;
+        let (c_name, c_sig) = (CString::new(name).unwrap(), CString::new(sig).unwrap());
+        let method = jni_fn!(env, GetStaticMethodID)(env, class, c_name.as_ptr(), c_sig.as_ptr());
+        self.check_exception()?;
+        jni_fn!(env, CallStaticVoidMethodA)(env, class, method, args.as_ptr());
+        self.check_exception()
+    }
+
+    /// Reads an `int` field of an object
+    pub(crate) unsafe fn get_int_field(&self, obj: jobject, name: &str) -> io::Result<jint> {
+        let env = self.env;
+        if obj.is_null() {
+            return Err(jni_error(format!("Can't read {name} from a null object")));
+        }
+        let class = jni_fn!(env, GetObjectClass)(env, obj);
+        let c_name = CString::new(name).unwrap();
+        let field = jni_fn!(env, GetFieldID)(env, class, c_name.as_ptr(), b"I\0".as_ptr().cast());
+        self.check_exception()?;
+        Ok(jni_fn!(env, GetIntField)(env, obj, field))
+    }
+}
@@ -1,20 +1,20 @@
-#![cfg(any(feature="native-activity", doc))]
+#![cfg(any(feature="native-activity", all(doc, not(feature="mock-activity"))))]
 
 use std::ffi::{CStr, CString};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::os::raw;
 use std::ptr::NonNull;
 use std::sync::Arc;
-use std::sync::RwLock;
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::io;
 use std::time::Duration;
-use std::{thread, ptr};
+use std::panic::AssertUnwindSafe;
+use std::ptr;
 use std::os::unix::prelude::*;
 
-use log::{Level, error, info, trace};
+use log::{error, info, trace, Level};
 
-use ndk_sys::ALooper_wake;
-use ndk_sys::{ALooper, ALooper_pollAll};
+#[cfg(not(feature = "epoll"))]
+use ndk_sys::{ALooper, ALooper_pollAll, ALooper_wake};
 
 use ndk::asset::AssetManager;
 use ndk::configuration::Configuration;
@@ -22,10 +22,25 @@ use ndk::input_queue::InputQueue;
 use ndk::looper::{FdEvent};
 use ndk::native_window::NativeWindow;
 
-use crate::{MainEvent, Rect, PollEvent, AndroidApp, NativeWindowRef};
+use crate::{InputStatus, MainEvent, MemoryTrimLevel, Rect, ShowSoftInputFlags, HideSoftInputFlags, TextInputState, InputType, ImeOptions, RawPollEvent, AndroidApp, NativeWindowRef, PollError};
+use crate::looper::{self, FdRegistry, FdToken, TimerId, TimerKind};
+use crate::proxy::UserEventQueue;
+use crate::filter::{EventFilters, KeyEventFilter, MotionEventFilter};
+use crate::frame::FrameCallbacks;
+use crate::frame::Choreographer;
+use crate::lifecycle::LifecycleTracker;
+use crate::window::{WindowManagerFlags, WindowState};
+use crate::insets::{InsetsState, InsetsType};
+use crate::recording::Recorder;
+use crate::entry::{self, MainEntry};
+use crate::util;
+#[cfg(feature = "epoll")]
+use crate::epoll::{EpollLooper, EpollPoll, TriggerMode};
 
 
 mod ffi;
+mod jni_utils;
+use jni_utils::JniEnv;
 
 pub mod input {
     pub use ndk::event::{
@@ -100,20 +115,34 @@ impl<'a> StateLoader<'a> {
 }
 
 
+/// The backend-specific identifier for a command from the Java main thread
+pub(crate) type MainCmd = i8;
+
 #[derive(Clone)]
 pub struct AndroidAppWaker {
     // The looper pointer is owned by the android_app and effectively
     // has a 'static lifetime, and the ALooper_wake C API is thread
     // safe, so this can be cloned safely and is send + sync safe
-    looper: NonNull<ALooper>
+    #[cfg(not(feature = "epoll"))]
+    looper: NonNull<ALooper>,
+    #[cfg(feature = "epoll")]
+    looper: Arc<EpollLooper>,
 }
+#[cfg(not(feature = "epoll"))]
 unsafe impl Send for AndroidAppWaker {}
+#[cfg(not(feature = "epoll"))]
 unsafe impl Sync for AndroidAppWaker {}
 
 impl AndroidAppWaker {
+    #[cfg(not(feature = "epoll"))]
     pub fn wake(&self) {
         unsafe { ALooper_wake(self.looper.as_ptr()); }
     }
+
+    #[cfg(feature = "epoll")]
+    pub fn wake(&self) {
+        self.looper.wake();
+    }
 }
 
 impl AndroidApp {
@@ -127,11 +156,42 @@ impl AndroidApp {
         // config state with a deep copy.
         let config = Configuration::clone_from_ptr(NonNull::new_unchecked((*ptr.as_ptr()).config));
 
+        // With the "epoll" feature we multiplex the glue's command pipe along with any
+        // user fds and timers ourselves. The epoll instance is nested in the ALooper that
+        // the native app glue prepared for this thread, which is only polled to block
+        // until there's an event, so it can still run callbacks and be woken up. Since
+        // user fds are only added to the epoll instance, its ident isn't otherwise used
+        // by the ALooper.
+        #[cfg(feature = "epoll")]
+        let epoll = {
+            let epoll = EpollLooper::new().expect("Failed to create epoll looper");
+            epoll.add_fd((*ptr.as_ptr()).msgread, ffi::LOOPER_ID_MAIN as i32, FdEvent::INPUT, TriggerMode::Level)
+                .expect("Failed to add glue command pipe to epoll looper");
+            epoll.nest_in_alooper((*ptr.as_ptr()).looper, ffi::LOOPER_ID_USER as i32)
+                .expect("Failed to add epoll looper to the ALooper");
+            Arc::new(epoll)
+        };
+
+        let mut insets = InsetsState::default();
+        insets.update(|ty| read_insets(ptr, ty));
+
         AndroidApp {
             inner: Arc::new(AndroidAppInner {
                 ptr,
                 config: RwLock::new(config),
-                native_window: Default::default()
+                window: RwLock::new(WindowState::with_content_rect(read_content_rect(ptr))),
+                insets: RwLock::new(insets),
+                trim_memory_level: RwLock::new(MemoryTrimLevel::Complete),
+                input_queue_attached: AtomicBool::new(false),
+                fd_registry: Mutex::new(FdRegistry::new(ffi::LOOPER_ID_USER as i32)),
+                user_events: Default::default(),
+                frames: Default::default(),
+                filters: EventFilters::new(),
+                choreographer: Choreographer::new(),
+                lifecycle: Default::default(),
+                recorder: Default::default(),
+                #[cfg(feature = "epoll")]
+                epoll,
             })
         }
     }
@@ -141,7 +201,70 @@ impl AndroidApp {
 pub(crate) struct AndroidAppInner {
     ptr: NonNull<ffi::android_app>,
     config: RwLock<Configuration>,
-    native_window: RwLock<Option<NativeWindow>>,
+    window: RwLock<WindowState>,
+    insets: RwLock<InsetsState>,
+    trim_memory_level: RwLock<MemoryTrimLevel>,
+    input_queue_attached: AtomicBool,
+    fd_registry: Mutex<FdRegistry>,
+    user_events: Arc<UserEventQueue>,
+    frames: Arc<FrameCallbacks>,
+    filters: EventFilters,
+    choreographer: Choreographer,
+    lifecycle: Mutex<LifecycleTracker>,
+    recorder: Mutex<Option<Recorder>>,
+    #[cfg(feature = "epoll")]
+    epoll: Arc<EpollLooper>,
+}
+
+// The android_app is owned by the native app glue and stays valid until after
+// android_main returns. Its state is only accessed via the glue's thread-safe APIs,
+// or the locked copies above, except from the APIs that are documented as only being
+// safe to call from the application's main thread.
+unsafe impl Send for AndroidAppInner {}
+unsafe impl Sync for AndroidAppInner {}
+
+// The glue sets the level from onTrimMemoryNative() (or 0 from onLowMemory())
+// before sending APP_CMD_LOW_MEMORY
+unsafe fn read_trim_memory_level(app_ptr: NonNull<ffi::android_app>) -> i32 {
+    let app_ptr = app_ptr.as_ptr();
+    let mutex = ptr::addr_of_mut!((*app_ptr).mutex).cast::<libc::pthread_mutex_t>();
+    libc::pthread_mutex_lock(mutex);
+    let level = (*app_ptr).trimMemoryLevel;
+    libc::pthread_mutex_unlock(mutex);
+    level
+}
+
+unsafe fn read_content_rect(app_ptr: NonNull<ffi::android_app>) -> Rect {
+    let rect = (*app_ptr.as_ptr()).contentRect;
+    Rect {
+        left: rect.left,
+        right: rect.right,
+        top: rect.top,
+        bottom: rect.bottom,
+    }
+}
+
+// Called by the thread's ALooper when the input queue has events, to wake up the
+// event loop. Returning 0 removes the queue from the ALooper, so that the events only
+// cause a single wake up, and it's attached again once they've all been read by
+// `input_events()`.
+unsafe extern "C" fn input_queue_callback(_fd: i32, _events: i32, data: *mut std::ffi::c_void) -> i32 {
+    let inner = &*data.cast::<AndroidAppInner>();
+    inner.input_queue_attached.store(false, Ordering::Relaxed);
+    inner.create_waker().wake();
+    0
+}
+
+// The glue queries the insets on the Java main thread, and copies them to
+// android_app->insets while handling APP_CMD_WINDOW_INSETS_CHANGED
+unsafe fn read_insets(app_ptr: NonNull<ffi::android_app>, ty: InsetsType) -> Rect {
+    let rect = (*app_ptr.as_ptr()).insets[ty as usize];
+    Rect {
+        left: rect.left,
+        right: rect.right,
+        top: rect.top,
+        bottom: rect.bottom,
+    }
 }
 
 impl AndroidAppInner {
@@ -153,18 +276,12 @@ impl AndroidAppInner {
     }
 
     pub fn native_window<'a>(&self) -> Option<NativeWindowRef> {
-        let guard = self.native_window.read().unwrap();
-        if let Some(ref window) = *guard {
-            Some(NativeWindowRef::new(window))
-        } else {
-            None
-        }
+        self.window.read().unwrap().native_window()
     }
 
-    pub fn poll_events<F>(&self, timeout: Option<Duration>, mut callback: F)
-        where F: FnMut(PollEvent)
-    {
-        trace!("poll_events");
+    #[cfg(not(feature = "epoll"))]
+    pub(crate) fn poll_raw(&self, timeout: Option<Duration>) -> Option<RawPollEvent> {
+        trace!("poll_raw");
 
         unsafe {
             let app_ptr = self.ptr;
@@ -180,26 +297,26 @@ impl AndroidAppInner {
             match id {
                 ffi::ALOOPER_POLL_WAKE => {
                     trace!("ALooper_pollAll returned POLL_WAKE");
-                    callback(PollEvent::Wake);
+                    Some(RawPollEvent::Wake)
                 }
                 ffi::ALOOPER_POLL_CALLBACK => {
                     // ALooper_pollAll is documented to handle all callback sources internally so it should
                     // never return a _CALLBACK source id...
                     error!("Spurious ALOOPER_POLL_CALLBACK from ALopper_pollAll() (ignored)");
+                    None
                 }
                 ffi::ALOOPER_POLL_TIMEOUT => {
                     trace!("ALooper_pollAll returned POLL_TIMEOUT");
-                    callback(PollEvent::Timeout);
+                    Some(RawPollEvent::Timeout)
                 }
                 ffi::ALOOPER_POLL_ERROR => {
                     trace!("ALooper_pollAll returned POLL_ERROR");
-                    callback(PollEvent::Error);
 
                     // Considering that this API is quite likely to be used in `android_main`
                     // it's rather unergonomic to require the call to unwrap a Result for each
-                    // call to poll_events(). Alternatively we could maybe even just panic!()
-                    // here, while it's hard to imagine practically being able to recover
-                    //return Err(LooperError);
+                    // call to poll_events(), so the error is only surfaced as a Result
+                    // via try_poll_events()
+                    Some(RawPollEvent::Error(PollError::Looper))
                 }
                 id if id >= 0 => {
                     match id as u32 {
@@ -207,96 +324,317 @@ impl AndroidAppInner {
                             trace!("ALooper_pollAll returned ID_MAIN");
                             let source: *mut ffi::android_poll_source = source.cast();
                             if source != ptr::null_mut() {
+                                // Note: the command is only read here, and it's up to the caller
+                                // to call pre_exec_cmd() and post_exec_cmd() either side of
+                                // dispatching the corresponding MainEvent
                                 let cmd_i = ffi::android_app_read_cmd(app_ptr.as_ptr());
-
-                                let cmd = match cmd_i as u32 {
-                                    // We don't forward info about the AInputQueue to apps since it's
-                                    // an implementation details that's also not compatible with
-                                    // GameActivity
-                                    ffi::APP_CMD_INPUT_CHANGED => None,
-
-                                    ffi::APP_CMD_INIT_WINDOW => Some(MainEvent::InitWindow {}),
-                                    ffi::APP_CMD_TERM_WINDOW => Some(MainEvent::TerminateWindow {}),
-                                    ffi::APP_CMD_WINDOW_RESIZED => Some(MainEvent::WindowResized {}),
-                                    ffi::APP_CMD_WINDOW_REDRAW_NEEDED => Some(MainEvent::RedrawNeeded {}),
-                                    ffi::APP_CMD_CONTENT_RECT_CHANGED => Some(MainEvent::ContentRectChanged),
-                                    ffi::APP_CMD_GAINED_FOCUS => Some(MainEvent::GainedFocus),
-                                    ffi::APP_CMD_LOST_FOCUS => Some(MainEvent::LostFocus),
-                                    ffi::APP_CMD_CONFIG_CHANGED => Some(MainEvent::ConfigChanged),
-                                    ffi::APP_CMD_LOW_MEMORY => Some(MainEvent::LowMemory),
-                                    ffi::APP_CMD_START => Some(MainEvent::Start),
-                                    ffi::APP_CMD_RESUME => Some(MainEvent::Resume { loader: StateLoader { app: &self } }),
-                                    ffi::APP_CMD_SAVE_STATE => Some(MainEvent::SaveState { saver: StateSaver { app: &self } }),
-                                    ffi::APP_CMD_PAUSE => Some(MainEvent::Pause),
-                                    ffi::APP_CMD_STOP => Some(MainEvent::Stop),
-                                    ffi::APP_CMD_DESTROY => Some(MainEvent::Destroy),
-
-                                    //ffi::NativeAppGlueAppCmd_APP_CMD_WINDOW_INSETS_CHANGED => MainEvent::InsetsChanged {},
-                                    _ => unreachable!()
-                                };
-
-                                trace!("Calling android_app_pre_exec_cmd({cmd_i})");
-                                ffi::android_app_pre_exec_cmd(app_ptr.as_ptr(), cmd_i);
-
-                                if let Some(cmd) = cmd {
-                                    trace!("Read ID_MAIN command {cmd_i} = {cmd:?}");
-                                    match cmd {
-                                        MainEvent::ConfigChanged => {
-                                            *self.config.write().unwrap() =
-                                                Configuration::clone_from_ptr(NonNull::new_unchecked((*app_ptr.as_ptr()).config));
-                                        }
-                                        MainEvent::InitWindow { .. } => {
-                                            let win_ptr = (*app_ptr.as_ptr()).window;
-                                            *self.native_window.write().unwrap() =
-                                                Some(NativeWindow::from_ptr(NonNull::new(win_ptr).unwrap()));
-                                        }
-                                        MainEvent::TerminateWindow { .. } => {
-                                            *self.native_window.write().unwrap() = None;
-                                        }
-                                        _ => {}
-                                    }
-
-                                    trace!("Invoking callback for ID_MAIN command = {:?}", cmd);
-                                    callback(PollEvent::Main(cmd));
+                                trace!("Read ID_MAIN command {cmd_i}");
+                                if cmd_i < 0 {
+                                    Some(RawPollEvent::Error(PollError::ReadCommand))
+                                } else {
+                                    Some(RawPollEvent::Main(cmd_i))
                                 }
-
-                                trace!("Calling android_app_post_exec_cmd({cmd_i})");
-                                ffi::android_app_post_exec_cmd(app_ptr.as_ptr(), cmd_i);
                             } else {
-                                panic!("ALooper_pollAll returned ID_MAIN event with NULL android_poll_source!");
+                                Some(RawPollEvent::Error(PollError::NullPollSource))
                             }
                         }
                         ffi::LOOPER_ID_INPUT => {
                             trace!("ALooper_pollAll returned ID_INPUT");
-                            // For now we don't forward notifications of input events specifically, we just
-                            // forward the notifications as a wake up, and assume the application main loop
-                            // will unconditionally check events for each iteration of it's event loop
-                            //
-                            // (Specifically notifying when input events are received would be inconsistent
-                            // with the current design of GameActivity input handling which we want to stay
-                            // compatible with))
-                            //
-                            // XXX: Actually it was a bad idea to emit a Wake for input since applications
-                            // are likely to _not_ consider that on its own a cause to redraw and it could
-                            // end up spamming enough wake ups to interfere with other events that would
-                            // trigger a redraw + input handling
-                            //callback(PollEvent::Wake);
+                            // The input queue is attached with input_queue_callback(), which wakes up
+                            // the looper instead (just once until the events are read, so that an
+                            // application that doesn't read input on every wake up isn't spammed
+                            // with them), so this ident isn't expected
+                            None
                         }
                         _ => {
-                            let events = FdEvent::from_bits(events as u32)
-                                .expect(&format!("Spurious ALooper_pollAll event flags {:#04x}", events as u32));
+                            let events = match FdEvent::from_bits(events as u32) {
+                                Some(events) => events,
+                                None => return Some(RawPollEvent::Error(PollError::InvalidFdEvents { ident: id, fd, events: events as u32 })),
+                            };
                             trace!("Custom ALooper event source: id = {id}, fd = {fd}, events = {events:?}, data = {source:?}");
-                            callback(PollEvent::FdEvent{ ident: id, fd: fd as RawFd, events, data: source });
+                            self.fd_registry.lock().unwrap().raw_event(id, fd as RawFd, events)
                         }
                     }
                 }
                 _ => {
                     error!("Spurious ALooper_pollAll return value {id} (ignored)");
+                    None
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "epoll")]
+    pub(crate) fn poll_raw(&self, timeout: Option<Duration>) -> Option<RawPollEvent> {
+        trace!("poll_raw");
+
+        match self.epoll.poll_alooper(timeout) {
+            Ok(EpollPoll::Wake) => Some(RawPollEvent::Wake),
+            Ok(EpollPoll::Timeout) => Some(RawPollEvent::Timeout),
+            Ok(EpollPoll::Fd { ident, .. }) if ident == ffi::LOOPER_ID_MAIN as i32 => {
+                // Note: as with ALooper, it's up to the caller to call pre_exec_cmd()
+                // and post_exec_cmd() either side of dispatching the corresponding MainEvent
+                let cmd_i = unsafe { ffi::android_app_read_cmd(self.ptr.as_ptr()) };
+                trace!("Read ID_MAIN command {cmd_i}");
+                if cmd_i < 0 {
+                    Some(RawPollEvent::Error(PollError::ReadCommand))
+                } else {
+                    Some(RawPollEvent::Main(cmd_i))
+                }
+            }
+            Ok(EpollPoll::Fd { ident, fd, events }) => self.fd_registry.lock().unwrap().raw_event(ident, fd, events),
+            Err(err) => Some(RawPollEvent::Error(PollError::Io(err))),
+        }
+    }
+
+    pub(crate) fn pre_exec_cmd(&self, cmd: &MainCmd) {
+        let cmd_i = *cmd;
+        unsafe {
+            let app_ptr = self.ptr;
+
+            // The old queue is destroyed once the glue has switched to the new queue
+            if cmd_i as u32 == ffi::APP_CMD_INPUT_CHANGED {
+                self.detach_input_queue();
+            }
+
+            trace!("Calling android_app_pre_exec_cmd({cmd_i})");
+            ffi::android_app_pre_exec_cmd(app_ptr.as_ptr(), cmd_i);
+
+            match cmd_i as u32 {
+                ffi::APP_CMD_INPUT_CHANGED => {
+                    self.attach_input_queue();
+                }
+                ffi::APP_CMD_CONFIG_CHANGED => {
+                    *self.config.write().unwrap() =
+                        Configuration::clone_from_ptr(NonNull::new_unchecked((*app_ptr.as_ptr()).config));
+                }
+                ffi::APP_CMD_INIT_WINDOW => {
+                    let win_ptr = (*app_ptr.as_ptr()).window;
+                    self.window.write().unwrap().init_window(NativeWindow::from_ptr(NonNull::new(win_ptr).unwrap()));
                 }
+                ffi::APP_CMD_WINDOW_RESIZED => {
+                    self.window.write().unwrap().resize_window();
+                }
+                ffi::APP_CMD_CONTENT_RECT_CHANGED => {
+                    self.window.write().unwrap().set_content_rect(read_content_rect(app_ptr));
+                }
+                ffi::APP_CMD_WINDOW_INSETS_CHANGED => {
+                    self.insets.write().unwrap().update(|ty| read_insets(app_ptr, ty));
+                }
+                ffi::APP_CMD_LOW_MEMORY => {
+                    let level = match read_trim_memory_level(app_ptr) {
+                        level if level > 0 => MemoryTrimLevel::from_raw(level),
+                        _ => self.query_trim_memory_level(),
+                    };
+                    *self.trim_memory_level.write().unwrap() = level;
+                }
+                _ => {}
             }
         }
     }
 
+    pub(crate) fn main_event(&self, cmd: &MainCmd) -> Result<Option<MainEvent<'_>>, PollError> {
+        let cmd_i = *cmd;
+        match cmd_i as u32 {
+            // We don't forward info about the AInputQueue to apps since it's
+            // an implementation details that's also not compatible with
+            // GameActivity
+            ffi::APP_CMD_INPUT_CHANGED => Ok(None),
+
+            ffi::APP_CMD_INIT_WINDOW => Ok(self.window.read().unwrap().init_window_event()),
+            ffi::APP_CMD_TERM_WINDOW => Ok(self.window.read().unwrap().terminate_window_event()),
+            ffi::APP_CMD_WINDOW_RESIZED => Ok(self.window.read().unwrap().window_resized_event()),
+            ffi::APP_CMD_WINDOW_REDRAW_NEEDED => Ok(Some(MainEvent::RedrawNeeded {})),
+            ffi::APP_CMD_CONTENT_RECT_CHANGED => Ok(Some(self.window.read().unwrap().content_rect_changed_event())),
+            ffi::APP_CMD_GAINED_FOCUS => Ok(Some(MainEvent::GainedFocus)),
+            ffi::APP_CMD_LOST_FOCUS => Ok(Some(MainEvent::LostFocus)),
+            ffi::APP_CMD_CONFIG_CHANGED => Ok(Some(MainEvent::ConfigChanged)),
+            ffi::APP_CMD_LOW_MEMORY => Ok(Some(MainEvent::TrimMemory { level: *self.trim_memory_level.read().unwrap() })),
+            ffi::APP_CMD_START => Ok(Some(MainEvent::Start)),
+            ffi::APP_CMD_RESUME => Ok(Some(MainEvent::Resume { loader: StateLoader { app: self } })),
+            ffi::APP_CMD_SAVE_STATE => Ok(Some(MainEvent::SaveState { saver: StateSaver { app: self } })),
+            ffi::APP_CMD_PAUSE => Ok(Some(MainEvent::Pause)),
+            ffi::APP_CMD_STOP => Ok(Some(MainEvent::Stop)),
+            ffi::APP_CMD_DESTROY => Ok(Some(MainEvent::Destroy)),
+            ffi::APP_CMD_WINDOW_INSETS_CHANGED => Ok(Some(MainEvent::InsetsChanged { changed: self.insets.read().unwrap().changed() })),
+
+            _ => Err(PollError::UnknownCommand(cmd_i as i32)),
+        }
+    }
+
+    pub(crate) fn post_exec_cmd(&self, cmd: &MainCmd) {
+        let cmd_i = *cmd;
+        // The window remains valid until the Java main thread is released
+        if cmd_i as u32 == ffi::APP_CMD_TERM_WINDOW {
+            self.window.write().unwrap().terminate_window();
+        }
+        unsafe {
+            trace!("Calling android_app_post_exec_cmd({cmd_i})");
+            ffi::android_app_post_exec_cmd(self.ptr.as_ptr(), cmd_i);
+        }
+    }
+
+    pub fn show_soft_input(&self, flags: ShowSoftInputFlags) {
+        unsafe {
+            ndk_sys::ANativeActivity_showSoftInput(self.native_activity() as *mut _, flags.bits());
+        }
+    }
+
+    pub fn hide_soft_input(&self, flags: HideSoftInputFlags) {
+        unsafe {
+            ndk_sys::ANativeActivity_hideSoftInput(self.native_activity() as *mut _, flags.bits());
+        }
+    }
+
+    pub fn text_input_state(&self) -> TextInputState {
+        // NOP - NativeActivity doesn't support text input
+        TextInputState::default()
+    }
+
+    pub fn set_text_input_state(&self, _state: TextInputState) {
+        // NOP - NativeActivity doesn't support text input
+    }
+
+    pub fn set_ime_editor_info(&self, _input_type: InputType, _options: ImeOptions) {
+        // NOP - NativeActivity doesn't support text input
+    }
+
+    pub fn set_window_flags(&self, add: WindowManagerFlags, remove: WindowManagerFlags) {
+        unsafe {
+            ndk_sys::ANativeActivity_setWindowFlags(self.native_activity() as *mut _, add.bits(), remove.bits());
+        }
+    }
+
+    pub fn set_key_event_filter(&self, filter: KeyEventFilter) {
+        self.filters.set_key_filter(filter);
+    }
+
+    pub fn set_motion_event_filter(&self, filter: MotionEventFilter) {
+        self.filters.set_motion_filter(filter);
+    }
+
+    /// Asks the Java main thread to finish (close) the `Activity`
+    pub fn finish(&self) {
+        unsafe {
+            ndk_sys::ANativeActivity_finish(self.native_activity() as *mut _);
+        }
+    }
+
+    #[cfg(not(feature = "epoll"))]
+    fn looper(&self) -> *mut ALooper {
+        unsafe { (*self.ptr.as_ptr()).looper }
+    }
+
+    #[cfg(not(feature = "epoll"))]
+    fn add_looper_fd(&self, fd: RawFd, ident: i32, events: FdEvent) -> io::Result<()> {
+        let ret = unsafe {
+            ndk_sys::ALooper_addFd(self.looper(), fd, ident, events.bits() as i32, None, ptr::null_mut())
+        };
+        if ret < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, format!("ALooper_addFd failed for file descriptor {fd}")));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "epoll"))]
+    fn remove_looper_fd(&self, fd: RawFd) -> io::Result<()> {
+        let ret = unsafe { ndk_sys::ALooper_removeFd(self.looper(), fd) };
+        if ret < 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, format!("ALooper_removeFd failed for file descriptor {fd}")));
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "epoll")]
+    fn add_looper_fd(&self, fd: RawFd, ident: i32, events: FdEvent) -> io::Result<()> {
+        self.epoll.add_fd(fd, ident, events, TriggerMode::Level)
+    }
+
+    #[cfg(feature = "epoll")]
+    fn remove_looper_fd(&self, fd: RawFd) -> io::Result<()> {
+        self.epoll.remove_fd(fd)
+    }
+
+    pub fn register_fd(&self, fd: RawFd, events: FdEvent) -> io::Result<FdToken> {
+        let mut registry = self.fd_registry.lock().unwrap();
+        let token = registry.allocate(fd)?;
+        if let Err(err) = self.add_looper_fd(fd, token.ident(), events) {
+            let _ = registry.release(token);
+            return Err(err);
+        }
+        Ok(token)
+    }
+
+    #[cfg(feature = "epoll")]
+    pub fn register_fd_with_trigger(&self, fd: RawFd, events: FdEvent, mode: TriggerMode) -> io::Result<FdToken> {
+        let mut registry = self.fd_registry.lock().unwrap();
+        let token = registry.allocate(fd)?;
+        if let Err(err) = self.epoll.add_fd(fd, token.ident(), events, mode) {
+            let _ = registry.release(token);
+            return Err(err);
+        }
+        Ok(token)
+    }
+
+    pub fn unregister_fd(&self, token: FdToken) -> io::Result<()> {
+        let fd = self.fd_registry.lock().unwrap().release(token)?;
+        self.remove_looper_fd(fd)
+    }
+
+    pub fn add_timer(&self, kind: TimerKind) -> io::Result<TimerId> {
+        let timerfd = looper::create_timerfd(kind)?;
+        let mut registry = self.fd_registry.lock().unwrap();
+        let id = match registry.allocate_timer(timerfd) {
+            Ok(id) => id,
+            Err(err) => {
+                unsafe { libc::close(timerfd); }
+                return Err(err);
+            }
+        };
+        if let Err(err) = self.add_looper_fd(timerfd, id.ident(), FdEvent::INPUT) {
+            let _ = registry.release_timer(id);
+            unsafe { libc::close(timerfd); }
+            return Err(err);
+        }
+        Ok(id)
+    }
+
+    pub fn remove_timer(&self, id: TimerId) -> io::Result<()> {
+        // Note: the registry lock is held until the timerfd is closed so that
+        // poll_raw() can't read from the fd after it's closed
+        let mut registry = self.fd_registry.lock().unwrap();
+        let timerfd = registry.release_timer(id)?;
+        let result = self.remove_looper_fd(timerfd);
+        unsafe { libc::close(timerfd); }
+        result
+    }
+
+    pub(crate) fn user_events(&self) -> &Arc<UserEventQueue> {
+        &self.user_events
+    }
+
+    pub(crate) fn frames(&self) -> &Arc<FrameCallbacks> {
+        &self.frames
+    }
+
+    pub fn request_frame_callback(&self) -> io::Result<()> {
+        self.frames.request(|| self.choreographer.post(&self.frames, self.create_waker()))
+    }
+
+    pub(crate) fn lifecycle(&self) -> &Mutex<LifecycleTracker> {
+        &self.lifecycle
+    }
+
+    pub(crate) fn recorder(&self) -> &Mutex<Option<Recorder>> {
+        &self.recorder
+    }
+
+    #[cfg(feature = "epoll")]
+    pub fn create_waker(&self) -> AndroidAppWaker {
+        AndroidAppWaker { looper: self.epoll.clone() }
+    }
+
+    #[cfg(not(feature = "epoll"))]
     pub fn create_waker(&self) -> AndroidAppWaker {
         unsafe {
             // From the application's pov we assume the app_ptr and looper pointer
@@ -311,17 +649,39 @@ impl AndroidAppInner {
     }
 
     pub fn content_rect(&self) -> Rect {
-        unsafe {
-            let app_ptr = self.ptr.as_ptr();
-            Rect {
-                left: (*app_ptr).contentRect.left,
-                right: (*app_ptr).contentRect.right,
-                top: (*app_ptr).contentRect.top,
-                bottom: (*app_ptr).contentRect.bottom,
+        self.window.read().unwrap().content_rect()
+    }
+
+    /// Queries the level of the last `onTrimMemory()` call for the process
+    ///
+    /// This is used for `onLowMemory()`, which NativeActivity sends as
+    /// `APP_CMD_LOW_MEMORY` without a level, so the level is read from
+    /// `ActivityManager.getMyMemoryState()` instead. It's `Complete` if that fails or
+    /// if the system hasn't reported a level.
+    fn query_trim_memory_level(&self) -> MemoryTrimLevel {
+        let level = unsafe {
+            let na = self.native_activity();
+            JniEnv::current((*na).vm.cast()).and_then(|env| env.with_local_frame(4, |env| {
+                let info = env.new_object("android/app/ActivityManager$RunningAppProcessInfo", "()V", &[])?;
+                env.call_static_void_method("android/app/ActivityManager", "getMyMemoryState",
+                    "(Landroid/app/ActivityManager$RunningAppProcessInfo;)V", &[jni_sys::jvalue { l: info }])?;
+                env.get_int_field(info, "lastTrimLevel")
+            }))
+        };
+        match level {
+            Ok(level) if level > 0 => MemoryTrimLevel::from_raw(level),
+            Ok(_) => MemoryTrimLevel::Complete,
+            Err(err) => {
+                error!("Failed to query the trim memory level: {err}");
+                MemoryTrimLevel::Complete
             }
         }
     }
 
+    pub fn window_insets(&self, ty: InsetsType) -> Rect {
+        self.insets.read().unwrap().get(ty)
+    }
+
     pub fn asset_manager(&self) -> AssetManager {
         unsafe {
             let app_ptr = self.ptr.as_ptr();
@@ -338,8 +698,33 @@ impl AndroidAppInner {
         // NOP - The InputQueue API doesn't let us optimize which axis values are read
     }
 
+    // Attaches the input queue to the thread's ALooper, to wake up the event loop
+    // when there are new input events, unless it's already attached
+    //
+    // This must be called on the thread running `android_main`.
+    fn attach_input_queue(&self) {
+        unsafe {
+            let app_ptr = self.ptr.as_ptr();
+            if (*app_ptr).inputQueue.is_null() || self.input_queue_attached.swap(true, Ordering::Relaxed) {
+                return;
+            }
+            let data = self as *const AndroidAppInner as *mut std::ffi::c_void;
+            ndk_sys::AInputQueue_attachLooper((*app_ptr).inputQueue.cast(), (*app_ptr).looper,
+                ndk_sys::ALOOPER_POLL_CALLBACK, Some(input_queue_callback), data);
+        }
+    }
+
+    fn detach_input_queue(&self) {
+        unsafe {
+            let app_ptr = self.ptr.as_ptr();
+            if !(*app_ptr).inputQueue.is_null() && self.input_queue_attached.swap(false, Ordering::Relaxed) {
+                ndk_sys::AInputQueue_detachLooper((*app_ptr).inputQueue.cast());
+            }
+        }
+    }
+
     pub fn input_events<'b, F>(&self, mut callback: F)
-        where F: FnMut(&input::InputEvent)
+        where F: FnMut(&input::InputEvent) -> InputStatus
     {
         let queue = unsafe {
             let app_ptr = self.ptr.as_ptr();
@@ -356,18 +741,34 @@ impl AndroidAppInner {
             if let Some(event) = queue.pre_dispatch(event) {
                 trace!("Pre dispatched input event {event:?}");
 
-                callback(&event);
+                // Events that are filtered out are reported as unhandled, so the
+                // system can handle them instead
+                let accepted = match &event {
+                    input::InputEvent::KeyEvent(key_event) => self.filters.accept_key(key_event),
+                    input::InputEvent::MotionEvent(motion_event) => self.filters.accept_motion(motion_event),
+                };
+                if !accepted {
+                    queue.finish_event(event, false);
+                    continue;
+                }
 
-                // Always report events as 'handled'. This means we won't get
-                // so called 'fallback' events generated (such as converting trackball
-                // events into emulated keypad events), but we could conceivably
-                // implement similar emulation somewhere else in the stack if
-                // necessary, and this will be more consistent with the GameActivity
-                // input handling that doesn't do any kind of emulation.
+                let status = callback(&event);
+
+                // Unhandled events may be handled by the system instead, which
+                // can also generate 'fallback' events for them (such as converting
+                // trackball events into emulated keypad events)
                 info!("Finishing input event {event:?}");
-                queue.finish_event(event, true);
+                queue.finish_event(event, status == InputStatus::Handled);
             }
         }
+
+        // All of the events have been read, so new events should wake up the event loop again
+        self.attach_input_queue();
+    }
+
+    pub fn dropped_input_events(&self) -> u64 {
+        // The events stay in the `AInputQueue` until they are read
+        0
     }
 
     fn try_get_path_from_ptr(path: *const u8) -> Option<std::path::PathBuf> {
@@ -418,51 +819,11 @@ unsafe extern "C" fn ANativeActivity_onCreate(
     ANativeActivity_onCreate_C(activity, saved_state, saved_state_size);
 }
 
-fn android_log(level: Level, tag: &CStr, msg: &CStr) {
-    let prio = match level {
-        Level::Error => ndk_sys::android_LogPriority_ANDROID_LOG_ERROR,
-        Level::Warn => ndk_sys::android_LogPriority_ANDROID_LOG_WARN,
-        Level::Info => ndk_sys::android_LogPriority_ANDROID_LOG_INFO,
-        Level::Debug => ndk_sys::android_LogPriority_ANDROID_LOG_DEBUG,
-        Level::Trace => ndk_sys::android_LogPriority_ANDROID_LOG_VERBOSE,
-    };
-    unsafe {
-        ndk_sys::__android_log_write(prio as raw::c_int, tag.as_ptr(), msg.as_ptr());
-    }
-}
-
-extern "Rust" {
-    pub fn android_main(app: AndroidApp);
-}
-
 // This is a spring board between android_native_app_glue and the user's
 // `app_main` function. This is run on a dedicated thread spawned
-// by android_native_app_glue.
-#[no_mangle]
-pub unsafe extern "C" fn _rust_glue_entry(app: *mut ffi::android_app) {
-
-    // Maybe make this stdout/stderr redirection an optional / opt-in feature?...
-    let mut logpipe: [RawFd; 2] = Default::default();
-    libc::pipe(logpipe.as_mut_ptr());
-    libc::dup2(logpipe[1], libc::STDOUT_FILENO);
-    libc::dup2(logpipe[1], libc::STDERR_FILENO);
-    thread::spawn(move || {
-        let tag = CStr::from_bytes_with_nul(b"RustStdoutStderr\0").unwrap();
-        let file = File::from_raw_fd(logpipe[0]);
-        let mut reader = BufReader::new(file);
-        let mut buffer = String::new();
-        loop {
-            buffer.clear();
-            if let Ok(len) = reader.read_line(&mut buffer) {
-                if len == 0 {
-                    break;
-                } else if let Ok(msg) = CString::new(buffer.clone()) {
-                    android_log(Level::Info, tag, &msg);
-                }
-            }
-        }
-    });
-
+// by android_native_app_glue, via the `_rust_glue_entry` function that's
+// exported by the application (see `export_main!`).
+pub(crate) unsafe fn glue_entry(app: *mut ffi::android_app, entry: &MainEntry) {
     let app = AndroidApp::from_ptr(NonNull::new(app).unwrap());
 
     let na = app.native_activity();
@@ -486,7 +847,17 @@ pub unsafe extern "C" fn _rust_glue_entry(app: *mut ffi::android_app) {
     // when calling FindClass to lookup a suitable classLoader, instead of
     // defaulting to the system loader. Without this then it's difficult for native
     // code to look up non-standard Java classes.
-    android_main(app);
+    //
+    // A panic must not unwind into the C glue code that called us, so it's caught
+    // here and the Activity is finished instead of being left running without an
+    // `android_main` to handle its events.
+    let main_app = app.clone();
+    if std::panic::catch_unwind(AssertUnwindSafe(|| entry.call(main_app))).is_err() {
+        let tag = CString::new("android-activity").unwrap();
+        let msg = CString::new("android_main panicked, finishing the Activity").unwrap();
+        util::android_log(Level::Error, &tag, &msg);
+        entry::finish_and_wait_for_destroy(&app);
+    }
 
     // Since this is a newly spawned thread then the JVM hasn't been attached
     // to the thread yet. Attach before calling the applications main function
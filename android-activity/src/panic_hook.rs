@@ -0,0 +1,132 @@
+//! A panic hook that reports panics to logcat, since the output of the default
+//! hook (written to stderr) is discarded on Android
+
+use std::backtrace::Backtrace;
+use std::ffi::CString;
+use std::fs;
+use std::io::{self, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::Level;
+
+use crate::util::android_log_str;
+use crate::AndroidApp;
+
+/// Configures a panic hook that logs panics, along with a backtrace, to logcat
+///
+/// The hook can also write a crash report for each panic to a `crashes/` directory
+/// under [`AndroidApp::internal_data_path()`], so that it can be uploaded the next
+/// time the application runs.
+///
+/// Regardless of whether a hook is installed, if `android_main` panics then the panic
+/// is caught before it can unwind into the native glue code and the `Activity` is
+/// finished, instead of being left without a running `android_main`.
+///
+/// ```ignore
+/// #[android_activity::main(panic_hook = false)]
+/// fn android_main(app: AndroidApp) {
+///     PanicHook::new().with_tag("MyApp").install(&app);
+///     ...
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PanicHook {
+    tag: String,
+    crash_reports: bool,
+    chain: bool,
+}
+
+impl Default for PanicHook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PanicHook {
+    /// Creates a hook that logs with a `RustPanic` tag and writes crash reports
+    pub fn new() -> Self {
+        Self {
+            tag: "RustPanic".to_string(),
+            crash_reports: true,
+            chain: false,
+        }
+    }
+
+    /// Sets the logcat tag for panic messages
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = tag.to_string();
+        self
+    }
+
+    /// Sets whether a crash report is written for each panic
+    pub fn with_crash_reports(mut self, enabled: bool) -> Self {
+        self.crash_reports = enabled;
+        self
+    }
+
+    /// Sets whether the previously installed panic hook (e.g. the default hook, which
+    /// writes to stderr) is also called for each panic
+    pub fn with_previous_hook(mut self, chain: bool) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    /// Installs this hook as the process-wide panic hook, via [`std::panic::set_hook()`]
+    ///
+    /// The hook applies to panics on any thread.
+    pub fn install(self, app: &AndroidApp) {
+        let crash_dir = if self.crash_reports {
+            app.internal_data_path().map(|path| path.join("crashes"))
+        } else {
+            None
+        };
+        let tag = CString::new(self.tag.replace('\0', "")).unwrap();
+        let previous = self.chain.then(panic::take_hook);
+
+        panic::set_hook(Box::new(move |info| {
+            let payload = info.payload();
+            let msg = payload.downcast_ref::<&str>().copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("Box<dyn Any>");
+            let thread = thread::current();
+            let thread = thread.name().unwrap_or("<unnamed>");
+            let location = info.location().map(|l| l.to_string()).unwrap_or_default();
+            let report = format!(
+                "thread '{thread}' panicked at {location}:\n{msg}\n\nstack backtrace:\n{}",
+                Backtrace::force_capture()
+            );
+
+            // One message per line, so that a long backtrace isn't truncated by logcat
+            for line in report.lines() {
+                android_log_str(Level::Error, &tag, line);
+            }
+
+            if let Some(dir) = &crash_dir {
+                match write_crash_report(dir, &report) {
+                    Ok(path) => android_log_str(Level::Error, &tag, &format!("Wrote crash report to {}", path.display())),
+                    Err(err) => android_log_str(Level::Error, &tag, &format!("Failed to write crash report to {}: {err}", dir.display())),
+                }
+            }
+
+            if let Some(previous) = &previous {
+                previous(info);
+            }
+        }));
+    }
+}
+
+fn write_crash_report(dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let path = dir.join(format!("crash-{}.txt", time.as_millis()));
+    let mut file = fs::File::create(&path)?;
+    writeln!(file, "time: {}", time.as_secs())?;
+    writeln!(file, "android-activity: {}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(file)?;
+    file.write_all(report.as_bytes())?;
+    file.sync_all()?;
+    Ok(path)
+}
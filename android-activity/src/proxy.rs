@@ -0,0 +1,113 @@
+//! Typed user events that can be sent to the main loop from other threads
+
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use crate::AndroidAppWaker;
+
+/// The queue of user events that have been sent via an [`EventProxy`] but not
+/// yet delivered by [`AndroidApp::poll_events()`](crate::AndroidApp::poll_events)
+///
+/// A single queue is shared by all proxies (of any type) so that events are
+/// delivered in the same order they were sent.
+#[derive(Debug, Default)]
+pub(crate) struct UserEventQueue {
+    events: Mutex<VecDeque<UserEvent>>,
+}
+
+impl UserEventQueue {
+    pub(crate) fn pop(&self) -> Option<UserEvent> {
+        self.events.lock().unwrap().pop_front()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.events.lock().unwrap().is_empty()
+    }
+}
+
+/// A user event, sent via an [`EventProxy`] and delivered as a
+/// [`PollEvent::User`](crate::PollEvent::User)
+///
+/// Since the [`PollEvent`](crate::PollEvent) type isn't generic, the event
+/// is type erased and can be taken by the application via [`UserEvent::take()`]
+pub struct UserEvent {
+    payload: Cell<Option<Box<dyn Any + Send>>>,
+}
+
+impl UserEvent {
+    /// Returns `true` if the event has a payload of type `T` that hasn't been taken yet
+    pub fn is<T: Send + 'static>(&self) -> bool {
+        let payload = self.payload.take();
+        let is = matches!(&payload, Some(payload) if payload.is::<T>());
+        self.payload.set(payload);
+        is
+    }
+
+    /// Takes the event's payload, if it has type `T`
+    ///
+    /// Returns `None` if the payload has a different type or has already been taken.
+    pub fn take<T: Send + 'static>(&self) -> Option<T> {
+        match self.payload.take()?.downcast::<T>() {
+            Ok(payload) => Some(*payload),
+            Err(payload) => {
+                self.payload.set(Some(payload));
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Debug for UserEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserEvent").finish_non_exhaustive()
+    }
+}
+
+/// A handle for sending user events of type `T` to the application's main loop, from any thread
+///
+/// Created via [`AndroidApp::create_proxy()`](crate::AndroidApp::create_proxy)
+pub struct EventProxy<T> {
+    queue: Arc<UserEventQueue>,
+    waker: AndroidAppWaker,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T: Send + 'static> EventProxy<T> {
+    pub(crate) fn new(queue: Arc<UserEventQueue>, waker: AndroidAppWaker) -> Self {
+        Self {
+            queue,
+            waker,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Queues an event and wakes up the main loop
+    ///
+    /// The event will be delivered as a [`PollEvent::User`](crate::PollEvent::User)
+    /// by a subsequent call to [`AndroidApp::poll_events()`](crate::AndroidApp::poll_events)
+    pub fn send(&self, event: T) {
+        let event = UserEvent { payload: Cell::new(Some(Box::new(event))) };
+        self.queue.events.lock().unwrap().push_back(event);
+        self.waker.wake();
+    }
+}
+
+impl<T> Clone for EventProxy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+            waker: self.waker.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for EventProxy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventProxy").field("type", &std::any::type_name::<T>()).finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,566 @@
+//! Recording of the events delivered by [`AndroidApp::poll_events()`] and
+//! [`AndroidApp::input_events()`], so they can be replayed later
+//!
+//! A recording is a line-based text file that starts with a header line giving
+//! the format version:
+//!
+//! ```text
+//! android-activity-recording 1
+//! 0 config density=420 orientation=1 screen_width_dp=411 ... sdk_version=30
+//! 0 state lifecycle=Resumed focus=1 window=1080,2340,1
+//! 1520331 main Pause
+//! 1873420 motion action=0 index=0 source=4098 ... p=0:0=530.5,1=1204,2=1 offset=0,0 h=123 hp=0:0=529,1=1200
+//! ```
+//!
+//! Each subsequent line starts with a timestamp, in nanoseconds since the recording
+//! was started, followed by the kind of record and then `key=value` fields. The
+//! first `config` and `state` records are a snapshot of the application's state when
+//! the recording was started.
+//!
+//! Motion events list each pointer as `p=<id>:<axis>=<value>,...` (only including
+//! non-zero axis values) and each historical sample as `h=<event_time>` followed by
+//! its pointers as `hp=...` fields. With the `game-activity` backend the raw `offset`
+//! and the history of motion events aren't available, so they aren't recorded.
+//!
+//! Only events that originate from the `Activity` are recorded. Events from sources
+//! that the application owns (wake ups, timeouts, file descriptors, timers and user
+//! events) aren't recorded and nor is any saved state. Text input and editor action
+//! events aren't recorded yet either.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use log::error;
+
+use crate::input::{self, Axis};
+use crate::{AndroidApp, Configuration, LifecycleState, MainEvent, MemoryTrimLevel};
+
+const MAGIC: &str = "android-activity-recording";
+
+/// The version of the recording format that's written by [`AndroidApp::start_recording()`]
+pub const RECORDING_VERSION: u32 = 1;
+
+// The highest AMOTION_EVENT_AXIS_* value (AMOTION_EVENT_AXIS_GENERIC_16)
+const MAX_AXIS: u32 = 47;
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_else(|| "none".to_string())
+}
+
+fn config_fields(config: &Configuration) -> String {
+    format!(
+        "density={} orientation={} screen_width_dp={} screen_height_dp={} smallest_screen_width_dp={} \
+         language={} country={} layout_direction={} ui_mode_type={} ui_mode_night={} sdk_version={}",
+        opt(config.density()),
+        config.orientation() as u32,
+        opt(config.screen_width_dp()),
+        opt(config.screen_height_dp()),
+        opt(config.smallest_screen_width_dp()),
+        opt(config.language()),
+        opt(config.country()),
+        config.layout_direction() as u32,
+        config.ui_mode_type() as u32,
+        config.ui_mode_night() as u32,
+        config.sdk_version(),
+    )
+}
+
+// Only covers the events that correspond to a command from the Java main thread
+fn main_event_name(event: &MainEvent) -> Option<&'static str> {
+    let name = match event {
+        MainEvent::InitWindow { .. } => "InitWindow",
+        MainEvent::TerminateWindow { .. } => "TerminateWindow",
+        MainEvent::WindowResized { .. } => "WindowResized",
+        MainEvent::RedrawNeeded { .. } => "RedrawNeeded",
+        MainEvent::ContentRectChanged { .. } => "ContentRectChanged",
+        MainEvent::GainedFocus => "GainedFocus",
+        MainEvent::LostFocus => "LostFocus",
+        MainEvent::ConfigChanged => "ConfigChanged",
+        MainEvent::TrimMemory { .. } => "TrimMemory",
+        MainEvent::Start => "Start",
+        MainEvent::Resume { .. } => "Resume",
+        MainEvent::SaveState { .. } => "SaveState",
+        MainEvent::Pause => "Pause",
+        MainEvent::Stop => "Stop",
+        MainEvent::Destroy => "Destroy",
+        MainEvent::InsetsChanged { .. } => "InsetsChanged",
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn pointer_fields(key: &str, id: i32, axis_value: impl Fn(Axis) -> f32) -> String {
+    let axes: Vec<String> = (0..=MAX_AXIS)
+        .filter_map(|axis| Axis::try_from(axis).ok())
+        .filter_map(|axis| {
+            let value = axis_value(axis);
+            // X and Y are always recorded, even if zero
+            if value != 0.0 || matches!(axis, Axis::X | Axis::Y) {
+                Some(format!("{}={value}", axis as u32))
+            } else {
+                None
+            }
+        })
+        .collect();
+    format!(" {key}={id}:{}", axes.join(","))
+}
+
+/// Writes a recording, while [`AndroidApp::start_recording()`] is active
+#[derive(Debug)]
+pub(crate) struct Recorder {
+    out: BufWriter<File>,
+    path: PathBuf,
+    start: Instant,
+}
+
+impl Recorder {
+    /// Creates a new recording under `dir`, starting with a snapshot of the application's state
+    pub(crate) fn create(dir: &Path, app: &AndroidApp) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let path = dir.join(format!("recording-{millis}.txt"));
+        let mut recorder = Self {
+            out: BufWriter::new(File::create(&path)?),
+            path,
+            start: Instant::now(),
+        };
+
+        writeln!(recorder.out, "{MAGIC} {RECORDING_VERSION}")?;
+        recorder.record("config", &config_fields(&app.config()))?;
+        let mut state = format!("lifecycle={:?} focus={}", app.lifecycle_state(), app.has_focus() as u8);
+        if let Some(window) = app.native_window() {
+            state += &format!(" window={},{},{}", window.width(), window.height(), window.format() as u32);
+        }
+        recorder.record("state", &state)?;
+        Ok(recorder)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn record(&mut self, kind: &str, fields: &str) -> io::Result<()> {
+        let time = self.start.elapsed().as_nanos();
+        if fields.is_empty() {
+            writeln!(self.out, "{time} {kind}")
+        } else {
+            writeln!(self.out, "{time} {kind} {fields}")
+        }
+    }
+
+    /// Records a main event, after the backend's `pre_exec_cmd()` has updated any
+    /// associated state (such as the window or config)
+    pub(crate) fn record_main(&mut self, event: &MainEvent, app: &AndroidApp) -> io::Result<()> {
+        let name = match main_event_name(event) {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let mut fields = name.to_string();
+        match event {
+            MainEvent::InitWindow { info, .. } | MainEvent::WindowResized { new: info, .. } => {
+                fields += &format!(" width={} height={} format={}", info.width, info.height, info.format as u32);
+            }
+            MainEvent::ContentRectChanged { new: rect, .. } => {
+                fields += &format!(" left={} top={} right={} bottom={}", rect.left, rect.top, rect.right, rect.bottom);
+            }
+            MainEvent::ConfigChanged => {
+                fields += " ";
+                fields += &config_fields(&app.config());
+            }
+            MainEvent::TrimMemory { level, .. } => {
+                fields += &format!(" level={}", *level as i32);
+            }
+            // Each changed type of insets is recorded as `<type>=<left>,<top>,<right>,<bottom>`
+            MainEvent::InsetsChanged { changed, .. } => {
+                for ty in changed {
+                    let rect = app.window_insets(*ty);
+                    fields += &format!(" {}={},{},{},{}", *ty as u32, rect.left, rect.top, rect.right, rect.bottom);
+                }
+            }
+            _ => {}
+        }
+        self.record("main", &fields)
+    }
+
+    pub(crate) fn record_input(&mut self, event: &input::InputEvent) -> io::Result<()> {
+        match event {
+            input::InputEvent::MotionEvent(event) => {
+                let mut fields = format!(
+                    "action={} index={} source={} device={} meta={} buttons={} edge={} flags={} down={} time={} precision={},{}",
+                    event.action() as u32, event.pointer_index(), event.source() as u32, event.device_id(),
+                    event.meta_state().0, event.button_state().0, event.edge_flags().0, event.flags().0,
+                    event.down_time(), event.event_time(), event.x_precision(), event.y_precision(),
+                );
+                for pointer in event.pointers() {
+                    fields += &pointer_fields("p", pointer.pointer_id(), |axis| pointer.axis_value(axis));
+                }
+                // GameActivity doesn't currently expose the raw offsets or history of motion events
+                #[cfg(not(feature = "game-activity"))]
+                {
+                    fields += &format!(" offset={},{}", event.x_offset(), event.y_offset());
+                    for historical in event.history() {
+                        fields += &format!(" h={}", historical.event_time());
+                        for pointer in historical.pointers() {
+                            fields += &pointer_fields("hp", pointer.pointer_id(), |axis| pointer.axis_value(axis));
+                        }
+                    }
+                }
+                self.record("motion", &fields)
+            }
+            input::InputEvent::KeyEvent(event) => {
+                let fields = format!(
+                    "action={} source={} device={} code={} scan={} repeat={} meta={} flags={} down={} time={}",
+                    event.action() as u32, event.source() as u32, event.device_id(), event.key_code() as u32,
+                    event.scan_code(), event.repeat_count(), event.meta_state().0, event.flags().0,
+                    event.down_time(), event.event_time(),
+                );
+                self.record("key", &fields)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> io::Result<PathBuf> {
+        self.out.flush()?;
+        Ok(self.path)
+    }
+}
+
+/// Records an event via the application's recorder, if one is active
+///
+/// Recording stops (with an error logged) if the recording can't be written.
+pub(crate) fn record_with(app: &AndroidApp, f: impl FnOnce(&mut Recorder) -> io::Result<()>) {
+    let mut recorder = app.inner.recorder().lock().unwrap();
+    if let Some(active) = recorder.as_mut() {
+        if let Err(err) = f(active) {
+            error!("Failed to write to recording {:?}, stopping: {err}", active.path());
+            *recorder = None;
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PointerRecord {
+    pub(crate) id: i32,
+    pub(crate) axes: Vec<(u32, f32)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MotionRecord {
+    pub(crate) action: u32,
+    pub(crate) pointer_index: usize,
+    pub(crate) source: u32,
+    pub(crate) device_id: i32,
+    pub(crate) meta_state: u32,
+    pub(crate) button_state: u32,
+    pub(crate) edge_flags: u32,
+    pub(crate) flags: u32,
+    pub(crate) down_time: i64,
+    pub(crate) event_time: i64,
+    pub(crate) offset: (f32, f32),
+    pub(crate) precision: (f32, f32),
+    pub(crate) pointers: Vec<PointerRecord>,
+    pub(crate) history: Vec<(i64, Vec<PointerRecord>)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct KeyRecord {
+    pub(crate) action: u32,
+    pub(crate) source: u32,
+    pub(crate) device_id: i32,
+    pub(crate) key_code: u32,
+    pub(crate) scan_code: i32,
+    pub(crate) repeat_count: i32,
+    pub(crate) meta_state: u32,
+    pub(crate) flags: u32,
+    pub(crate) down_time: i64,
+    pub(crate) event_time: i64,
+}
+
+/// The recorded fields of a `Configuration`, with enums as their raw values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ConfigRecord {
+    pub(crate) density: Option<u32>,
+    pub(crate) orientation: u32,
+    pub(crate) screen_width_dp: Option<i32>,
+    pub(crate) screen_height_dp: Option<i32>,
+    pub(crate) smallest_screen_width_dp: Option<i32>,
+    pub(crate) language: Option<String>,
+    pub(crate) country: Option<String>,
+    pub(crate) layout_direction: u32,
+    pub(crate) ui_mode_type: u32,
+    pub(crate) ui_mode_night: u32,
+    pub(crate) sdk_version: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Record {
+    Config(ConfigRecord),
+    State { lifecycle: LifecycleState, focused: bool, window: Option<(i32, i32, u32)> },
+    InitWindow { width: i32, height: i32, format: u32 },
+    WindowResized { width: i32, height: i32 },
+    ContentRectChanged { left: i32, top: i32, right: i32, bottom: i32 },
+    ConfigChanged(ConfigRecord),
+    InsetsChanged(Vec<(u32, [i32; 4])>),
+    TrimMemory { level: i32 },
+    /// Any other main event, without associated state
+    Main(String),
+    Motion(MotionRecord),
+    Key(KeyRecord),
+}
+
+/// A recording that was written via [`AndroidApp::start_recording()`]
+///
+/// With the `mock-activity` backend a recording can be replayed via
+/// `MockActivity::replay()`.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    pub(crate) records: Vec<(Duration, Record)>,
+}
+
+struct Fields<'a> {
+    fields: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Fields<'a> {
+    fn parse(tokens: impl Iterator<Item = &'a str>) -> io::Result<Self> {
+        let fields = tokens
+            .map(|token| token.split_once('=').ok_or_else(|| invalid(format!("Malformed field {token:?}"))))
+            .collect::<io::Result<_>>()?;
+        Ok(Self { fields })
+    }
+
+    fn raw(&self, key: &str) -> io::Result<&'a str> {
+        self.fields.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+            .ok_or_else(|| invalid(format!("Missing field {key:?}")))
+    }
+
+    fn get<T: std::str::FromStr>(&self, key: &str) -> io::Result<T> {
+        let value = self.raw(key)?;
+        value.parse().map_err(|_| invalid(format!("Invalid value for {key:?}: {value:?}")))
+    }
+
+    fn opt<T: std::str::FromStr>(&self, key: &str) -> io::Result<Option<T>> {
+        match self.raw(key)? {
+            "none" => Ok(None),
+            _ => self.get(key).map(Some),
+        }
+    }
+
+    fn pair<T: std::str::FromStr>(&self, key: &str) -> io::Result<(T, T)> {
+        let value = self.raw(key)?;
+        value.split_once(',')
+            .and_then(|(a, b)| Some((a.parse().ok()?, b.parse().ok()?)))
+            .ok_or_else(|| invalid(format!("Invalid value for {key:?}: {value:?}")))
+    }
+}
+
+fn parse_pointer(value: &str) -> io::Result<PointerRecord> {
+    let malformed = || invalid(format!("Malformed pointer {value:?}"));
+    let (id, axes) = value.split_once(':').ok_or_else(malformed)?;
+    let axes = axes.split(',')
+        .filter(|axis| !axis.is_empty())
+        .map(|axis| {
+            let (axis, value) = axis.split_once('=')?;
+            Some((axis.parse().ok()?, value.parse().ok()?))
+        })
+        .collect::<Option<_>>()
+        .ok_or_else(malformed)?;
+    Ok(PointerRecord { id: id.parse().map_err(|_| malformed())?, axes })
+}
+
+fn parse_config(fields: &Fields) -> io::Result<ConfigRecord> {
+    Ok(ConfigRecord {
+        density: fields.opt("density")?,
+        orientation: fields.get("orientation")?,
+        screen_width_dp: fields.opt("screen_width_dp")?,
+        screen_height_dp: fields.opt("screen_height_dp")?,
+        smallest_screen_width_dp: fields.opt("smallest_screen_width_dp")?,
+        language: fields.opt("language")?,
+        country: fields.opt("country")?,
+        layout_direction: fields.get("layout_direction")?,
+        ui_mode_type: fields.get("ui_mode_type")?,
+        ui_mode_night: fields.get("ui_mode_night")?,
+        sdk_version: fields.get("sdk_version")?,
+    })
+}
+
+fn parse_lifecycle(value: &str) -> io::Result<LifecycleState> {
+    let states = HashMap::from([
+        ("Created", LifecycleState::Created),
+        ("Started", LifecycleState::Started),
+        ("Resumed", LifecycleState::Resumed),
+        ("Paused", LifecycleState::Paused),
+        ("Stopped", LifecycleState::Stopped),
+        ("Destroying", LifecycleState::Destroying),
+    ]);
+    states.get(value).copied().ok_or_else(|| invalid(format!("Unknown lifecycle state {value:?}")))
+}
+
+fn parse_record(kind: &str, mut tokens: std::str::SplitWhitespace) -> io::Result<Option<Record>> {
+    let record = match kind {
+        "config" => Record::Config(parse_config(&Fields::parse(tokens)?)?),
+        "state" => {
+            let fields = Fields::parse(tokens)?;
+            let window = match fields.raw("window") {
+                Ok(window) => {
+                    let parts: Vec<&str> = window.split(',').collect();
+                    match parts[..] {
+                        [w, h, f] => Some((
+                            w.parse().map_err(|_| invalid("Invalid window width"))?,
+                            h.parse().map_err(|_| invalid("Invalid window height"))?,
+                            f.parse().map_err(|_| invalid("Invalid window format"))?,
+                        )),
+                        _ => return Err(invalid(format!("Malformed window {window:?}"))),
+                    }
+                }
+                Err(_) => None,
+            };
+            Record::State {
+                lifecycle: parse_lifecycle(fields.raw("lifecycle")?)?,
+                focused: fields.get::<u8>("focus")? != 0,
+                window,
+            }
+        }
+        "main" => {
+            let name = tokens.next().ok_or_else(|| invalid("Missing main event name"))?;
+            let fields = Fields::parse(tokens)?;
+            match name {
+                "InitWindow" => Record::InitWindow {
+                    width: fields.get("width")?,
+                    height: fields.get("height")?,
+                    format: fields.get("format")?,
+                },
+                "WindowResized" => Record::WindowResized { width: fields.get("width")?, height: fields.get("height")? },
+                "ContentRectChanged" => Record::ContentRectChanged {
+                    left: fields.get("left")?,
+                    top: fields.get("top")?,
+                    right: fields.get("right")?,
+                    bottom: fields.get("bottom")?,
+                },
+                "ConfigChanged" => Record::ConfigChanged(parse_config(&fields)?),
+                "TrimMemory" => Record::TrimMemory { level: fields.get("level")? },
+                // Recorded before trim memory levels were reported
+                "LowMemory" => Record::TrimMemory { level: MemoryTrimLevel::Complete as i32 },
+                "InsetsChanged" => Record::InsetsChanged(fields.fields.iter().map(|(ty, rect)| {
+                    let malformed = || invalid(format!("Malformed insets {ty}={rect}"));
+                    let rect: Vec<i32> = rect.split(',').map(|v| v.parse().map_err(|_| malformed())).collect::<io::Result<_>>()?;
+                    Ok((ty.parse().map_err(|_| malformed())?, <[i32; 4]>::try_from(rect).map_err(|_| malformed())?))
+                }).collect::<io::Result<_>>()?),
+                name => Record::Main(name.to_string()),
+            }
+        }
+        "motion" => {
+            let fields = Fields::parse(tokens)?;
+            let mut pointers = vec![];
+            let mut history: Vec<(i64, Vec<PointerRecord>)> = vec![];
+            for (key, value) in &fields.fields {
+                match *key {
+                    "p" => pointers.push(parse_pointer(value)?),
+                    "h" => history.push((value.parse().map_err(|_| invalid(format!("Invalid history time {value:?}")))?, vec![])),
+                    "hp" => history.last_mut()
+                        .ok_or_else(|| invalid("Historical pointer without a history sample"))?
+                        .1.push(parse_pointer(value)?),
+                    _ => {}
+                }
+            }
+            Record::Motion(MotionRecord {
+                action: fields.get("action")?,
+                pointer_index: fields.get("index")?,
+                source: fields.get("source")?,
+                device_id: fields.get("device")?,
+                meta_state: fields.get("meta")?,
+                button_state: fields.get("buttons")?,
+                edge_flags: fields.get("edge")?,
+                flags: fields.get("flags")?,
+                down_time: fields.get("down")?,
+                event_time: fields.get("time")?,
+                offset: if fields.raw("offset").is_ok() { fields.pair("offset")? } else { (0.0, 0.0) },
+                precision: fields.pair("precision")?,
+                pointers,
+                history,
+            })
+        }
+        "key" => {
+            let fields = Fields::parse(tokens)?;
+            Record::Key(KeyRecord {
+                action: fields.get("action")?,
+                source: fields.get("source")?,
+                device_id: fields.get("device")?,
+                key_code: fields.get("code")?,
+                scan_code: fields.get("scan")?,
+                repeat_count: fields.get("repeat")?,
+                meta_state: fields.get("meta")?,
+                flags: fields.get("flags")?,
+                down_time: fields.get("down")?,
+                event_time: fields.get("time")?,
+            })
+        }
+        // Skip unknown records, in case they're added without bumping the version
+        _ => return Ok(None),
+    };
+    Ok(Some(record))
+}
+
+impl Recording {
+    /// Loads a recording from a file
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a recording from its text representation
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the recording is
+    /// malformed or has an unsupported version.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        match header.split_once(' ') {
+            Some((MAGIC, version)) => {
+                let version: u32 = version.trim().parse().map_err(|_| invalid(format!("Invalid recording version {version:?}")))?;
+                if version > RECORDING_VERSION {
+                    return Err(invalid(format!("Unsupported recording version {version}")));
+                }
+            }
+            _ => return Err(invalid("Not an android-activity recording")),
+        }
+
+        let mut records = vec![];
+        for (i, line) in lines.enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let with_line = |err: io::Error| invalid(format!("Line {}: {err}", i + 2));
+            let mut tokens = line.split_whitespace();
+            let time = tokens.next()
+                .and_then(|time| time.parse::<u64>().ok())
+                .ok_or_else(|| with_line(invalid("Invalid timestamp")))?;
+            let kind = tokens.next().ok_or_else(|| with_line(invalid("Missing record kind")))?;
+            if let Some(record) = parse_record(kind, tokens).map_err(with_line)? {
+                records.push((Duration::from_nanos(time), record));
+            }
+        }
+        Ok(Self { records })
+    }
+
+    /// The number of recorded events (including the initial state snapshot)
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if nothing was recorded
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The time of the last recorded event, relative to the start of the recording
+    pub fn duration(&self) -> Duration {
+        self.records.last().map(|(time, _)| *time).unwrap_or_default()
+    }
+}
@@ -0,0 +1,182 @@
+//! Redirection of stdout and stderr to logcat
+//!
+//! On Android, anything written to stdout or stderr is discarded by default, which
+//! means the output of `println!()`, `dbg!()` and the default panic message is lost.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::Level;
+
+#[cfg(not(feature = "mock-activity"))]
+use std::{
+    ffi::CString,
+    fs::File,
+    io::{BufRead, BufReader},
+    os::unix::prelude::*,
+    thread,
+};
+
+use crate::util::MAX_LOG_MESSAGE_LEN;
+#[cfg(not(feature = "mock-activity"))]
+use crate::util::{android_log, split_log_message};
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Configures the redirection of stdout and stderr to logcat
+///
+/// Nothing is redirected unless a configuration is installed via
+/// [`install()`](Self::install), which should be done before `android_main` produces
+/// any output since output that was written before the redirection was installed is
+/// lost. The `#[android_activity::main]` attribute macro (with the `macros` feature)
+/// installs a redirection before calling `android_main`, which is configured via
+/// its `stdio`, `stdio_tag` and `stderr_level` arguments.
+///
+/// Each line that's written to a redirected stream becomes a separate logcat message,
+/// and lines that are longer than the maximum length of a logcat message are split
+/// into multiple messages.
+///
+/// ```ignore
+/// StdioRedirect::new()
+///     .with_tag("MyApp")
+///     .with_stderr_level(Some(log::Level::Warn))
+///     .install()
+///     .expect("Failed to redirect stdio");
+/// ```
+///
+/// With the `mock-activity` backend stdout and stderr are left untouched, since
+/// they're already visible on the host.
+#[derive(Clone, Debug)]
+pub struct StdioRedirect {
+    tag: String,
+    stdout_level: Option<Level>,
+    stderr_level: Option<Level>,
+    max_line_len: usize,
+}
+
+impl Default for StdioRedirect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StdioRedirect {
+    /// Creates a configuration that redirects both stdout and stderr at
+    /// [`Level::Info`] with a `RustStdoutStderr` tag
+    pub fn new() -> Self {
+        Self {
+            tag: "RustStdoutStderr".to_string(),
+            stdout_level: Some(Level::Info),
+            stderr_level: Some(Level::Info),
+            max_line_len: MAX_LOG_MESSAGE_LEN,
+        }
+    }
+
+    /// Sets the logcat tag for redirected output
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = tag.to_string();
+        self
+    }
+
+    /// Sets the priority for lines written to stdout, or `None` to leave stdout untouched
+    pub fn with_stdout_level(mut self, level: Option<Level>) -> Self {
+        self.stdout_level = level;
+        self
+    }
+
+    /// Sets the priority for lines written to stderr, or `None` to leave stderr untouched
+    pub fn with_stderr_level(mut self, level: Option<Level>) -> Self {
+        self.stderr_level = level;
+        self
+    }
+
+    /// Sets the maximum length, in bytes, of each logcat message
+    ///
+    /// Longer lines are split into multiple messages (without splitting any UTF-8
+    /// characters). The length is capped at the logcat limit of about 4000 bytes,
+    /// which is also the default.
+    pub fn with_max_line_len(mut self, len: usize) -> Self {
+        self.max_line_len = len.min(MAX_LOG_MESSAGE_LEN);
+        self
+    }
+
+    /// Redirects stdout and/or stderr according to this configuration
+    ///
+    /// Each redirected stream is replaced by a pipe whose output is forwarded to
+    /// logcat by a background thread.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::AlreadyExists`] if a redirection has
+    /// already been installed, or [`io::ErrorKind::InvalidInput`] if the tag contains
+    /// a NUL byte.
+    pub fn install(self) -> io::Result<()> {
+        if self.tag.contains('\0') {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Logcat tag contains a NUL byte"));
+        }
+        if INSTALLED.swap(true, Ordering::SeqCst) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "Stdio redirection has already been installed"));
+        }
+
+        #[cfg(not(feature = "mock-activity"))]
+        {
+            if let Some(level) = self.stdout_level {
+                redirect(libc::STDOUT_FILENO, "stdout", level, &self.tag, self.max_line_len)?;
+            }
+            if let Some(level) = self.stderr_level {
+                redirect(libc::STDERR_FILENO, "stderr", level, &self.tag, self.max_line_len)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "mock-activity"))]
+fn redirect(fd: RawFd, name: &str, level: Level, tag: &str, max_line_len: usize) -> io::Result<()> {
+    let mut pipe: [RawFd; 2] = Default::default();
+    unsafe {
+        if libc::pipe2(pipe.as_mut_ptr(), libc::O_CLOEXEC) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let ret = libc::dup2(pipe[1], fd);
+        // The write end stays open as `fd`
+        libc::close(pipe[1]);
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            libc::close(pipe[0]);
+            return Err(err);
+        }
+    }
+
+    let reader = BufReader::new(unsafe { File::from_raw_fd(pipe[0]) });
+    let tag = CString::new(tag).unwrap();
+    thread::Builder::new()
+        .name(format!("{name} to logcat"))
+        .spawn(move || forward_lines(reader, level, &tag, max_line_len))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "mock-activity"))]
+fn forward_lines(mut reader: BufReader<File>, level: Level, tag: &CString, max_line_len: usize) {
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+
+        // Logcat messages are C strings, so any NUL bytes are dropped
+        line.retain(|b| *b != 0);
+        let line = String::from_utf8_lossy(&line);
+        if line.is_empty() {
+            android_log(level, tag, &CString::default());
+        }
+        for chunk in split_log_message(&line, max_line_len) {
+            android_log(level, tag, &CString::new(chunk).unwrap());
+        }
+    }
+}
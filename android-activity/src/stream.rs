@@ -0,0 +1,287 @@
+#![cfg(feature = "async")]
+
+//! An async alternative to [`AndroidApp::poll_events()`]
+//!
+//! [`AndroidApp::events()`] returns a [`Stream`] of [`EventGuard`]s and
+//! [`AndroidApp::block_on()`] is a minimal executor that blocks on the
+//! application's looper while the future it's running is pending.
+//!
+//! The looper is only ever polled by [`AndroidApp::block_on()`] and any
+//! events that are read are queued until they are taken from an [`EventStream`].
+//! The synchronization with the Java main thread that's normally done either
+//! side of the [`AndroidApp::poll_events()`] callback is tied to the lifetime
+//! of the [`EventGuard`] instead: the equivalent of `android_app_pre_exec_cmd`
+//! is called before a guard is returned by the stream and the equivalent of
+//! `android_app_post_exec_cmd` is called when the guard is dropped.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, ThreadId};
+
+use futures_core::Stream;
+use log::trace;
+
+use crate::{AndroidApp, AndroidAppWaker, PollError, PollEvent, RawPollEvent};
+
+// Since the looper may only be polled from the application's main thread
+// then the queue of events waiting to be taken from an EventStream is
+// thread local.
+#[derive(Default)]
+struct EventQueue {
+    pending: VecDeque<RawPollEvent>,
+    stream_waker: Option<Waker>,
+    in_flight: bool,
+    executor_active: bool,
+}
+
+thread_local! {
+    static EVENT_QUEUE: RefCell<EventQueue> = RefCell::new(EventQueue::default());
+}
+
+/// Takes an event that was read from the looper by [`AndroidApp::block_on()`] but
+/// never taken from an [`EventStream`], so it can be dispatched via `poll_events()`
+pub(crate) fn take_pending() -> Option<RawPollEvent> {
+    EVENT_QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        if queue.in_flight {
+            None
+        } else {
+            queue.pending.pop_front()
+        }
+    })
+}
+
+fn wake_stream() {
+    // Note: we make sure not to hold a borrow of the queue while calling into
+    // an arbitrary waker
+    let waker = EVENT_QUEUE.with(|queue| queue.borrow_mut().stream_waker.take());
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+/// A [`Stream`] of application events, returned by [`AndroidApp::events()`]
+///
+/// This stream must be polled from within [`AndroidApp::block_on()`], which is
+/// responsible for reading events from the application's looper.
+///
+/// Only one [`EventGuard`] can be alive at a time, and the stream won't return
+/// any more events until the current guard has been dropped.
+#[derive(Debug)]
+pub struct EventStream {
+    app: AndroidApp,
+}
+
+impl Stream for EventStream {
+    type Item = EventGuard;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let raw = EVENT_QUEUE.with(|queue| {
+                let mut queue = queue.borrow_mut();
+                if !queue.executor_active {
+                    log::warn!("EventStream polled outside of AndroidApp::block_on(); no events will be delivered");
+                }
+                let raw = if queue.in_flight { None } else { queue.pending.pop_front() };
+                if raw.is_none() {
+                    queue.stream_waker = Some(cx.waker().clone());
+                }
+                raw
+            });
+            let raw = match raw {
+                Some(raw) => raw,
+                None => return Poll::Pending,
+            };
+
+            if let RawPollEvent::Main(cmd) = &raw {
+                self.app.pre_exec_cmd(cmd);
+            }
+
+            let raw = match raw.poll_event(&self.app.inner) {
+                Ok(Some(_)) => raw,
+                result => {
+                    // Either an internal command that's not forwarded to the application
+                    // or a command that couldn't be mapped to an event
+                    if let RawPollEvent::Main(cmd) = &raw {
+                        self.app.inner.post_exec_cmd(cmd);
+                    }
+                    match result {
+                        Err(err) => RawPollEvent::Error(err),
+                        _ => continue,
+                    }
+                }
+            };
+
+            EVENT_QUEUE.with(|queue| queue.borrow_mut().in_flight = true);
+            return Poll::Ready(Some(EventGuard { app: self.app.clone(), raw }));
+        }
+    }
+}
+
+/// An event returned by an [`EventStream`]
+///
+/// For a [`PollEvent::Main`] event the Java main thread may be blocked waiting
+/// for the event to be handled until this guard is dropped. For example, the
+/// [`NativeWindow`](crate::NativeWindowRef) is accessible while handling
+/// a [`MainEvent::TerminateWindow`](crate::MainEvent::TerminateWindow) event and
+/// the window will be terminated once the guard is dropped. Events should be
+/// handled promptly, and the guard shouldn't be held across unrelated `.await`
+/// points.
+#[derive(Debug)]
+pub struct EventGuard {
+    app: AndroidApp,
+    raw: RawPollEvent,
+}
+
+impl EventGuard {
+    /// The event that needs to be handled
+    pub fn event(&self) -> PollEvent<'_> {
+        // The stream only returns guards for events that map to a PollEvent
+        self.raw.poll_event(&self.app.inner).ok().flatten().unwrap_or(PollEvent::Error)
+    }
+
+    /// The error that's reported as a [`PollEvent::Error`], if any
+    pub fn error(&self) -> Option<&PollError> {
+        match &self.raw {
+            RawPollEvent::Error(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for EventGuard {
+    fn drop(&mut self) {
+        if let RawPollEvent::Main(cmd) = &self.raw {
+            self.app.inner.post_exec_cmd(cmd);
+        }
+        EVENT_QUEUE.with(|queue| queue.borrow_mut().in_flight = false);
+        wake_stream();
+    }
+}
+
+// Wakes up the executor's thread via the looper, except when woken from
+// the executor's own thread, where we know it's not blocked on the looper.
+struct ExecutorWaker {
+    woken: AtomicBool,
+    thread: ThreadId,
+    app_waker: AndroidAppWaker,
+}
+
+impl Wake for ExecutorWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if !self.woken.swap(true, Ordering::SeqCst) && thread::current().id() != self.thread {
+            self.app_waker.wake();
+        }
+    }
+}
+
+struct ExecutorActive;
+
+impl ExecutorActive {
+    fn enter() -> Self {
+        EVENT_QUEUE.with(|queue| {
+            let mut queue = queue.borrow_mut();
+            if queue.executor_active {
+                panic!("AndroidApp::block_on() can't be called recursively");
+            }
+            queue.executor_active = true;
+        });
+        ExecutorActive
+    }
+}
+
+impl Drop for ExecutorActive {
+    fn drop(&mut self) {
+        EVENT_QUEUE.with(|queue| queue.borrow_mut().executor_active = false);
+    }
+}
+
+impl AndroidApp {
+    /// Returns a [`Stream`] of application events
+    ///
+    /// This is an async alternative to [`AndroidApp::poll_events()`] and the stream
+    /// must be polled from within [`AndroidApp::block_on()`].
+    ///
+    /// ```ignore
+    /// # use android_activity::{AndroidApp, MainEvent, PollEvent};
+    /// # use futures::StreamExt;
+    /// fn android_main(app: AndroidApp) {
+    ///     let mut events = app.events();
+    ///     app.block_on(async {
+    ///         while let Some(guard) = events.next().await {
+    ///             if let PollEvent::Main(MainEvent::Destroy) = guard.event() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    ///
+    /// # Safety
+    /// This API must only be called from the application's main thread
+    pub fn events(&self) -> EventStream {
+        EventStream { app: self.clone() }
+    }
+
+    /// Runs a future to completion on the application's main thread
+    ///
+    /// While the future is pending this blocks on the application's looper and any
+    /// events that are read are queued for the [`EventStream`] returned by
+    /// [`AndroidApp::events()`].
+    ///
+    /// Wake ups of the future (including from other threads) are implemented via
+    /// [`AndroidAppWaker`] and are not reported as a [`PollEvent::Wake`]. Since looper
+    /// wake ups are coalesced it's possible that a wake up via [`AndroidApp::create_waker()`]
+    /// will be absorbed by the executor, or that a wake up of the executor is seen as a
+    /// spurious [`PollEvent::Wake`].
+    ///
+    /// Any events that were read but not taken from an [`EventStream`] before
+    /// the future completes will be delivered by the next call to
+    /// [`AndroidApp::poll_events()`].
+    ///
+    /// # Safety
+    /// This API must only be called from the application's main thread and it can't be
+    /// called recursively.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let _active = ExecutorActive::enter();
+
+        let executor_waker = Arc::new(ExecutorWaker {
+            woken: AtomicBool::new(true),
+            thread: thread::current().id(),
+            app_waker: self.create_waker(),
+        });
+        let waker = Waker::from(executor_waker.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+
+        loop {
+            if executor_waker.woken.swap(false, Ordering::SeqCst) {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+                continue;
+            }
+
+            match self.poll_raw(None) {
+                Some(RawPollEvent::Wake) if executor_waker.woken.load(Ordering::SeqCst) => {
+                    trace!("block_on: executor woken");
+                }
+                Some(RawPollEvent::Timeout) | None => {}
+                Some(raw) => {
+                    trace!("block_on: queueing {raw:?}");
+                    EVENT_QUEUE.with(|queue| queue.borrow_mut().pending.push_back(raw));
+                    wake_stream();
+                }
+            }
+        }
+    }
+}
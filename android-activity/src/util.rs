@@ -0,0 +1,63 @@
+use std::ffi::{CStr, CString};
+#[cfg(not(feature = "mock-activity"))]
+use std::os::raw;
+
+use log::Level;
+
+/// The maximum length of a message that's written via [`android_log()`]
+///
+/// Logcat truncates entries whose payload (including the priority, tag and
+/// message) exceeds `LOGGER_ENTRY_MAX_PAYLOAD` (4068 bytes), so this leaves some
+/// room for the tag.
+pub(crate) const MAX_LOG_MESSAGE_LEN: usize = 4000;
+
+#[cfg(not(feature = "mock-activity"))]
+pub(crate) fn android_log(level: Level, tag: &CStr, msg: &CStr) {
+    let prio = match level {
+        Level::Error => ndk_sys::android_LogPriority_ANDROID_LOG_ERROR,
+        Level::Warn => ndk_sys::android_LogPriority_ANDROID_LOG_WARN,
+        Level::Info => ndk_sys::android_LogPriority_ANDROID_LOG_INFO,
+        Level::Debug => ndk_sys::android_LogPriority_ANDROID_LOG_DEBUG,
+        Level::Trace => ndk_sys::android_LogPriority_ANDROID_LOG_VERBOSE,
+    };
+    unsafe {
+        ndk_sys::__android_log_write(prio as raw::c_int, tag.as_ptr(), msg.as_ptr());
+    }
+}
+
+// There's no logcat on the host, so messages go to stderr instead
+#[cfg(feature = "mock-activity")]
+pub(crate) fn android_log(level: Level, tag: &CStr, msg: &CStr) {
+    eprintln!("{level} {}: {}", tag.to_string_lossy(), msg.to_string_lossy());
+}
+
+/// Writes `msg` to logcat, split into multiple messages if it's too long
+///
+/// Any NUL bytes in `msg` are dropped, since logcat messages are C strings.
+pub(crate) fn android_log_str(level: Level, tag: &CStr, msg: &str) {
+    let msg = msg.replace('\0', "");
+    for chunk in split_log_message(&msg, MAX_LOG_MESSAGE_LEN) {
+        android_log(level, tag, &CString::new(chunk).unwrap());
+    }
+}
+
+/// Splits `msg` into chunks of at most `max_len` bytes, without splitting any
+/// UTF-8 characters
+///
+/// `max_len` is clamped so that every chunk contains at least one character.
+pub(crate) fn split_log_message(msg: &str, max_len: usize) -> impl Iterator<Item = &str> {
+    let max_len = max_len.max(4);
+    let mut rest = msg;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut end = rest.len().min(max_len);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
@@ -0,0 +1,202 @@
+//! Tracking of the application's window and content rect, so that window events
+//! can report what changed, along with the flags of the `Activity`'s window
+
+use bitflags::bitflags;
+use ndk::hardware_buffer_format::HardwareBufferFormat;
+
+use crate::{MainEvent, NativeWindow, NativeWindowRef, Rect};
+
+bitflags! {
+    /// Flags for [`AndroidApp::set_window_flags()`](crate::AndroidApp::set_window_flags),
+    /// as per the Java API at [`android.view.WindowManager.LayoutParams`]
+    ///
+    /// Some flags only take effect if they're set before the window's decorations
+    /// are created, as noted for [`Window.setFlags()`].
+    ///
+    /// [`android.view.WindowManager.LayoutParams`]: https://developer.android.com/reference/android/view/WindowManager.LayoutParams
+    /// [`Window.setFlags()`]: https://developer.android.com/reference/android/view/Window#setFlags(int,%20int)
+    #[derive(Default)]
+    pub struct WindowManagerFlags: u32 {
+        /// Allow the lock screen to activate while this window is visible and the
+        /// screen is on
+        const ALLOW_LOCK_WHILE_SCREEN_ON = 0x0000_0001;
+
+        /// Dim everything behind this window
+        const DIM_BEHIND = 0x0000_0002;
+
+        #[deprecated = "Blurring is no longer supported"]
+        const BLUR_BEHIND = 0x0000_0004;
+
+        /// This window never gets key input focus
+        const NOT_FOCUSABLE = 0x0000_0008;
+
+        /// This window never receives touch events
+        const NOT_TOUCHABLE = 0x0000_0010;
+
+        /// Send pointer events outside of this window to the windows behind it,
+        /// even when it's focusable
+        const NOT_TOUCH_MODAL = 0x0000_0020;
+
+        #[deprecated = "This flag has no effect"]
+        const TOUCHABLE_WHEN_WAKING = 0x0000_0040;
+
+        /// Keep the screen turned on and bright while this window is visible
+        const KEEP_SCREEN_ON = 0x0000_0080;
+
+        /// Place the window within the entire screen, ignoring decorations such as
+        /// the status bar
+        const LAYOUT_IN_SCREEN = 0x0000_0100;
+
+        /// Allow the window to extend outside of the screen
+        const LAYOUT_NO_LIMITS = 0x0000_0200;
+
+        /// Hide all screen decorations, such as the status bar, while this window
+        /// is displayed
+        const FULLSCREEN = 0x0000_0400;
+
+        /// Override [`WindowManagerFlags::FULLSCREEN`] and force the screen
+        /// decorations to be shown
+        const FORCE_NOT_FULLSCREEN = 0x0000_0800;
+
+        #[deprecated = "This flag is no longer used"]
+        const DITHER = 0x0000_1000;
+
+        /// Treat the content of the window as secure, preventing it from appearing
+        /// in screenshots or from being viewed on non-secure displays
+        const SECURE = 0x0000_2000;
+
+        /// Scale the surface when it's composited to the screen, as per the
+        /// window's layout parameters
+        const SCALED = 0x0000_4000;
+
+        /// Filter out presses that are likely to be from the user's cheek
+        const IGNORE_CHEEK_PRESSES = 0x0000_8000;
+
+        /// With [`WindowManagerFlags::LAYOUT_IN_SCREEN`], report the insets that
+        /// are needed to avoid the screen decorations
+        const LAYOUT_INSET_DECOR = 0x0001_0000;
+
+        /// Invert how [`WindowManagerFlags::NOT_FOCUSABLE`] decides whether the
+        /// window interacts with the soft keyboard (IME)
+        const ALT_FOCUSABLE_IM = 0x0002_0000;
+
+        /// With [`WindowManagerFlags::NOT_TOUCH_MODAL`], receive a single
+        /// `ACTION_OUTSIDE` event for touches outside of the window
+        const WATCH_OUTSIDE_TOUCH = 0x0004_0000;
+
+        /// Show this window when the screen is locked
+        const SHOW_WHEN_LOCKED = 0x0008_0000;
+
+        /// Show the system wallpaper behind this window
+        const SHOW_WALLPAPER = 0x0010_0000;
+
+        /// Turn the screen on once this window is shown
+        const TURN_SCREEN_ON = 0x0020_0000;
+
+        /// Dismiss the keyguard when this window is shown, if it's not a secure
+        /// keyguard
+        const DISMISS_KEYGUARD = 0x0040_0000;
+    }
+}
+
+/// The size and pixel format of a [`NativeWindow`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowInfo {
+    pub width: i32,
+    pub height: i32,
+    pub format: HardwareBufferFormat,
+}
+
+impl WindowInfo {
+    fn of(window: &NativeWindow) -> Self {
+        Self {
+            width: window.width(),
+            height: window.height(),
+            format: window.format(),
+        }
+    }
+}
+
+/// The window and content rect, as of the most recent main event, along with their
+/// previous state
+///
+/// Backends update this from their `pre_exec_cmd()` (or `post_exec_cmd()`, for a
+/// terminated window) and build the corresponding [`MainEvent`]s from it, so that
+/// each event reports the same change however many times it's built.
+#[derive(Debug, Default)]
+pub(crate) struct WindowState {
+    window: Option<NativeWindow>,
+    info: Option<WindowInfo>,
+    old_info: Option<WindowInfo>,
+    content_rect: Rect,
+    old_content_rect: Rect,
+}
+
+impl WindowState {
+    #[cfg(not(feature = "mock-activity"))]
+    pub(crate) fn with_content_rect(content_rect: Rect) -> Self {
+        Self {
+            content_rect: content_rect.clone(),
+            old_content_rect: content_rect,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn native_window(&self) -> Option<NativeWindowRef> {
+        self.window.as_ref().map(NativeWindowRef::new)
+    }
+
+    #[cfg(feature = "mock-activity")]
+    pub(crate) fn native_window_mut(&mut self) -> Option<&mut NativeWindow> {
+        self.window.as_mut()
+    }
+
+    pub(crate) fn content_rect(&self) -> Rect {
+        self.content_rect.clone()
+    }
+
+    pub(crate) fn init_window(&mut self, window: NativeWindow) {
+        self.old_info = self.info.replace(WindowInfo::of(&window));
+        self.window = Some(window);
+    }
+
+    /// Updates the window's size and format after it has been resized
+    pub(crate) fn resize_window(&mut self) {
+        if let Some(window) = &self.window {
+            self.old_info = self.info.replace(WindowInfo::of(window));
+        }
+    }
+
+    /// Forgets the window, once the application has handled its termination
+    pub(crate) fn terminate_window(&mut self) {
+        self.window = None;
+        self.info = None;
+    }
+
+    pub(crate) fn set_content_rect(&mut self, rect: Rect) {
+        self.old_content_rect = std::mem::replace(&mut self.content_rect, rect);
+    }
+
+    pub(crate) fn init_window_event<'a>(&self) -> Option<MainEvent<'a>> {
+        Some(MainEvent::InitWindow { window: self.native_window()?, info: self.info? })
+    }
+
+    pub(crate) fn terminate_window_event<'a>(&self) -> Option<MainEvent<'a>> {
+        Some(MainEvent::TerminateWindow { window: self.native_window()? })
+    }
+
+    pub(crate) fn window_resized_event<'a>(&self) -> Option<MainEvent<'a>> {
+        Some(MainEvent::WindowResized {
+            window: self.native_window()?,
+            old: self.old_info?,
+            new: self.info?,
+        })
+    }
+
+    pub(crate) fn content_rect_changed_event<'a>(&self) -> MainEvent<'a> {
+        MainEvent::ContentRectChanged {
+            old: self.old_content_rect.clone(),
+            new: self.content_rect.clone(),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "mock-activity")]
+
+use std::ffi::CString;
+use std::io::{Read, Seek, SeekFrom};
+
+use android_activity::MockActivity;
+
+#[test]
+fn assets_are_read_from_the_asset_dir() {
+    let dir = std::env::temp_dir().join(format!("android-activity-assets-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("b.txt"), b"hello").unwrap();
+    std::fs::write(dir.join("a.txt"), b"a").unwrap();
+    std::fs::write(dir.join("sub/c.txt"), b"c").unwrap();
+
+    let activity = MockActivity::new();
+    let name = |name: &str| CString::new(name).unwrap();
+    assert!(activity.app().asset_manager().open(&name("b.txt")).is_none());
+
+    activity.set_asset_dir(Some(dir.clone()));
+    let assets = activity.app().asset_manager();
+    let mut asset = assets.open(&name("b.txt")).unwrap();
+    assert_eq!(asset.get_length(), 5);
+    asset.seek(SeekFrom::Start(1)).unwrap();
+    assert_eq!(asset.get_remaining_length(), 4);
+    let mut contents = String::new();
+    asset.read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "ello");
+
+    assert!(assets.open(&name("sub/c.txt")).is_some());
+    assert!(assets.open(&name("../b.txt")).is_none());
+
+    // Only files are listed, not sub directories
+    let names = assets.open_dir(&name("")).unwrap().collect::<Vec<_>>();
+    assert_eq!(names, [name("a.txt"), name("b.txt")]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,30 @@
+#![cfg(all(feature = "mock-activity", feature = "macros"))]
+
+use std::time::Duration;
+
+use android_activity::{AndroidApp, LifecycleState, MainEvent, MockActivity, MockEvent, PollEvent};
+
+#[android_activity::main(log_filter = "debug", panic_hook = false, stdio = false)]
+fn android_main(app: AndroidApp) -> Result<(), String> {
+    let mut started = false;
+    while !started {
+        app.poll_events(Some(Duration::from_secs(2)), |event| {
+            started = matches!(event, PollEvent::Main(MainEvent::Start));
+        });
+    }
+    Err("Failed to start".to_string())
+}
+
+#[test]
+fn error_finishes_the_activity() {
+    let activity = MockActivity::new();
+    let main = activity.run_entry(&ANDROID_ACTIVITY_MAIN);
+    activity.send(MockEvent::Start);
+
+    // Finishing the activity after the error is followed by Stop and Destroy, which
+    // are handled before the thread exits
+    main.join().unwrap();
+    assert!(activity.is_finished());
+    assert_eq!(activity.app().lifecycle_state(), LifecycleState::Destroying);
+    assert_eq!(log::max_level(), log::LevelFilter::Debug);
+}
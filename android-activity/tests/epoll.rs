@@ -0,0 +1,93 @@
+//! The mock backend polls the same epoll based looper that's used by the real
+//! backends with the `epoll` feature
+#![cfg(feature = "mock-activity")]
+
+use std::time::Duration;
+#[cfg(feature = "epoll")]
+use std::time::Instant;
+
+use android_activity::{MockActivity, PollEvent};
+#[cfg(feature = "epoll")]
+use android_activity::TriggerMode;
+use ndk::looper::FdEvent;
+
+#[test]
+fn fd_events_and_wake_ups() {
+    let activity = MockActivity::new();
+    let app = activity.app();
+
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let token = app.register_fd(fds[0], FdEvent::INPUT).unwrap();
+    assert!(app.register_fd(fds[0], FdEvent::INPUT).is_err());
+
+    let mut timed_out = false;
+    app.poll_events(Some(Duration::from_millis(10)), |event| timed_out = matches!(event, PollEvent::Timeout));
+    assert!(timed_out);
+
+    assert_eq!(unsafe { libc::write(fds[1], b"x".as_ptr().cast(), 1) }, 1);
+    let mut ready = None;
+    app.poll_events(Some(Duration::from_secs(1)), |event| {
+        if let PollEvent::FdEvent { token, events, .. } = event {
+            assert!(events.contains(FdEvent::INPUT));
+            ready = Some(token);
+        }
+    });
+    assert_eq!(ready, Some(token));
+
+    app.unregister_fd(token).unwrap();
+    let mut timed_out = false;
+    app.poll_events(Some(Duration::from_millis(10)), |event| timed_out = matches!(event, PollEvent::Timeout));
+    assert!(timed_out);
+
+    let waker = app.create_waker();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        waker.wake();
+    });
+    let mut woken = false;
+    app.poll_events(None, |event| woken = matches!(event, PollEvent::Wake));
+    assert!(woken);
+
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
+
+#[cfg(feature = "epoll")]
+#[test]
+fn edge_triggered_fd_and_precise_timeout() {
+    let activity = MockActivity::new();
+    let app = activity.app();
+
+    let mut fds = [0; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let token = app.register_fd_with_trigger(fds[0], FdEvent::INPUT, TriggerMode::Edge).unwrap();
+    assert_eq!(unsafe { libc::write(fds[1], b"x".as_ptr().cast(), 1) }, 1);
+
+    // The data is never read, so a level triggered fd would be reported each time
+    let mut ready = 0;
+    for _ in 0..3 {
+        app.poll_events(Some(Duration::from_millis(20)), |event| {
+            if let PollEvent::FdEvent { token: ready_token, .. } = event {
+                assert_eq!(ready_token, token);
+                ready += 1;
+            }
+        });
+    }
+    assert_eq!(ready, 1);
+
+    let start = Instant::now();
+    let mut timed_out = false;
+    app.poll_events(Some(Duration::from_micros(1500)), |event| timed_out = matches!(event, PollEvent::Timeout));
+    let elapsed = start.elapsed();
+    assert!(timed_out);
+    assert!(elapsed >= Duration::from_micros(1500) && elapsed < Duration::from_millis(50), "{elapsed:?}");
+
+    app.unregister_fd(token).unwrap();
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "mock-activity")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use android_activity::input::{InputEvent, KeyAction, KeyEvent, Keycode, MockPointer, MotionAction, MotionEvent, Source};
+use android_activity::{InputStatus, MainEvent, MockActivity, MockEvent, PollEvent};
+
+fn describe(event: &InputEvent) -> String {
+    match event {
+        InputEvent::KeyEvent(key) => format!("{:?}", key.key_code()),
+        InputEvent::MotionEvent(motion) => format!("{:?}", motion.source()),
+        _ => "other".to_string(),
+    }
+}
+
+#[test]
+fn filtered_and_unhandled_events_are_passed_back() {
+    let activity = MockActivity::new();
+    let app = activity.app();
+
+    // Filters can be set from any thread
+    let other = app.clone();
+    std::thread::spawn(move || {
+        other.set_key_event_filter(|key| key.key_code() != Keycode::VolumeUp);
+        other.set_motion_event_filter(|motion| motion.source() == Source::Touchscreen);
+    }).join().unwrap();
+
+    for key_code in [Keycode::VolumeUp, Keycode::A] {
+        activity.send_input(InputEvent::KeyEvent(KeyEvent::new(KeyAction::Down, Source::Keyboard, key_code)));
+    }
+    for source in [Source::Mouse, Source::Touchscreen] {
+        activity.send_input(InputEvent::MotionEvent(MotionEvent::new(MotionAction::Down, source, vec![MockPointer::new(0, 1.0, 1.0)])));
+    }
+    activity.send(MockEvent::Destroy);
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let main = activity.run({
+        let seen = seen.clone();
+        move |app| {
+            let mut quit = false;
+            while !quit {
+                app.poll_events(Some(Duration::from_secs(2)), |event| {
+                    if let PollEvent::Main(MainEvent::Destroy) = event {
+                        quit = true;
+                    }
+                });
+                app.input_events(|event| {
+                    seen.lock().unwrap().push(describe(event));
+                    match event {
+                        InputEvent::KeyEvent(_) => InputStatus::Unhandled,
+                        _ => InputStatus::Handled,
+                    }
+                });
+            }
+        }
+    });
+    main.join().unwrap();
+
+    assert_eq!(*seen.lock().unwrap(), ["A", "Touchscreen"]);
+    let unhandled = activity.take_unhandled_input().iter().map(describe).collect::<Vec<_>>();
+    assert_eq!(unhandled, ["VolumeUp", "A", "Mouse"]);
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "mock-activity")]
+
+use std::time::Duration;
+
+use android_activity::{LifecycleState, MainEvent, MockActivity, MockEvent, PollEvent};
+use ndk::hardware_buffer_format::HardwareBufferFormat;
+
+#[test]
+fn lifecycle_state_follows_main_events() {
+    let activity = MockActivity::new();
+    let app = activity.app();
+    assert_eq!(app.lifecycle_state(), LifecycleState::Created);
+
+    let main = activity.run(|app| {
+        let mut states = Vec::new();
+        let mut quit = false;
+        while !quit {
+            app.poll_events(Some(Duration::from_secs(2)), |event| match event {
+                PollEvent::Main(event) => {
+                    // The state is already updated when the event is delivered
+                    match event {
+                        MainEvent::InitWindow { .. } => assert!(app.is_window_ready()),
+                        MainEvent::GainedFocus => assert!(app.has_focus()),
+                        MainEvent::LostFocus => assert!(!app.has_focus()),
+                        MainEvent::Destroy => quit = true,
+                        _ => {}
+                    }
+                    states.push(app.lifecycle_state());
+                }
+                PollEvent::Timeout => panic!("Timed out waiting for lifecycle events"),
+                _ => {}
+            });
+        }
+        states.dedup();
+        assert_eq!(states, [
+            LifecycleState::Started,
+            LifecycleState::Resumed,
+            LifecycleState::Paused,
+            LifecycleState::Stopped,
+            LifecycleState::Destroying,
+        ]);
+    });
+
+    for event in [
+        MockEvent::Start,
+        MockEvent::Resume,
+        MockEvent::InitWindow { width: 1080, height: 1920, format: HardwareBufferFormat::R8G8B8A8_UNORM },
+        MockEvent::GainedFocus,
+    ] {
+        activity.send(event);
+    }
+    assert!(activity.wait_idle(Duration::from_secs(2)));
+    assert_eq!(app.lifecycle_state(), LifecycleState::Resumed);
+    assert!(app.has_focus());
+    assert!(app.is_window_ready());
+
+    for event in [MockEvent::LostFocus, MockEvent::Pause, MockEvent::TerminateWindow, MockEvent::Stop, MockEvent::Destroy] {
+        activity.send(event);
+    }
+    main.join().unwrap();
+    assert_eq!(app.lifecycle_state(), LifecycleState::Destroying);
+    assert!(!app.has_focus());
+    assert!(!app.is_window_ready());
+}
+
+#[test]
+fn finish_from_another_thread() {
+    let activity = MockActivity::new();
+    let main = activity.run(|app| {
+        let mut events = Vec::new();
+        let mut quit = false;
+        while !quit {
+            app.poll_events(Some(Duration::from_secs(2)), |event| match event {
+                PollEvent::Main(event) => {
+                    match event {
+                        MainEvent::GainedFocus => {
+                            let other = app.clone();
+                            std::thread::spawn(move || other.finish()).join().unwrap();
+                        }
+                        MainEvent::Destroy => quit = true,
+                        _ => {}
+                    }
+                    events.push(format!("{event:?}"));
+                }
+                PollEvent::Timeout => panic!("Timed out waiting for the activity to be destroyed"),
+                _ => {}
+            });
+        }
+        let events: Vec<_> = events.iter().map(|event| event.split([' ', '{']).next().unwrap()).collect();
+        assert_eq!(events, [
+            "Start",
+            "Resume",
+            "InitWindow",
+            "GainedFocus",
+            "Pause",
+            "LostFocus",
+            "Stop",
+            "SaveState",
+            "TerminateWindow",
+            "Destroy",
+        ]);
+        assert_eq!(app.lifecycle_state(), LifecycleState::Destroying);
+        assert!(!app.has_focus());
+        assert!(!app.is_window_ready());
+    });
+
+    for event in [
+        MockEvent::Start,
+        MockEvent::Resume,
+        MockEvent::InitWindow { width: 1080, height: 1920, format: HardwareBufferFormat::R8G8B8A8_UNORM },
+        MockEvent::GainedFocus,
+    ] {
+        activity.send(event);
+    }
+    main.join().unwrap();
+    assert!(activity.is_finished());
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "mock-activity")]
+
+use std::time::Duration;
+
+use android_activity::{MainEvent, MockActivity, MockEvent, PollError, PollEvent};
+
+#[test]
+fn try_poll_events_returns_errors() {
+    let activity = MockActivity::new();
+    let app = activity.app();
+
+    activity.send_poll_error(PollError::UnknownCommand(42));
+    activity.send(MockEvent::Start);
+
+    let mut events = 0;
+    let err = app.try_poll_events(Some(Duration::from_secs(2)), |_| events += 1).unwrap_err();
+    assert!(matches!(err, PollError::UnknownCommand(42)));
+    assert_eq!(events, 0);
+
+    // Polling can continue after an error
+    let mut started = false;
+    app.try_poll_events(Some(Duration::from_secs(2)), |event| {
+        started = matches!(event, PollEvent::Main(MainEvent::Start));
+    }).unwrap();
+    assert!(started);
+    assert!(activity.wait_idle(Duration::from_secs(2)));
+}
+
+#[test]
+fn poll_events_reports_errors_to_the_callback() {
+    let activity = MockActivity::new();
+    let app = activity.app();
+
+    activity.send_poll_error(PollError::Looper);
+    let mut got_error = false;
+    app.poll_events(Some(Duration::from_secs(2)), |event| got_error = matches!(event, PollEvent::Error));
+    assert!(got_error);
+}
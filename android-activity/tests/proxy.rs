@@ -0,0 +1,66 @@
+#![cfg(feature = "mock-activity")]
+
+use std::time::Duration;
+
+use android_activity::{MainEvent, MockActivity, MockEvent, PollEvent};
+
+#[test]
+fn user_events_are_ordered() {
+    let activity = MockActivity::new();
+    let main = activity.run(|app| {
+        let numbers = app.create_proxy::<u32>();
+        let strings = app.create_proxy::<String>();
+        std::thread::spawn(move || {
+            for i in 0..100u32 {
+                numbers.send(i);
+                if i % 10 == 0 {
+                    strings.send(format!("s{i}"));
+                }
+            }
+        });
+
+        let mut received = Vec::new();
+        while received.len() < 110 {
+            app.poll_events(Some(Duration::from_secs(2)), |event| match event {
+                PollEvent::User(event) => {
+                    assert!(event.take::<u8>().is_none());
+                    match event.take::<u32>() {
+                        Some(i) => received.push(i.to_string()),
+                        None => received.push(event.take::<String>().unwrap()),
+                    }
+                    assert!(!event.is::<u32>() && !event.is::<String>());
+                }
+                PollEvent::Timeout => panic!("Timed out waiting for user events"),
+                _ => {}
+            });
+        }
+        assert_eq!(received[..3], ["0", "s0", "1"]);
+        assert_eq!(received.last().unwrap(), "99");
+    });
+    main.join().unwrap();
+}
+
+#[test]
+fn user_events_dont_starve_main_events() {
+    let activity = MockActivity::new();
+    // The Destroy command is already queued when the backlog of user events is sent
+    activity.send(MockEvent::Destroy);
+    let main = activity.run(|app| {
+        let proxy = app.create_proxy::<u32>();
+        for i in 0..1000 {
+            proxy.send(i);
+        }
+
+        let mut user_events = 0;
+        let mut quit = false;
+        while !quit {
+            app.poll_events(Some(Duration::from_secs(2)), |event| match event {
+                PollEvent::Main(MainEvent::Destroy) => quit = true,
+                PollEvent::User(_) => user_events += 1,
+                _ => {}
+            });
+        }
+        assert_eq!(user_events, 0);
+    });
+    main.join().unwrap();
+}
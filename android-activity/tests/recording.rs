@@ -0,0 +1,101 @@
+#![cfg(feature = "mock-activity")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use android_activity::input::{Axis, InputEvent, KeyAction, KeyEvent, Keycode, MockPointer, MotionAction, MotionEvent, Source};
+use android_activity::{AndroidApp, Configuration, InputStatus, MainEvent, MockActivity, MockEvent, PollEvent, Rect, Recording};
+use ndk::hardware_buffer_format::HardwareBufferFormat;
+
+/// Logs everything that's delivered to the application, along with the state
+/// that's visible while handling each main event
+fn log_events(app: AndroidApp, log: Arc<Mutex<Vec<String>>>, record: bool) {
+    if record {
+        app.start_recording().unwrap();
+    }
+
+    let mut quit = false;
+    while !quit {
+        app.poll_events(Some(Duration::from_secs(2)), |event| {
+            match &event {
+                PollEvent::Main(main_event) => {
+                    // Only the variant is logged, since some events also borrow the backend's state
+                    let debug = format!("{main_event:?}");
+                    let name = debug.split([' ', '(']).next().unwrap();
+                    let window = app.native_window().map(|window| (window.width(), window.height()));
+                    log.lock().unwrap().push(format!("{name} {window:?} {:?} {:?}", app.content_rect(), app.config().density));
+                    quit = matches!(main_event, MainEvent::Destroy);
+                }
+                PollEvent::Timeout => panic!("Timed out waiting for events"),
+                _ => {}
+            }
+            app.input_events(|event| {
+                log.lock().unwrap().push(format!("{event:?}"));
+                InputStatus::Handled
+            });
+        });
+    }
+
+    if record {
+        app.stop_recording().unwrap();
+    }
+}
+
+#[test]
+fn record_and_replay() {
+    let dir = std::env::temp_dir().join(format!("android-activity-recording-{}", std::process::id()));
+    let activity = MockActivity::new();
+    activity.set_internal_data_path(Some(dir.clone()));
+
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    let main = activity.run({
+        let log = recorded.clone();
+        move |app| log_events(app, log, true)
+    });
+
+    let config = Configuration { density: Some(420), language: Some("en".to_string()), ..Default::default() };
+    activity.send(MockEvent::Start);
+    activity.send(MockEvent::Resume);
+    activity.send(MockEvent::InitWindow { width: 100, height: 200, format: HardwareBufferFormat::R8G8B8A8_UNORM });
+    activity.send(MockEvent::ContentRectChanged(Rect { left: 1, top: 2, right: 3, bottom: 4 }));
+    activity.send(MockEvent::ConfigChanged(config));
+    assert!(activity.wait_idle(Duration::from_secs(2)));
+
+    let motion = MotionEvent::new(MotionAction::Move, Source::Touchscreen, vec![MockPointer::new(0, 1.5, 2.25).with_axis(Axis::Pressure, 0.75)])
+        .with_times(10, 20)
+        .with_history(15, vec![MockPointer::new(0, 1.0, 2.0)]);
+    activity.send_input(InputEvent::MotionEvent(motion));
+    activity.send_input(InputEvent::KeyEvent(KeyEvent::new(KeyAction::Down, Source::Keyboard, Keycode::A).with_scan_code(30, 1)));
+    activity.send(MockEvent::WindowResized { width: 50, height: 60 });
+    activity.send(MockEvent::Pause);
+    activity.send(MockEvent::Stop);
+    activity.send(MockEvent::Destroy);
+    main.join().unwrap();
+
+    let recordings = std::fs::read_dir(dir.join("recordings")).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(recordings.len(), 1);
+    let recording = Recording::load(recordings[0].path()).unwrap();
+
+    let replayed = Arc::new(Mutex::new(Vec::new()));
+    let activity = MockActivity::new();
+    let main = activity.run({
+        let log = replayed.clone();
+        move |app| log_events(app, log, false)
+    });
+    activity.replay(&recording).unwrap();
+    main.join().unwrap();
+
+    let recorded = recorded.lock().unwrap();
+    assert!(recorded.iter().any(|event| event.starts_with("KeyEvent")));
+    assert!(recorded.iter().any(|event| event.starts_with("MotionEvent")));
+    assert_eq!(*recorded, *replayed.lock().unwrap());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn parse_errors() {
+    assert!(Recording::parse("not a recording").is_err());
+    assert!(Recording::parse("android-activity-recording 99\n").is_err());
+    assert!(Recording::parse("android-activity-recording 1\n5 key action=x\n").is_err());
+}
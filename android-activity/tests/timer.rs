@@ -0,0 +1,35 @@
+#![cfg(feature = "mock-activity")]
+
+use std::time::{Duration, Instant};
+
+use android_activity::{MockActivity, PollEvent, TimerKind};
+
+#[test]
+fn interval_and_deadline_timers() {
+    let activity = MockActivity::new();
+    let main = activity.run(|app| {
+        let interval = app.add_timer(TimerKind::Interval(Duration::from_millis(10))).unwrap();
+        let deadline = app.add_timer(TimerKind::Deadline(Instant::now() + Duration::from_millis(25))).unwrap();
+
+        let (mut ticks, mut fired) = (0, 0);
+        while ticks < 5 || fired == 0 {
+            app.poll_events(Some(Duration::from_secs(2)), |event| match event {
+                PollEvent::Timer { id, .. } if id == interval => ticks += 1,
+                PollEvent::Timer { id, .. } if id == deadline => fired += 1,
+                PollEvent::Timeout => panic!("Timed out waiting for timers"),
+                _ => {}
+            });
+        }
+        // A deadline only fires once
+        assert_eq!(fired, 1);
+
+        app.remove_timer(interval).unwrap();
+        app.remove_timer(deadline).unwrap();
+        assert!(app.remove_timer(deadline).is_err());
+
+        let mut timed_out = false;
+        app.poll_events(Some(Duration::from_millis(30)), |event| timed_out = matches!(event, PollEvent::Timeout));
+        assert!(timed_out);
+    });
+    main.join().unwrap();
+}
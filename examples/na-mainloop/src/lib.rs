@@ -1,12 +1,8 @@
-use android_activity::{PollEvent, MainEvent, AndroidApp};
-use log::info;
+use android_activity::{InputStatus, PollEvent, MainEvent, AndroidApp};
+use log::{info, trace, warn};
 
-#[no_mangle]
+#[android_activity::main]
 fn android_main(app: AndroidApp) {
-    android_logger::init_once(
-        android_logger::Config::default().with_min_level(log::Level::Info)
-    );
-
     let mut quit = false;
     let mut redraw_pending = true;
     let mut render_state: Option<()> = Default::default();
@@ -15,9 +11,9 @@ fn android_main(app: AndroidApp) {
         app.poll_events(Some(std::time::Duration::from_millis(500)) /* timeout */, |event| {
             match event {
                 PollEvent::Wake => { info!("Early wake up"); },
-                PollEvent::Timeout => {
-                    info!("Timed out");
-                    // Real app would probably rely on vblank sync via graphics API...
+                PollEvent::Timeout => { info!("Timed out"); },
+                PollEvent::Frame { vsync_time_ns, .. } => {
+                    trace!("Frame callback, vsync = {vsync_time_ns}ns");
                     redraw_pending = true;
                 },
                 PollEvent::Main(main_event) => {
@@ -43,7 +39,7 @@ fn android_main(app: AndroidApp) {
                         }
                         MainEvent::WindowResized { .. } => { redraw_pending = true; },
                         MainEvent::RedrawNeeded { ..} => { redraw_pending = true; },
-                        MainEvent::LowMemory => {},
+                        MainEvent::TrimMemory { .. } => {},
 
                         MainEvent::Destroy => { quit = true },
                         _ => { /* ... */}
@@ -59,10 +55,15 @@ fn android_main(app: AndroidApp) {
                     // Handle input
                     app.input_events(|event| {
                         info!("Input Event: {event:?}");
-
+                        InputStatus::Handled
                     });
 
-                    info!("Render...");
+                    trace!("Render...");
+
+                    // Pace the next redraw to the display's vsync
+                    if let Err(err) = app.request_frame_callback() {
+                        warn!("Failed to request a frame callback: {err}");
+                    }
                 }
             }
         });
@@ -3,6 +3,8 @@ package co.realfit.nasubclassjni;
 import android.app.NativeActivity;
 import android.content.Intent;
 import android.os.Bundle;
+import android.view.View;
+import android.view.WindowInsets;
 
 public class MainActivity extends NativeActivity {
 
@@ -14,6 +16,16 @@ public class MainActivity extends NativeActivity {
     protected void onCreate(Bundle savedInstanceState) {
         super.onCreate(savedInstanceState);
 
+        // Registered by android-activity, which re-reads the insets and reports any
+        // changes as a MainEvent::InsetsChanged
+        getWindow().getDecorView().setOnApplyWindowInsetsListener(new View.OnApplyWindowInsetsListener() {
+            @Override
+            public WindowInsets onApplyWindowInsets(View view, WindowInsets insets) {
+                WindowInsets applied = view.onApplyWindowInsets(insets);
+                onWindowInsetsChangedNative();
+                return applied;
+            }
+        });
     }
 
     @Override
@@ -23,5 +35,17 @@ public class MainActivity extends NativeActivity {
         notifyOnNewIntent();
     }
 
+    @Override
+    public void onTrimMemory(int level) {
+        super.onTrimMemory(level);
+
+        // Registered by android-activity, which reports it as a MainEvent::TrimMemory
+        onTrimMemoryNative(level);
+    }
+
     private native void notifyOnNewIntent();
+
+    private native void onTrimMemoryNative(int level);
+
+    private native void onWindowInsetsChangedNative();
 }
@@ -1,6 +1,5 @@
 
-use android_activity::{PollEvent, MainEvent, AndroidApp};
-use log::Level;
+use android_activity::{InputStatus, PollEvent, MainEvent, AndroidApp};
 use log::{trace, info};
 use std::time::Duration;
 use serde::{Serialize, Deserialize};
@@ -10,12 +9,8 @@ struct AppState {
     uri: String,
 }
 
-#[no_mangle]
+#[android_activity::main]
 fn android_main(app: AndroidApp) {
-    android_logger::init_once(
-        android_logger::Config::default().with_min_level(Level::Info)
-    );
-
     let mut quit = false;
     let mut redraw_pending = true;
     let mut render_state: Option<()> = Default::default();
@@ -52,7 +47,12 @@ fn android_main(app: AndroidApp) {
                         }
                         MainEvent::WindowResized { .. } => { redraw_pending = true; },
                         MainEvent::RedrawNeeded { ..} => { redraw_pending = true; },
-                        MainEvent::LowMemory => {},
+                        MainEvent::TrimMemory { level, .. } => { info!("Trim memory level = {level:?}"); },
+                        MainEvent::InsetsChanged { changed, .. } => {
+                            for ty in changed {
+                                info!("{ty:?} insets = {:?}", app.window_insets(ty));
+                            }
+                        },
 
                         MainEvent::Destroy => { quit = true },
                         _ => { /* ... */}
@@ -69,7 +69,7 @@ fn android_main(app: AndroidApp) {
                     // Handle input
                     app.input_events(|event| {
                         info!("Input Event: {event:?}");
-
+                        InputStatus::Handled
                     });
 
                     // Render...